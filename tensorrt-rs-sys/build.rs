@@ -1,5 +1,6 @@
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 fn find_dir(
     env_key: &'static str,
@@ -22,6 +23,26 @@ fn find_dir(
     }
 }
 
+fn tensorrt_major_version(include_dir: &Path) -> Option<u32> {
+    let header = fs::read_to_string(include_dir.join("NvInferVersion.h")).ok()?;
+    let define = |name: &str| {
+        header.lines().find_map(|line| {
+            let mut tokens = line.split_whitespace();
+            match (tokens.next(), tokens.next(), tokens.next()) {
+                (Some("#define"), Some(key), Some(value)) if key == name => Some(value.to_string()),
+                _ => None,
+            }
+        })
+    };
+
+    // newer headers alias the version to e.g. TRT_MAJOR_ENTERPRISE
+    let major = define("NV_TENSORRT_MAJOR")?;
+    match major.parse() {
+        Ok(major) => Some(major),
+        Err(_) => define(&major)?.parse().ok(),
+    }
+}
+
 fn main() {
     let cuda_include_dir = find_dir(
         "CUDA_INCLUDE_PATH",
@@ -41,17 +62,55 @@ fn main() {
         "libnvinfer.so",
     ).expect("Could not find TensorRT library path");
 
-    let include_files = vec![
+    // the versions the code checks for, and the one actually emitted
+    let major = tensorrt_major_version(&tensorrt_include_dir);
+    let mut majors = vec![8, 10];
+    majors.extend(major.filter(|major| !majors.contains(major)));
+    let values = majors
+        .iter()
+        .map(|major| format!("\"{}\"", major))
+        .collect::<Vec<_>>()
+        .join(", ");
+    println!("cargo:rustc-check-cfg=cfg(trt_major, values({}))", values);
+    if let Some(major) = major {
+        println!("cargo:rustc-cfg=trt_major=\"{}\"", major);
+    }
+
+    let onnx = env::var_os("CARGO_FEATURE_ONNX").is_some();
+
+    let mut include_files = vec![
+        "cxx/include/algorithm.h",
+        "cxx/include/allocator.h",
+        "cxx/include/builder.h",
+        "cxx/include/calibrator.h",
+        "cxx/include/device.h",
         "cxx/include/logger.h",
+        "cxx/include/plugin.h",
+        "cxx/include/profiler.h",
+        "cxx/include/progress.h",
+        "cxx/include/recorder.h",
+        "cxx/include/refitter.h",
         "cxx/include/runtime.h"
     ];
     let cpp_files = vec![
+        "cxx/src/algorithm.cpp",
+        "cxx/src/allocator.cpp",
+        "cxx/src/builder.cpp",
+        "cxx/src/calibrator.cpp",
         "cxx/src/logger.cpp",
+        "cxx/src/plugin.cpp",
+        "cxx/src/profiler.cpp",
+        "cxx/src/progress.cpp",
+        "cxx/src/recorder.cpp",
         "cxx/src/runtime.cpp"
     ];
-    let rust_files = vec![
+    let mut rust_files = vec![
         "src/lib.rs",
     ];
+    if onnx {
+        include_files.push("cxx/include/parser.h");
+        rust_files.push("src/parser.rs");
+    }
 
     cxx_build::bridges(&rust_files)
         .include(cuda_include_dir)
@@ -64,15 +123,20 @@ fn main() {
 
     println!("cargo:rustc-link-search={}", tensorrt_library_dir.to_string_lossy());
 
-    let libraries = vec![
+    let mut libraries = vec![
         "nvinfer",
         "nvinfer_plugin",
         "nvparsers",
     ];
+    if onnx {
+        libraries.push("nvonnxparser");
+    }
 
     for library in libraries {
         println!("cargo:rustc-link-lib={}", library);
     }
+    // dlopen, used to tell why a plugin library fails to load
+    println!("cargo:rustc-link-lib=dl");
 
     for file in include_files {
         println!("cargo:rerun-if-changed={}", file);
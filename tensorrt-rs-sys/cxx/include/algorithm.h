@@ -0,0 +1,15 @@
+#pragma once
+
+#include <memory>
+#include <NvInfer.h>
+#include "rust/cxx.h"
+
+namespace trt_rs::algorithm {
+
+struct RustAlgorithmSelector;
+
+// An `IAlgorithmSelector` forwarding to a Rust `AlgorithmSelector`.
+std::unique_ptr<nvinfer1::IAlgorithmSelector> create_algorithm_selector(
+    rust::Box<RustAlgorithmSelector> selector) noexcept;
+
+} // namespace trt_rs::algorithm
@@ -0,0 +1,16 @@
+#pragma once
+
+#include <memory>
+#include <NvInferRuntime.h>
+#include "rust/cxx.h"
+
+namespace trt_rs::allocator {
+
+struct RustGpuAllocator;
+
+// An `IGpuAllocator` forwarding to a Rust `GpuAllocator`. It is shared with
+// everything that frees memory through it, e.g. engines outliving the runtime.
+std::shared_ptr<nvinfer1::IGpuAllocator> create_gpu_allocator(
+    rust::Box<RustGpuAllocator> allocator) noexcept;
+
+} // namespace trt_rs::allocator
@@ -0,0 +1,313 @@
+#pragma once
+
+#include <memory>
+#include <vector>
+#include <NvInfer.h>
+#include "rust/cxx.h"
+#include "algorithm.h"
+#include "calibrator.h"
+#include "logger.h"
+#include "progress.h"
+#include "runtime.h"
+
+namespace trt_rs::builder {
+
+using nvinfer1::IBuilder;
+using nvinfer1::IBuilderConfig;
+using nvinfer1::INetworkDefinition;
+using nvinfer1::ITensor;
+using nvinfer1::ITimingCache;
+using logger::Logger;
+using runtime::HostMemory;
+
+// The Rust `BuilderFlag` values, which are this crate's own: TensorRT 10
+// renumbered its flags, e.g. kREFIT went from 5 to 4.
+inline nvinfer1::BuilderFlag to_builder_flag(int32_t flag) noexcept {
+    switch (flag) {
+    case 0:
+        return nvinfer1::BuilderFlag::kFP16;
+    case 1:
+        return nvinfer1::BuilderFlag::kINT8;
+    case 2:
+    default:
+        return nvinfer1::BuilderFlag::kREFIT;
+    }
+}
+
+// Layers are added through const methods, so the Rust side can take `&self`
+// and tie the tensors it hands out to a shared borrow of the network.
+class NetworkDefinition {
+public:
+    NetworkDefinition(std::unique_ptr<INetworkDefinition> network) : network_(std::move(network)) {}
+
+    std::size_t add_input(
+        rust::Str name, int32_t dtype, rust::Slice<const int32_t> dims) const noexcept;
+
+    std::size_t add_identity(std::size_t input) const noexcept;
+
+    std::size_t add_non_zero(std::size_t input) const noexcept;
+
+    std::size_t add_constant(
+        rust::Slice<const int32_t> dims, rust::Slice<const float> weights) const noexcept;
+
+    std::size_t add_convolution(
+        rust::Str name,
+        std::size_t input,
+        int32_t nb_output_maps,
+        rust::Slice<const int32_t> kernel_size,
+        rust::Slice<const float> kernel,
+        rust::Slice<const float> bias) const noexcept;
+
+    void mark_output(std::size_t tensor) const noexcept {
+        network_->markOutput(*reinterpret_cast<ITensor*>(tensor));
+    }
+
+    void unmark_output(std::size_t tensor) const noexcept {
+        network_->unmarkOutput(*reinterpret_cast<ITensor*>(tensor));
+    }
+
+    int32_t get_nb_layers() const noexcept {
+        return network_->getNbLayers();
+    }
+
+    int32_t get_nb_inputs() const noexcept {
+        return network_->getNbInputs();
+    }
+
+    std::size_t get_input(int32_t index) const noexcept {
+        return reinterpret_cast<std::size_t>(network_->getInput(index));
+    }
+
+    int32_t get_nb_outputs() const noexcept {
+        return network_->getNbOutputs();
+    }
+
+    std::size_t get_output(int32_t index) const noexcept {
+        return reinterpret_cast<std::size_t>(network_->getOutput(index));
+    }
+
+    void set_tensor_name(std::size_t tensor, rust::Str name) const noexcept {
+        const auto name_str = std::string(name);
+        reinterpret_cast<ITensor*>(tensor)->setName(name_str.c_str());
+    }
+
+    rust::String get_tensor_name(std::size_t tensor) const noexcept {
+        return rust::String::lossy(reinterpret_cast<ITensor*>(tensor)->getName());
+    }
+
+    rust::Vec<int32_t> get_tensor_dimensions(std::size_t tensor) const noexcept;
+
+    int32_t get_tensor_dtype(std::size_t tensor) const noexcept {
+        return static_cast<int32_t>(reinterpret_cast<ITensor*>(tensor)->getType());
+    }
+
+    void set_tensor_allowed_formats(std::size_t tensor, uint32_t formats) const noexcept {
+        reinterpret_cast<ITensor*>(tensor)->setAllowedFormats(
+            static_cast<nvinfer1::TensorFormats>(formats));
+    }
+
+    bool is_network_input(std::size_t tensor) const noexcept {
+        return reinterpret_cast<ITensor*>(tensor)->isNetworkInput();
+    }
+
+    bool is_network_output(std::size_t tensor) const noexcept {
+        return reinterpret_cast<ITensor*>(tensor)->isNetworkOutput();
+    }
+
+    bool get_flag(int32_t flag) const noexcept {
+        return network_->getFlag(static_cast<nvinfer1::NetworkDefinitionCreationFlag>(flag));
+    }
+
+    INetworkDefinition& get() noexcept {
+        return *network_;
+    }
+private:
+    std::unique_ptr<INetworkDefinition> network_;
+    // TensorRT only references layer weights, so they live as long as the network
+    mutable std::vector<std::vector<float>> weights_;
+};
+
+// Shared with the configs it is set on, which only reference it.
+class TimingCache {
+public:
+    TimingCache(std::shared_ptr<ITimingCache> cache) : cache_(std::move(cache)) {}
+
+    std::unique_ptr<HostMemory> serialize() const noexcept {
+        auto memory = cache_->serialize();
+        if (!memory) {
+            return nullptr;
+        }
+        return std::make_unique<HostMemory>(std::unique_ptr<nvinfer1::IHostMemory>(memory));
+    }
+
+    bool combine(const TimingCache& other, bool ignore_mismatch) noexcept {
+        return cache_->combine(*other.cache_, ignore_mismatch);
+    }
+
+    bool reset() noexcept {
+        return cache_->reset();
+    }
+
+    const std::shared_ptr<ITimingCache>& get() const noexcept {
+        return cache_;
+    }
+private:
+    std::shared_ptr<ITimingCache> cache_;
+};
+
+class BuilderConfig {
+public:
+    BuilderConfig(std::unique_ptr<IBuilderConfig> config) : config_(std::move(config)) {}
+
+    void set_memory_pool_limit(int32_t pool, std::size_t size) noexcept {
+        config_->setMemoryPoolLimit(static_cast<nvinfer1::MemoryPoolType>(pool), size);
+    }
+
+    std::size_t get_memory_pool_limit(int32_t pool) const noexcept {
+        return config_->getMemoryPoolLimit(static_cast<nvinfer1::MemoryPoolType>(pool));
+    }
+
+    void set_builder_optimization_level(int32_t level) noexcept {
+        config_->setBuilderOptimizationLevel(level);
+    }
+
+    int32_t get_builder_optimization_level() const noexcept {
+        return config_->getBuilderOptimizationLevel();
+    }
+
+    void set_max_aux_streams(int32_t nb_streams) noexcept {
+        config_->setMaxAuxStreams(nb_streams);
+    }
+
+    int32_t get_max_aux_streams() const noexcept {
+        return config_->getMaxAuxStreams();
+    }
+
+    void set_flag(int32_t flag) noexcept {
+        config_->setFlag(to_builder_flag(flag));
+    }
+
+    void clear_flag(int32_t flag) noexcept {
+        config_->clearFlag(to_builder_flag(flag));
+    }
+
+    bool get_flag(int32_t flag) const noexcept {
+        return config_->getFlag(to_builder_flag(flag));
+    }
+
+    void set_profiling_verbosity(int32_t verbosity) noexcept {
+        config_->setProfilingVerbosity(static_cast<nvinfer1::ProfilingVerbosity>(verbosity));
+    }
+
+    int32_t get_profiling_verbosity() const noexcept {
+        return static_cast<int32_t>(config_->getProfilingVerbosity());
+    }
+
+    void set_default_device_type(int32_t device_type) noexcept {
+        config_->setDefaultDeviceType(static_cast<nvinfer1::DeviceType>(device_type));
+    }
+
+    int32_t get_default_device_type() const noexcept {
+        return static_cast<int32_t>(config_->getDefaultDeviceType());
+    }
+
+    int32_t add_optimization_profile(std::size_t profile) noexcept {
+        return config_->addOptimizationProfile(reinterpret_cast<nvinfer1::IOptimizationProfile*>(profile));
+    }
+
+    void set_int8_calibrator(rust::Box<calibrator::RustCalibrator> calibrator) noexcept;
+
+    bool has_int8_calibrator() const noexcept {
+        return config_->getInt8Calibrator() != nullptr;
+    }
+
+    void set_algorithm_selector(rust::Box<algorithm::RustAlgorithmSelector> selector) noexcept;
+
+    bool has_algorithm_selector() const noexcept {
+        return config_->getAlgorithmSelector() != nullptr;
+    }
+
+    bool set_progress_monitor(rust::Box<progress::RustProgressMonitor> monitor) noexcept;
+
+    bool has_progress_monitor() const noexcept {
+#if NV_TENSORRT_MAJOR >= 10
+        return config_->getProgressMonitor() != nullptr;
+#else
+        return false;
+#endif
+    }
+
+    std::unique_ptr<TimingCache> create_timing_cache(rust::Slice<const uint8_t> blob) const noexcept {
+        auto cache = config_->createTimingCache(blob.data(), blob.size());
+        if (!cache) {
+            return nullptr;
+        }
+        return std::make_unique<TimingCache>(std::shared_ptr<ITimingCache>(cache));
+    }
+
+    bool set_timing_cache(const TimingCache& cache, bool ignore_mismatch) noexcept {
+        if (!config_->setTimingCache(*cache.get(), ignore_mismatch)) {
+            return false;
+        }
+        timing_cache_ = cache.get();
+        return true;
+    }
+
+    bool has_timing_cache() const noexcept {
+        return config_->getTimingCache() != nullptr;
+    }
+
+    // Shares the cache set with `set_timing_cache`, or null if there is none.
+    std::unique_ptr<TimingCache> get_timing_cache() const noexcept {
+        if (!timing_cache_) {
+            return nullptr;
+        }
+        return std::make_unique<TimingCache>(timing_cache_);
+    }
+
+    IBuilderConfig& get() noexcept {
+        return *config_;
+    }
+private:
+    // the config only references the calibrator, the algorithm selector,
+    // the progress monitor and the timing cache, so they are declared first
+    std::unique_ptr<nvinfer1::IInt8Calibrator> calibrator_;
+    std::unique_ptr<nvinfer1::IAlgorithmSelector> algorithm_selector_;
+#if NV_TENSORRT_MAJOR >= 10
+    std::unique_ptr<nvinfer1::IProgressMonitor> progress_monitor_;
+#endif
+    std::shared_ptr<ITimingCache> timing_cache_;
+    std::unique_ptr<IBuilderConfig> config_;
+};
+
+class Builder {
+public:
+    Builder(std::unique_ptr<IBuilder> builder) : builder_(std::move(builder)) {}
+
+    std::unique_ptr<NetworkDefinition> create_network() noexcept;
+
+    std::unique_ptr<NetworkDefinition> create_network_v2(uint32_t flags) noexcept;
+
+    std::unique_ptr<BuilderConfig> create_builder_config() noexcept;
+
+    // const, so that several profiles can borrow the builder at once; they
+    // are owned by the IBuilder, not by this wrapper
+    std::size_t create_optimization_profile() const noexcept {
+        return reinterpret_cast<std::size_t>(builder_->createOptimizationProfile());
+    }
+
+    std::unique_ptr<HostMemory> build_serialized_network(
+        NetworkDefinition& network, BuilderConfig& config) noexcept;
+
+    void set_gpu_allocator(rust::Box<allocator::RustGpuAllocator> allocator) noexcept;
+private:
+    std::shared_ptr<nvinfer1::IGpuAllocator> allocator_;
+    std::unique_ptr<IBuilder> builder_;
+};
+
+std::unique_ptr<Builder> create_builder(Logger& logger);
+
+bool set_profile_dimensions(
+    std::size_t profile, rust::Str name, int32_t select, rust::Slice<const int32_t> dims) noexcept;
+
+} // namespace trt_rs::builder
@@ -0,0 +1,15 @@
+#pragma once
+
+#include <memory>
+#include <NvInfer.h>
+#include "rust/cxx.h"
+
+namespace trt_rs::calibrator {
+
+struct RustCalibrator;
+
+// An `IInt8EntropyCalibrator2` forwarding to a Rust `Int8Calibrator`.
+std::unique_ptr<nvinfer1::IInt8Calibrator> create_int8_calibrator(
+    rust::Box<RustCalibrator> calibrator) noexcept;
+
+} // namespace trt_rs::calibrator
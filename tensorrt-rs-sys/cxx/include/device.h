@@ -0,0 +1,83 @@
+#pragma once
+
+#include <cuda.h>
+#include "rust/cxx.h"
+
+namespace trt_rs::device {
+
+// Returns major * 10 + minor, or -1 if the device can't be queried.
+inline int32_t get_compute_capability(int32_t device) noexcept {
+    CUdevice dev;
+    if (cuDeviceGet(&dev, device) != CUDA_SUCCESS) {
+        return -1;
+    }
+    int major = 0;
+    int minor = 0;
+    if (cuDeviceGetAttribute(&major, CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR, dev) != CUDA_SUCCESS ||
+        cuDeviceGetAttribute(&minor, CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR, dev) != CUDA_SUCCESS) {
+        return -1;
+    }
+    return major * 10 + minor;
+}
+
+// Returns the device of the current context, or -1 if there is none.
+inline int32_t get_current_device() noexcept {
+    CUdevice dev;
+    if (cuCtxGetDevice(&dev) != CUDA_SUCCESS) {
+        return -1;
+    }
+    return static_cast<int32_t>(dev);
+}
+
+// Releases memory cached by the device's default stream-ordered pool (which
+// serves cuMemAllocAsync) until at most `min_bytes_to_keep` remain reserved.
+inline bool trim_default_mem_pool(int32_t device, std::size_t min_bytes_to_keep) noexcept {
+    CUdevice dev;
+    CUmemoryPool pool;
+    if (cuDeviceGet(&dev, device) != CUDA_SUCCESS ||
+        cuDeviceGetDefaultMemPool(&pool, dev) != CUDA_SUCCESS) {
+        return false;
+    }
+    return cuMemPoolTrimTo(pool, min_bytes_to_keep) == CUDA_SUCCESS;
+}
+
+// Returns {least, greatest} for the current context, or nothing if there is none.
+inline rust::Vec<int32_t> get_stream_priority_range() noexcept {
+    int least = 0;
+    int greatest = 0;
+    auto range = rust::Vec<int32_t>();
+    if (cuCtxGetStreamPriorityRange(&least, &greatest) == CUDA_SUCCESS) {
+        range.push_back(least);
+        range.push_back(greatest);
+    }
+    return range;
+}
+
+// Returns the created stream, or 0 if it can't be created.
+inline std::size_t create_stream(bool non_blocking, int32_t priority) noexcept {
+    CUstream stream;
+    const unsigned int flags = non_blocking ? CU_STREAM_NON_BLOCKING : CU_STREAM_DEFAULT;
+    if (cuStreamCreateWithPriority(&stream, flags, priority) != CUDA_SUCCESS) {
+        return 0;
+    }
+    return reinterpret_cast<std::size_t>(stream);
+}
+
+inline void destroy_stream(std::size_t stream) noexcept {
+    cuStreamDestroy(reinterpret_cast<CUstream>(stream));
+}
+
+// Returns memory from the current context, or 0 if it can't be allocated.
+inline std::size_t mem_alloc(std::size_t size) noexcept {
+    CUdeviceptr ptr;
+    if (cuMemAlloc(&ptr, size) != CUDA_SUCCESS) {
+        return 0;
+    }
+    return static_cast<std::size_t>(ptr);
+}
+
+inline bool mem_free(std::size_t ptr) noexcept {
+    return cuMemFree(static_cast<CUdeviceptr>(ptr)) == CUDA_SUCCESS;
+}
+
+} // namespace trt_rs::device
@@ -1,7 +1,9 @@
 #pragma once
 
 #include <memory>
+#include <mutex>
 #include <string>
+#include <vector>
 #include <NvInferRuntime.h>
 #include "rust/cxx.h"
 
@@ -9,6 +11,11 @@ namespace trt_rs::logger {
 
 using nvinfer1::ILogger;
 
+struct InternalErrorHandler;
+struct InternalErrorHandlerHolder;
+struct LogCallback;
+struct LogCallbackHolder;
+
 class Logger : public ILogger {
 public:
     void log(Severity severity, const char* msg) noexcept override;
@@ -16,6 +23,32 @@ public:
     void log(int32_t severity, rust::Str msg) noexcept;
 
     void set_level(int32_t severity) noexcept;
+
+    int32_t get_level() const noexcept {
+        return static_cast<int32_t>(level_);
+    }
+
+    void set_capture(bool capture) noexcept;
+
+    rust::Vec<rust::String> take_captured() noexcept;
+
+    rust::String take_last_error() noexcept;
+
+    void set_internal_error_handler(rust::Box<InternalErrorHandler> handler) noexcept;
+
+    void set_callback(rust::Box<LogCallback> callback) noexcept;
+private:
+    Severity level_ = Severity::kINFO;
+    // TensorRT may log from its own threads
+    std::mutex capture_mutex_;
+    bool capture_ = false;
+    std::vector<std::string> captured_;
+    std::string last_error_;
+    // shared, so a message can run the handler outside the lock while it is replaced
+    std::mutex handler_mutex_;
+    std::shared_ptr<InternalErrorHandlerHolder> internal_error_handler_;
+    // replaces the spdlog sink once set
+    std::shared_ptr<LogCallbackHolder> callback_;
 };
 
 std::unique_ptr<Logger> create_logger();
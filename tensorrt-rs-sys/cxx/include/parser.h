@@ -0,0 +1,59 @@
+#pragma once
+
+#include <memory>
+#include <string>
+#include <NvOnnxParser.h>
+#include "rust/cxx.h"
+#include "builder.h"
+#include "logger.h"
+
+namespace trt_rs::parser {
+
+using nvonnxparser::IParser;
+using builder::NetworkDefinition;
+using logger::Logger;
+
+class Parser {
+public:
+    Parser(std::unique_ptr<IParser> parser) : parser_(std::move(parser)) {}
+
+    bool parse(rust::Slice<const uint8_t> data) noexcept {
+        return parser_->parse(data.data(), data.size());
+    }
+
+    bool parse_from_file(rust::Str path, int32_t verbosity) noexcept {
+        const auto path_str = std::string(path);
+        return parser_->parseFromFile(path_str.c_str(), verbosity);
+    }
+
+    int32_t get_nb_errors() const noexcept {
+        return parser_->getNbErrors();
+    }
+
+    rust::String get_error(int32_t index) const noexcept {
+        const auto error = parser_->getError(index);
+        if (!error) {
+            return rust::String();
+        }
+        return rust::String(
+            "node " + std::to_string(error->node()) + " (" + nvonnxparser::errorCodeStr(error->code())
+            + "): " + error->desc());
+    }
+
+    void clear_errors() noexcept {
+        parser_->clearErrors();
+    }
+private:
+    std::unique_ptr<IParser> parser_;
+};
+
+inline std::unique_ptr<Parser> create_parser(NetworkDefinition& network, Logger& logger) noexcept {
+    auto parser = nvonnxparser::createParser(network.get(), logger);
+    if (!parser) {
+        return nullptr;
+    } else {
+        return std::make_unique<Parser>(std::unique_ptr<IParser>(parser));
+    }
+}
+
+} // namespace trt_rs::parser
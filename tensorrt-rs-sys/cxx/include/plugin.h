@@ -1,17 +1,41 @@
 #pragma once
 
 #include <memory>
+#include <dlfcn.h>
 #include <NvInferRuntime.h>
+#include <NvInferPlugin.h>
 #include "rust/cxx.h"
+#include "logger.h"
 
 namespace trt_rs::plugin {
 
 using nvinfer1::IPluginRegistry;
+using logger::Logger;
 
-inline size_t load_library(rust::Str plugin_path) noexcept {
+struct RustPluginCreator;
+
+// TensorRT only logs why `loadLibrary` failed, so the library is opened here
+// first to read `dlerror` before anything else can clear it; the registry's
+// own `dlopen` then only takes another reference. On failure `error` holds the
+// reason and 0 is returned.
+inline size_t load_library(rust::Str plugin_path, rust::String& error) noexcept {
     const auto path = std::string(plugin_path);
-    const auto handle = getPluginRegistry()->loadLibrary(path.c_str());
-    return reinterpret_cast<size_t>(handle);
+    auto handle = dlopen(path.c_str(), RTLD_LAZY | RTLD_LOCAL);
+    if (!handle) {
+        const auto msg = dlerror();
+        error = rust::String(msg ? msg : "dlopen failed");
+        return 0;
+    }
+    const auto library = getPluginRegistry()->loadLibrary(path.c_str());
+    if (!library) {
+        const bool has_creators =
+            dlsym(handle, "getPluginCreators") != nullptr || dlsym(handle, "getCreators") != nullptr;
+        error = rust::String(has_creators
+            ? "rejected by the plugin registry"
+            : "library exports neither getPluginCreators nor getCreators");
+    }
+    dlclose(handle);
+    return reinterpret_cast<size_t>(library);
 }
 
 inline void unload_library(size_t handle) noexcept {
@@ -19,4 +43,17 @@ inline void unload_library(size_t handle) noexcept {
         reinterpret_cast<IPluginRegistry::PluginLibraryHandle>(handle));
 }
 
+inline bool init_lib_nvinfer_plugins(Logger& logger, rust::Str plugin_namespace) noexcept {
+    const auto ns = std::string(plugin_namespace);
+    return initLibNvInferPlugins(&logger, ns.c_str());
+}
+
+bool register_plugin_creator(
+    rust::Str name,
+    rust::Str version,
+    rust::Str plugin_namespace,
+    rust::Box<RustPluginCreator> creator) noexcept;
+
+rust::Vec<rust::String> list_creators() noexcept;
+
 } // namespace trt_rs::plugin
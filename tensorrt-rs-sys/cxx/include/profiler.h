@@ -0,0 +1,14 @@
+#pragma once
+
+#include <memory>
+#include <NvInferRuntime.h>
+#include "rust/cxx.h"
+
+namespace trt_rs::profiler {
+
+struct RustProfiler;
+
+// An `IProfiler` forwarding to a Rust `Profiler`.
+std::unique_ptr<nvinfer1::IProfiler> create_profiler(rust::Box<RustProfiler> profiler) noexcept;
+
+} // namespace trt_rs::profiler
@@ -0,0 +1,17 @@
+#pragma once
+
+#include <memory>
+#include <NvInfer.h>
+#include "rust/cxx.h"
+
+namespace trt_rs::progress {
+
+struct RustProgressMonitor;
+
+#if NV_TENSORRT_MAJOR >= 10
+// An `IProgressMonitor` forwarding to a Rust `ProgressMonitor`.
+std::unique_ptr<nvinfer1::IProgressMonitor> create_progress_monitor(
+    rust::Box<RustProgressMonitor> monitor) noexcept;
+#endif
+
+} // namespace trt_rs::progress
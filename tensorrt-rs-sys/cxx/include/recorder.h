@@ -0,0 +1,96 @@
+#pragma once
+
+#include <memory>
+#include <mutex>
+#include <string>
+#include <utility>
+#include <vector>
+#include <NvInferRuntime.h>
+#include "rust/cxx.h"
+
+namespace trt_rs::recorder {
+
+using nvinfer1::ErrorCode;
+using nvinfer1::IErrorRecorder;
+
+struct RecordedError;
+
+// Keeps the errors TensorRT reports until they are taken, dropping the ones
+// past `kMAX_ERRORS`, and reports each to `forward` as well if set. TensorRT
+// may report from its own threads.
+class ErrorRecorderImpl : public IErrorRecorder {
+public:
+    static constexpr std::size_t kMAX_ERRORS = 64;
+
+    explicit ErrorRecorderImpl(std::shared_ptr<ErrorRecorderImpl> forward = nullptr)
+        : forward_(std::move(forward)) {}
+
+    int32_t getNbErrors() const noexcept override;
+
+    ErrorCode getErrorCode(int32_t errorIdx) const noexcept override;
+
+    ErrorDesc getErrorDesc(int32_t errorIdx) const noexcept override;
+
+    bool hasOverflowed() const noexcept override;
+
+    void clear() noexcept override;
+
+    bool reportError(ErrorCode val, ErrorDesc desc) noexcept override;
+
+    // the shared_ptrs holding the recorder decide its lifetime
+    RefCount incRefCount() noexcept override {
+        return 1;
+    }
+
+    RefCount decRefCount() noexcept override {
+        return 1;
+    }
+
+    // Returns the recorded errors and clears them.
+    std::vector<std::pair<ErrorCode, std::string>> take() noexcept;
+private:
+    mutable std::mutex mutex_;
+    std::vector<std::pair<ErrorCode, std::string>> errors_;
+    bool overflowed_ = false;
+    const std::shared_ptr<ErrorRecorderImpl> forward_;
+};
+
+// Shared with the runtimes, engines and contexts it is set on.
+class ErrorRecorder {
+public:
+    ErrorRecorder(std::shared_ptr<ErrorRecorderImpl> recorder) : recorder_(std::move(recorder)) {}
+
+    int32_t get_nb_errors() const noexcept {
+        return recorder_->getNbErrors();
+    }
+
+    bool has_overflowed() const noexcept {
+        return recorder_->hasOverflowed();
+    }
+
+    // const, like the getters: the recorder guards its errors itself
+    void clear() const noexcept {
+        recorder_->clear();
+    }
+
+    rust::Vec<RecordedError> take_errors() const noexcept;
+
+    std::unique_ptr<ErrorRecorder> clone() const noexcept {
+        return std::make_unique<ErrorRecorder>(recorder_);
+    }
+
+    // A new recorder with errors of its own, which are also reported to this one.
+    std::unique_ptr<ErrorRecorder> forwarding() const noexcept {
+        return std::make_unique<ErrorRecorder>(std::make_shared<ErrorRecorderImpl>(recorder_));
+    }
+
+    const std::shared_ptr<ErrorRecorderImpl>& get() const noexcept {
+        return recorder_;
+    }
+private:
+    std::shared_ptr<ErrorRecorderImpl> recorder_;
+};
+
+std::unique_ptr<ErrorRecorder> create_error_recorder() noexcept;
+
+} // namespace trt_rs::recorder
@@ -0,0 +1,121 @@
+#pragma once
+
+#include <memory>
+#include <vector>
+#include <NvInferRuntime.h>
+#include "rust/cxx.h"
+#include "logger.h"
+#include "runtime.h"
+
+namespace trt_rs::refitter {
+
+using nvinfer1::IRefitter;
+using logger::Logger;
+using runtime::CudaEngine;
+
+class Refitter {
+public:
+    Refitter(std::unique_ptr<IRefitter> refitter) : refitter_(std::move(refitter)) {}
+
+    bool set_weights(
+        rust::Str layer_name,
+        int32_t role,
+        int32_t dtype,
+        rust::Slice<const uint8_t> data,
+        int64_t count) noexcept {
+        const auto name = std::string(layer_name);
+        weights_.emplace_back(data.begin(), data.end());
+        const auto& values = weights_.back();
+        const nvinfer1::Weights weights{static_cast<nvinfer1::DataType>(dtype), values.data(), count};
+        return refitter_->setWeights(
+            name.c_str(), static_cast<nvinfer1::WeightsRole>(role), weights);
+    }
+
+    bool set_named_weights(
+        rust::Str name,
+        int32_t dtype,
+        rust::Slice<const uint8_t> data,
+        int64_t count) noexcept {
+#if NV_TENSORRT_MAJOR > 8 || (NV_TENSORRT_MAJOR == 8 && NV_TENSORRT_MINOR >= 5)
+        const auto name_str = std::string(name);
+        weights_.emplace_back(data.begin(), data.end());
+        const auto& values = weights_.back();
+        const nvinfer1::Weights weights{static_cast<nvinfer1::DataType>(dtype), values.data(), count};
+        return refitter_->setNamedWeights(name_str.c_str(), weights);
+#else
+        return false;
+#endif
+    }
+
+    bool set_named_weights_from_ptr(
+        rust::Str name,
+        int32_t dtype,
+        std::size_t ptr,
+        int64_t count,
+        int32_t location) noexcept {
+#if NV_TENSORRT_MAJOR >= 10
+        const auto name_str = std::string(name);
+        const nvinfer1::Weights weights{
+            static_cast<nvinfer1::DataType>(dtype), reinterpret_cast<const void*>(ptr), count};
+        return refitter_->setNamedWeights(
+            name_str.c_str(), weights, static_cast<nvinfer1::TensorLocation>(location));
+#else
+        return false;
+#endif
+    }
+
+    rust::Vec<rust::String> get_all_weights() const noexcept {
+        rust::Vec<rust::String> names;
+#if NV_TENSORRT_MAJOR > 8 || (NV_TENSORRT_MAJOR == 8 && NV_TENSORRT_MINOR >= 5)
+        std::vector<const char*> names_ptr(refitter_->getAllWeights(0, nullptr));
+        const auto count = refitter_->getAllWeights(names_ptr.size(), names_ptr.data());
+        for (int32_t i = 0; i < count; ++i) {
+            names.push_back(names_ptr[i]);
+        }
+#endif
+        return names;
+    }
+
+    rust::Vec<rust::String> get_missing_weights() const noexcept {
+        rust::Vec<rust::String> names;
+#if NV_TENSORRT_MAJOR > 8 || (NV_TENSORRT_MAJOR == 8 && NV_TENSORRT_MINOR >= 5)
+        std::vector<const char*> names_ptr(refitter_->getMissingWeights(0, nullptr));
+        const auto count = refitter_->getMissingWeights(names_ptr.size(), names_ptr.data());
+        for (int32_t i = 0; i < count; ++i) {
+            names.push_back(names_ptr[i]);
+        }
+#endif
+        return names;
+    }
+
+    bool refit_cuda_engine() noexcept {
+        const auto refitted = refitter_->refitCudaEngine();
+        weights_.clear();
+        return refitted;
+    }
+
+    bool refit_cuda_engine_async(std::size_t stream) noexcept {
+#if NV_TENSORRT_MAJOR >= 10
+        // the staged weights may still be read on `stream`, so they are kept
+        // until the next synchronous refit or until the refitter is dropped
+        return refitter_->refitCudaEngineAsync(reinterpret_cast<cudaStream_t>(stream));
+#else
+        return false;
+#endif
+    }
+private:
+    std::unique_ptr<IRefitter> refitter_;
+    // TensorRT only references the new weights until the engine is refitted
+    std::vector<std::vector<uint8_t>> weights_;
+};
+
+inline std::unique_ptr<Refitter> create_refitter(CudaEngine& engine, Logger& logger) noexcept {
+    auto refitter = nvinfer1::createInferRefitter(engine.get(), logger);
+    if (!refitter) {
+        return nullptr;
+    } else {
+        return std::make_unique<Refitter>(std::unique_ptr<IRefitter>(refitter));
+    }
+}
+
+} // namespace trt_rs::refitter
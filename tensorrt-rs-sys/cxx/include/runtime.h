@@ -1,27 +1,53 @@
 #pragma once
 
 #include <memory>
+#include <cuda.h>
 #include <NvInferRuntime.h>
 #include "rust/cxx.h"
+#include "allocator.h"
 #include "logger.h"
 #include "plugin.h"
+#include "profiler.h"
+#include "recorder.h"
 
 namespace trt_rs::runtime {
 
 using nvinfer1::IRuntime;
 using nvinfer1::ICudaEngine;
 using nvinfer1::IExecutionContext;
+using nvinfer1::IHostMemory;
 using nvinfer1::Dims;
 using logger::Logger;
 
 class CudaEngine;
 
+// The recorder set on an engine. The engine's deleter holds the slot too, so
+// the recorder lives as long as the ICudaEngine reporting to it, which the
+// contexts created from it keep alive.
+using RecorderSlot = std::shared_ptr<std::shared_ptr<recorder::ErrorRecorderImpl>>;
+
+class HostMemory {
+public:
+    HostMemory(std::unique_ptr<IHostMemory> memory) : memory_(std::move(memory)) {}
+
+    rust::Slice<const std::uint8_t> data() const noexcept {
+        if (memory_->size() == 0) {
+            return {};
+        }
+        return {static_cast<const std::uint8_t*>(memory_->data()), memory_->size()};
+    }
+private:
+    std::unique_ptr<IHostMemory> memory_;
+};
+
 class Runtime {
 public:
     Runtime(std::unique_ptr<IRuntime> runtime) : runtime_(std::move(runtime)) {}
 
     std::unique_ptr<CudaEngine> deserialize(rust::Slice<const std::uint8_t> data) noexcept;
 
+    bool is_engine_compatible(rust::Slice<const std::uint8_t> data) const noexcept;
+
     bool set_max_threads(int32_t threads) noexcept {
         return runtime_->setMaxThreads(threads);
     }
@@ -37,15 +63,80 @@ public:
     bool get_engine_host_code_allowed() const noexcept {
         return runtime_->getEngineHostCodeAllowed();
     }
+
+    void set_gpu_allocator(rust::Box<allocator::RustGpuAllocator> allocator) noexcept;
+
+    void set_error_recorder(const recorder::ErrorRecorder& recorder) noexcept {
+        recorder_ = recorder.get();
+        runtime_->setErrorRecorder(recorder_.get());
+    }
+
+    bool has_error_recorder() const noexcept {
+        return runtime_->getErrorRecorder() != nullptr;
+    }
 private:
+    // engines deserialized with the allocator or recorder keep them alive themselves
+    std::shared_ptr<nvinfer1::IGpuAllocator> allocator_;
+    std::shared_ptr<recorder::ErrorRecorderImpl> recorder_;
     std::unique_ptr<IRuntime> runtime_;
 };
 
 class ExecutionContext;
 
+// Flags for serializeWithConfig; a stub that rejects every call before TensorRT 10.
+class SerializationConfig {
+public:
+    bool set_flags(uint32_t flags) noexcept;
+
+    uint32_t get_flags() const noexcept;
+
+    bool set_flag(int32_t flag) noexcept;
+
+    bool clear_flag(int32_t flag) noexcept;
+
+    bool get_flag(int32_t flag) const noexcept;
+
+#if NV_TENSORRT_MAJOR >= 10
+    SerializationConfig(std::unique_ptr<nvinfer1::ISerializationConfig> config)
+        : config_(std::move(config)) {}
+
+    nvinfer1::ISerializationConfig& get() noexcept {
+        return *config_;
+    }
+private:
+    std::unique_ptr<nvinfer1::ISerializationConfig> config_;
+#endif
+};
+
+// An instantiated CUDA graph recorded from an enqueue.
+class CudaGraph {
+public:
+    CudaGraph(CUgraph graph, CUgraphExec exec) : graph_(graph), exec_(exec) {}
+
+    ~CudaGraph() {
+        cuGraphExecDestroy(exec_);
+        cuGraphDestroy(graph_);
+    }
+
+    CudaGraph(const CudaGraph&) = delete;
+    CudaGraph& operator=(const CudaGraph&) = delete;
+
+    bool launch(std::size_t stream) noexcept {
+        return cuGraphLaunch(exec_, reinterpret_cast<CUstream>(stream)) == CUDA_SUCCESS;
+    }
+
+    // Updates the instantiated graph in place to match `graph`, which must
+    // have the same topology; takes ownership of `graph` on success.
+    bool update(CUgraph graph) noexcept;
+private:
+    CUgraph graph_;
+    CUgraphExec exec_;
+};
+
 class CudaEngine {
 public:
-    CudaEngine(std::unique_ptr<ICudaEngine> engine) : engine_(std::move(engine)) {}
+    CudaEngine(std::shared_ptr<ICudaEngine> engine, RecorderSlot recorder)
+        : recorder_(std::move(recorder)), engine_(std::move(engine)) {}
 
     rust::Vec<int32_t> get_tensor_shape(rust::Str name) const noexcept;
 
@@ -76,6 +167,24 @@ public:
         return engine_->getDeviceMemorySize();
     }
 
+    size_t get_device_memory_size_v2() const noexcept {
+#if NV_TENSORRT_MAJOR >= 10
+        return engine_->getDeviceMemorySizeV2();
+#else
+        return engine_->getDeviceMemorySize();
+#endif
+    }
+
+    size_t get_device_memory_size_for_profile_v2(int32_t profile_index) const noexcept {
+#if NV_TENSORRT_MAJOR > 10 || (NV_TENSORRT_MAJOR == 10 && NV_TENSORRT_MINOR >= 1)
+        return engine_->getDeviceMemorySizeForProfileV2(profile_index);
+#elif NV_TENSORRT_MAJOR >= 10
+        return engine_->getDeviceMemorySizeForProfile(profile_index);
+#else
+        return engine_->getDeviceMemorySize();
+#endif
+    }
+
     bool is_refittable() const noexcept {
         return engine_->isRefittable();
     }
@@ -95,6 +204,12 @@ public:
         return static_cast<int32_t>(engine_->getTensorFormat(name_str.c_str()));
     }
 
+    rust::String get_tensor_format_desc(rust::Str name) const noexcept {
+        const auto name_str = std::string(name);
+        const auto desc = engine_->getTensorFormatDesc(name_str.c_str());
+        return desc ? rust::String::lossy(desc) : rust::String();
+    }
+
     int32_t get_tensor_vectorized_dim(rust::Str name) const noexcept {
         const auto name_str = std::string(name);
         return engine_->getTensorVectorizedDim(name_str.c_str());
@@ -108,6 +223,8 @@ public:
         return engine_->getNbOptimizationProfiles();
     }
 
+    rust::Vec<int32_t> get_profile_shape(rust::Str name, int32_t profile_index, int32_t select) const noexcept;
+
     int32_t get_engine_capability() const noexcept {
         return static_cast<int32_t>(engine_->getEngineCapability());
     }
@@ -131,13 +248,47 @@ public:
     int32_t get_num_aux_streams() const noexcept {
         return engine_->getNbAuxStreams();
     }
+
+    std::unique_ptr<HostMemory> serialize() const noexcept;
+
+    std::unique_ptr<SerializationConfig> create_serialization_config() const noexcept;
+
+    std::unique_ptr<HostMemory> serialize_with_config(SerializationConfig& config) const noexcept;
+
+    size_t get_raw() const noexcept {
+        return reinterpret_cast<size_t>(engine_.get());
+    }
+
+    ICudaEngine& get() noexcept {
+        return *engine_;
+    }
+
+    void set_error_recorder(const recorder::ErrorRecorder& recorder) noexcept {
+        *recorder_ = recorder.get();
+        engine_->setErrorRecorder(recorder_->get());
+    }
+
+    bool has_error_recorder() const noexcept {
+        return engine_->getErrorRecorder() != nullptr;
+    }
 private:
-    std::unique_ptr<ICudaEngine> engine_;
+    // also handed to the contexts created afterwards, which inherit it and
+    // keep reporting to it if the engine gets another one
+    RecorderSlot recorder_;
+    // shared with the contexts created from it, which keep it alive for as long
+    // as they exist
+    std::shared_ptr<ICudaEngine> engine_;
 };
 
 class ExecutionContext {
 public:
-    ExecutionContext(std::unique_ptr<IExecutionContext> context) : context_(std::move(context)) {}
+    ExecutionContext(
+        std::unique_ptr<IExecutionContext> context,
+        std::shared_ptr<ICudaEngine> engine,
+        std::shared_ptr<recorder::ErrorRecorderImpl> recorder)
+        : recorder_(std::move(recorder)),
+          engine_(std::move(engine)),
+          context_(std::move(context)) {}
 
     void set_debug_sync(bool sync) noexcept {
         context_->setDebugSync(sync);
@@ -160,6 +311,14 @@ public:
         context_->setDeviceMemory(reinterpret_cast<void*>(memory));
     }
 
+    void set_device_memory_v2(std::size_t memory, int64_t size) noexcept {
+#if NV_TENSORRT_MAJOR > 10 || (NV_TENSORRT_MAJOR == 10 && NV_TENSORRT_MINOR >= 1)
+        context_->setDeviceMemoryV2(reinterpret_cast<void*>(memory), size);
+#else
+        context_->setDeviceMemory(reinterpret_cast<void*>(memory));
+#endif
+    }
+
     rust::Vec<int32_t> get_tensor_strides(rust::Str name) const noexcept;
 
     int32_t get_optimization_profile() const noexcept {
@@ -178,6 +337,10 @@ public:
         return context_->allInputShapesSpecified();
     }
 
+    int32_t infer_shapes() noexcept {
+        return context_->inferShapes(0, nullptr);
+    }
+
     bool set_optimization_profile_async(int32_t profile, std::size_t stream) noexcept {
         return context_->setOptimizationProfileAsync(profile, reinterpret_cast<cudaStream_t>(stream));
     }
@@ -194,6 +357,12 @@ public:
         context_->reportToProfiler();
     }
 
+    void set_profiler(rust::Box<profiler::RustProfiler> profiler) noexcept;
+
+    bool has_profiler() const noexcept {
+        return context_->getProfiler() != nullptr;
+    }
+
     bool set_tensor_address(rust::Str name, std::size_t address) noexcept {
         const auto name_str = std::string(name);
         return context_->setTensorAddress(name_str.c_str(), reinterpret_cast<void*>(address));
@@ -235,6 +404,10 @@ public:
 
     bool enqueue_v3(std::size_t stream) noexcept;
 
+    std::unique_ptr<CudaGraph> capture_graph(std::size_t stream) noexcept;
+
+    bool update_graph(CudaGraph& graph, std::size_t stream) noexcept;
+
     void set_persistent_cache_limit(std::size_t limit) noexcept {
         context_->setPersistentCacheLimit(limit);
     }
@@ -252,7 +425,24 @@ public:
             reinterpret_cast<cudaStream_t const*>(streams.data()));
         context_->setAuxStreams(streams_ptr, streams.size());
     }
+
+    size_t get_raw() const noexcept {
+        return reinterpret_cast<size_t>(context_.get());
+    }
+
+    void set_error_recorder(const recorder::ErrorRecorder& recorder) noexcept {
+        recorder_ = recorder.get();
+        context_->setErrorRecorder(recorder_.get());
+    }
+
+    bool has_error_recorder() const noexcept {
+        return context_->getErrorRecorder() != nullptr;
+    }
 private:
+    std::shared_ptr<recorder::ErrorRecorderImpl> recorder_;
+    std::unique_ptr<nvinfer1::IProfiler> profiler_;
+    // declared before the context so it is destroyed after it
+    std::shared_ptr<ICudaEngine> engine_;
     std::unique_ptr<IExecutionContext> context_;
 };
 
@@ -0,0 +1,77 @@
+#include "algorithm.h"
+#include "tensorrt-rs-sys/src/lib.rs.h"
+
+namespace trt_rs::algorithm {
+
+namespace {
+
+AlgorithmChoice to_choice(const nvinfer1::IAlgorithm& algorithm) {
+    const auto& variant = algorithm.getAlgorithmVariant();
+    AlgorithmChoice choice{};
+    choice.implementation = variant.getImplementation();
+    choice.tactic = variant.getTactic();
+    choice.timing_ms = algorithm.getTimingMSec();
+    choice.workspace_size = algorithm.getWorkspaceSize();
+    return choice;
+}
+
+class RustAlgorithmSelectorImpl : public nvinfer1::IAlgorithmSelector {
+public:
+    RustAlgorithmSelectorImpl(rust::Box<RustAlgorithmSelector> selector)
+        : selector_(std::move(selector)) {}
+
+    int32_t selectAlgorithms(
+        const nvinfer1::IAlgorithmContext& context,
+        const nvinfer1::IAlgorithm* const* choices,
+        int32_t nb_choices,
+        int32_t* selection) noexcept override {
+        rust::Vec<AlgorithmChoice> choices_vec;
+        choices_vec.reserve(nb_choices);
+        for (int32_t i = 0; i < nb_choices; ++i) {
+            choices_vec.push_back(to_choice(*choices[i]));
+        }
+
+        const auto name = rust::String::lossy(context.getName());
+        const auto selected = selector_select_algorithms(
+            *selector_,
+            name,
+            rust::Slice<const AlgorithmChoice>(choices_vec.data(), choices_vec.size()));
+        // already filtered on the Rust side; the bounds are checked again so a
+        // bad index can never write past `selection`
+        int32_t nb_selected = 0;
+        for (const auto index : selected) {
+            if (index >= 0 && index < nb_choices && nb_selected < nb_choices) {
+                selection[nb_selected++] = index;
+            }
+        }
+        return nb_selected;
+    }
+
+    void reportAlgorithms(
+        const nvinfer1::IAlgorithmContext* const* contexts,
+        const nvinfer1::IAlgorithm* const* choices,
+        int32_t nb_algorithms) noexcept override {
+        rust::Vec<AlgorithmReport> reports;
+        reports.reserve(nb_algorithms);
+        for (int32_t i = 0; i < nb_algorithms; ++i) {
+            AlgorithmReport report{};
+            report.layer_name = rust::String::lossy(contexts[i]->getName());
+            report.choice = to_choice(*choices[i]);
+            reports.push_back(std::move(report));
+        }
+
+        selector_report_algorithms(
+            *selector_, rust::Slice<const AlgorithmReport>(reports.data(), reports.size()));
+    }
+private:
+    rust::Box<RustAlgorithmSelector> selector_;
+};
+
+} // namespace
+
+std::unique_ptr<nvinfer1::IAlgorithmSelector> create_algorithm_selector(
+    rust::Box<RustAlgorithmSelector> selector) noexcept {
+    return std::make_unique<RustAlgorithmSelectorImpl>(std::move(selector));
+}
+
+} // namespace trt_rs::algorithm
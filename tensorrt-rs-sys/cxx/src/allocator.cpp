@@ -0,0 +1,41 @@
+#include "allocator.h"
+#include "tensorrt-rs-sys/src/lib.rs.h"
+
+namespace trt_rs::allocator {
+
+namespace {
+
+class RustGpuAllocatorImpl : public nvinfer1::IGpuAllocator {
+public:
+    RustGpuAllocatorImpl(rust::Box<RustGpuAllocator> allocator)
+        : allocator_(std::move(allocator)) {}
+
+    void* allocate(
+        uint64_t size, uint64_t alignment, nvinfer1::AllocatorFlags flags) noexcept override {
+        const auto resizable_bit = 1U << static_cast<uint32_t>(nvinfer1::AllocatorFlag::kRESIZABLE);
+        const bool resizable = (flags & resizable_bit) != 0;
+        return reinterpret_cast<void*>(allocator_allocate(*allocator_, size, alignment, resizable));
+    }
+
+    bool deallocate(void* memory) noexcept override {
+        return allocator_deallocate(*allocator_, reinterpret_cast<std::size_t>(memory));
+    }
+
+#if NV_TENSORRT_MAJOR < 10
+    // TensorRT 8 still frees through the deprecated `free`
+    void free(void* memory) noexcept override {
+        deallocate(memory);
+    }
+#endif
+private:
+    rust::Box<RustGpuAllocator> allocator_;
+};
+
+} // namespace
+
+std::shared_ptr<nvinfer1::IGpuAllocator> create_gpu_allocator(
+    rust::Box<RustGpuAllocator> allocator) noexcept {
+    return std::make_shared<RustGpuAllocatorImpl>(std::move(allocator));
+}
+
+} // namespace trt_rs::allocator
@@ -0,0 +1,222 @@
+#include "builder.h"
+#include "tensorrt-rs-sys/src/lib.rs.h"
+
+namespace trt_rs::builder {
+
+namespace {
+
+// Fails for more dimensions than `Dims` holds.
+bool to_dims(rust::Slice<const int32_t> dims, nvinfer1::Dims& dims_trt) noexcept {
+    if (dims.size() > static_cast<std::size_t>(nvinfer1::Dims::MAX_DIMS)) {
+        return false;
+    }
+    dims_trt.nbDims = static_cast<int32_t>(dims.size());
+    for (int32_t i = 0; i < dims_trt.nbDims; ++i) {
+        dims_trt.d[i] = dims[i];
+    }
+    return true;
+}
+
+} // namespace
+
+std::size_t NetworkDefinition::add_input(
+    rust::Str name, int32_t dtype, rust::Slice<const int32_t> dims) const noexcept {
+    const auto name_str = std::string(name);
+    nvinfer1::Dims dims_trt;
+    if (!to_dims(dims, dims_trt)) {
+        return 0;
+    }
+    auto tensor = network_->addInput(
+        name_str.c_str(), static_cast<nvinfer1::DataType>(dtype), dims_trt);
+    return reinterpret_cast<std::size_t>(tensor);
+}
+
+rust::Vec<int32_t> NetworkDefinition::get_tensor_dimensions(std::size_t tensor) const noexcept {
+    const auto dims = reinterpret_cast<ITensor*>(tensor)->getDimensions();
+    auto dims_vec = rust::Vec<int32_t>();
+    dims_vec.reserve(dims.nbDims);
+    for (int32_t i = 0; i < dims.nbDims; ++i) {
+        dims_vec.push_back(dims.d[i]);
+    }
+    return dims_vec;
+}
+
+std::size_t NetworkDefinition::add_identity(std::size_t input) const noexcept {
+    auto layer = network_->addIdentity(*reinterpret_cast<ITensor*>(input));
+    if (!layer) {
+        return 0;
+    } else {
+        return reinterpret_cast<std::size_t>(layer->getOutput(0));
+    }
+}
+
+std::size_t NetworkDefinition::add_non_zero(std::size_t input) const noexcept {
+#if NV_TENSORRT_MAJOR > 8 || (NV_TENSORRT_MAJOR == 8 && NV_TENSORRT_MINOR >= 5)
+    auto layer = network_->addNonZero(*reinterpret_cast<ITensor*>(input));
+    if (!layer) {
+        return 0;
+    } else {
+        return reinterpret_cast<std::size_t>(layer->getOutput(0));
+    }
+#else
+    return 0;
+#endif
+}
+
+std::size_t NetworkDefinition::add_constant(
+    rust::Slice<const int32_t> dims, rust::Slice<const float> weights) const noexcept {
+    nvinfer1::Dims dims_trt;
+    if (!to_dims(dims, dims_trt)) {
+        return 0;
+    }
+    // TensorRT would read past the weights if they do not fill the dims
+    int64_t volume = 1;
+    for (const auto dim : dims) {
+        if (dim < 0) {
+            return 0;
+        }
+        volume *= dim;
+    }
+    if (volume != static_cast<int64_t>(weights.size())) {
+        return 0;
+    }
+    weights_.emplace_back(weights.begin(), weights.end());
+    const auto& values = weights_.back();
+    const nvinfer1::Weights weights_trt{
+        nvinfer1::DataType::kFLOAT, values.data(), static_cast<int64_t>(values.size())};
+    auto layer = network_->addConstant(dims_trt, weights_trt);
+    if (!layer) {
+        return 0;
+    } else {
+        return reinterpret_cast<std::size_t>(layer->getOutput(0));
+    }
+}
+
+std::size_t NetworkDefinition::add_convolution(
+    rust::Str name,
+    std::size_t input,
+    int32_t nb_output_maps,
+    rust::Slice<const int32_t> kernel_size,
+    rust::Slice<const float> kernel,
+    rust::Slice<const float> bias) const noexcept {
+    nvinfer1::Dims dims_trt;
+    if (!to_dims(kernel_size, dims_trt)) {
+        return 0;
+    }
+    weights_.emplace_back(kernel.begin(), kernel.end());
+    const auto& kernel_values = weights_.back();
+    const nvinfer1::Weights kernel_trt{
+        nvinfer1::DataType::kFLOAT, kernel_values.data(), static_cast<int64_t>(kernel_values.size())};
+    weights_.emplace_back(bias.begin(), bias.end());
+    const auto& bias_values = weights_.back();
+    const nvinfer1::Weights bias_trt{
+        nvinfer1::DataType::kFLOAT, bias_values.data(), static_cast<int64_t>(bias_values.size())};
+
+    auto layer = network_->addConvolutionNd(
+        *reinterpret_cast<ITensor*>(input), nb_output_maps, dims_trt, kernel_trt, bias_trt);
+    if (!layer) {
+        return 0;
+    }
+    const auto name_str = std::string(name);
+    layer->setName(name_str.c_str());
+#if NV_TENSORRT_MAJOR > 8 || (NV_TENSORRT_MAJOR == 8 && NV_TENSORRT_MINOR >= 5)
+    // name the weights like ONNX exporters do, so they can be refitted by name
+    network_->setWeightsName(kernel_trt, (name_str + ".weight").c_str());
+    if (bias_trt.count > 0) {
+        network_->setWeightsName(bias_trt, (name_str + ".bias").c_str());
+    }
+#endif
+    return reinterpret_cast<std::size_t>(layer->getOutput(0));
+}
+
+std::unique_ptr<NetworkDefinition> Builder::create_network() noexcept {
+    return create_network_v2(0);
+}
+
+std::unique_ptr<NetworkDefinition> Builder::create_network_v2(uint32_t flags) noexcept {
+#if NV_TENSORRT_MAJOR < 9
+    // strongly typed networks came with TensorRT 9
+    if (flags & (1U << 1)) {
+        return nullptr;
+    }
+#endif
+#if NV_TENSORRT_MAJOR < 10
+    // implicit batch networks are not supported by this crate
+    flags |= 1U << static_cast<uint32_t>(
+        nvinfer1::NetworkDefinitionCreationFlag::kEXPLICIT_BATCH);
+#endif
+    auto network = builder_->createNetworkV2(flags);
+    if (!network) {
+        return nullptr;
+    } else {
+        return std::make_unique<NetworkDefinition>(std::unique_ptr<INetworkDefinition>(network));
+    }
+}
+
+void BuilderConfig::set_int8_calibrator(rust::Box<calibrator::RustCalibrator> calibrator) noexcept {
+    calibrator_ = calibrator::create_int8_calibrator(std::move(calibrator));
+    config_->setInt8Calibrator(calibrator_.get());
+}
+
+void BuilderConfig::set_algorithm_selector(
+    rust::Box<algorithm::RustAlgorithmSelector> selector) noexcept {
+    algorithm_selector_ = algorithm::create_algorithm_selector(std::move(selector));
+    config_->setAlgorithmSelector(algorithm_selector_.get());
+}
+
+bool BuilderConfig::set_progress_monitor(
+    rust::Box<progress::RustProgressMonitor> monitor) noexcept {
+#if NV_TENSORRT_MAJOR >= 10
+    progress_monitor_ = progress::create_progress_monitor(std::move(monitor));
+    config_->setProgressMonitor(progress_monitor_.get());
+    return true;
+#else
+    return false;
+#endif
+}
+
+std::unique_ptr<BuilderConfig> Builder::create_builder_config() noexcept {
+    auto config = builder_->createBuilderConfig();
+    if (!config) {
+        return nullptr;
+    } else {
+        return std::make_unique<BuilderConfig>(std::unique_ptr<IBuilderConfig>(config));
+    }
+}
+
+std::unique_ptr<HostMemory> Builder::build_serialized_network(
+    NetworkDefinition& network, BuilderConfig& config) noexcept {
+    auto memory = builder_->buildSerializedNetwork(network.get(), config.get());
+    if (!memory) {
+        return nullptr;
+    } else {
+        return std::make_unique<HostMemory>(std::unique_ptr<nvinfer1::IHostMemory>(memory));
+    }
+}
+
+void Builder::set_gpu_allocator(rust::Box<allocator::RustGpuAllocator> allocator) noexcept {
+    allocator_ = allocator::create_gpu_allocator(std::move(allocator));
+    builder_->setGpuAllocator(allocator_.get());
+}
+
+std::unique_ptr<Builder> create_builder(Logger& logger) {
+    auto builder = nvinfer1::createInferBuilder(logger);
+    if (!builder) {
+        return nullptr;
+    } else {
+        return std::make_unique<Builder>(std::unique_ptr<IBuilder>(builder));
+    }
+}
+
+bool set_profile_dimensions(
+    std::size_t profile, rust::Str name, int32_t select, rust::Slice<const int32_t> dims) noexcept {
+    const auto name_str = std::string(name);
+    nvinfer1::Dims dims_trt;
+    if (!to_dims(dims, dims_trt)) {
+        return false;
+    }
+    return reinterpret_cast<nvinfer1::IOptimizationProfile*>(profile)->setDimensions(
+        name_str.c_str(), static_cast<nvinfer1::OptProfileSelector>(select), dims_trt);
+}
+
+} // namespace trt_rs::builder
@@ -0,0 +1,62 @@
+#include <vector>
+#include "calibrator.h"
+#include "tensorrt-rs-sys/src/lib.rs.h"
+
+namespace trt_rs::calibrator {
+
+namespace {
+
+class RustInt8Calibrator : public nvinfer1::IInt8EntropyCalibrator2 {
+public:
+    RustInt8Calibrator(rust::Box<RustCalibrator> calibrator) : calibrator_(std::move(calibrator)) {}
+
+    int32_t getBatchSize() const noexcept override {
+        return calibrator_get_batch_size(*calibrator_);
+    }
+
+    bool getBatch(void* bindings[], char const* names[], int32_t nb_bindings) noexcept override {
+        rust::Vec<rust::String> names_vec;
+        names_vec.reserve(nb_bindings);
+        for (int32_t i = 0; i < nb_bindings; ++i) {
+            names_vec.push_back(rust::String(names[i]));
+        }
+        std::vector<std::size_t> ptrs(nb_bindings, 0);
+
+        const bool has_batch = calibrator_get_batch(
+            *calibrator_,
+            rust::Slice<const rust::String>(names_vec.data(), names_vec.size()),
+            rust::Slice<std::size_t>(ptrs.data(), ptrs.size()));
+        if (!has_batch) {
+            return false;
+        }
+        for (int32_t i = 0; i < nb_bindings; ++i) {
+            bindings[i] = reinterpret_cast<void*>(ptrs[i]);
+        }
+        return true;
+    }
+
+    void const* readCalibrationCache(std::size_t& length) noexcept override {
+        // TensorRT reads the cache after this returns, so it is kept here
+        cache_ = calibrator_read_cache(*calibrator_);
+        length = cache_.size();
+        return cache_.empty() ? nullptr : cache_.data();
+    }
+
+    void writeCalibrationCache(void const* ptr, std::size_t length) noexcept override {
+        calibrator_write_cache(
+            *calibrator_,
+            rust::Slice<const uint8_t>(static_cast<const uint8_t*>(ptr), length));
+    }
+private:
+    rust::Box<RustCalibrator> calibrator_;
+    rust::Vec<uint8_t> cache_;
+};
+
+} // namespace
+
+std::unique_ptr<nvinfer1::IInt8Calibrator> create_int8_calibrator(
+    rust::Box<RustCalibrator> calibrator) noexcept {
+    return std::make_unique<RustInt8Calibrator>(std::move(calibrator));
+}
+
+} // namespace trt_rs::calibrator
@@ -1,10 +1,58 @@
 #include <iostream>
 #include "spdlog/spdlog.h"
 #include "logger.h"
+#include "tensorrt-rs-sys/src/lib.rs.h"
 
 namespace trt_rs::logger {
 
+struct InternalErrorHandlerHolder {
+    rust::Box<InternalErrorHandler> handler;
+};
+
+struct LogCallbackHolder {
+    rust::Box<LogCallback> callback;
+};
+
 void Logger::log(Severity severity, const char *msg) noexcept {
+    if (severity <= Severity::kERROR) {
+        std::lock_guard<std::mutex> lock(capture_mutex_);
+        last_error_ = msg;
+    }
+
+    if (severity > level_) {
+        return;
+    }
+
+    {
+        std::lock_guard<std::mutex> lock(capture_mutex_);
+        if (capture_) {
+            captured_.emplace_back(msg);
+        }
+    }
+
+    if (severity == Severity::kINTERNAL_ERROR) {
+        std::shared_ptr<InternalErrorHandlerHolder> holder;
+        {
+            std::lock_guard<std::mutex> lock(handler_mutex_);
+            holder = internal_error_handler_;
+        }
+        if (holder) {
+            handle_internal_error(*holder->handler, rust::String::lossy(msg));
+        }
+    }
+
+    std::shared_ptr<LogCallbackHolder> callback;
+    {
+        std::lock_guard<std::mutex> lock(handler_mutex_);
+        callback = callback_;
+    }
+    if (callback) {
+        // TensorRT messages may quote non-UTF-8 bytes, e.g. from file names
+        log_callback(
+            *callback->callback, static_cast<int32_t>(severity), rust::String::lossy(msg));
+        return;
+    }
+
     switch (severity) {
         case Severity::kINTERNAL_ERROR:
             spdlog::critical(msg);
@@ -34,6 +82,7 @@ void Logger::log(int32_t severity, rust::Str msg) noexcept {
 
 void Logger::set_level(int32_t severity) noexcept {
     const auto level = static_cast<Severity>(severity);
+    level_ = level;
     switch (level)
     {
     case Severity::kINTERNAL_ERROR:
@@ -56,6 +105,45 @@ void Logger::set_level(int32_t severity) noexcept {
     }
 }
 
+void Logger::set_capture(bool capture) noexcept {
+    std::lock_guard<std::mutex> lock(capture_mutex_);
+    capture_ = capture;
+    if (!capture) {
+        captured_.clear();
+    }
+}
+
+rust::Vec<rust::String> Logger::take_captured() noexcept {
+    std::lock_guard<std::mutex> lock(capture_mutex_);
+    auto messages = rust::Vec<rust::String>();
+    messages.reserve(captured_.size());
+    for (const auto& msg : captured_) {
+        messages.push_back(rust::String::lossy(msg));
+    }
+    captured_.clear();
+    return messages;
+}
+
+rust::String Logger::take_last_error() noexcept {
+    std::lock_guard<std::mutex> lock(capture_mutex_);
+    auto msg = rust::String::lossy(last_error_);
+    last_error_.clear();
+    return msg;
+}
+
+void Logger::set_internal_error_handler(rust::Box<InternalErrorHandler> handler) noexcept {
+    auto holder = std::make_shared<InternalErrorHandlerHolder>(
+        InternalErrorHandlerHolder{std::move(handler)});
+    std::lock_guard<std::mutex> lock(handler_mutex_);
+    internal_error_handler_ = std::move(holder);
+}
+
+void Logger::set_callback(rust::Box<LogCallback> callback) noexcept {
+    auto holder = std::make_shared<LogCallbackHolder>(LogCallbackHolder{std::move(callback)});
+    std::lock_guard<std::mutex> lock(handler_mutex_);
+    callback_ = std::move(holder);
+}
+
 std::unique_ptr<Logger> create_logger() {
     return std::make_unique<Logger>();
 }
@@ -1 +1,372 @@
+#include <cstring>
+#include <mutex>
+#include <vector>
 #include "plugin.h"
+#include "tensorrt-rs-sys/src/lib.rs.h"
+
+namespace trt_rs::plugin {
+
+#if NV_TENSORRT_MAJOR >= 10
+
+using nvinfer1::AsciiChar;
+using nvinfer1::DataType;
+using nvinfer1::DimsExprs;
+using nvinfer1::DynamicPluginTensorDesc;
+using nvinfer1::IExprBuilder;
+using nvinfer1::IPluginCapability;
+using nvinfer1::IPluginCreator;
+using nvinfer1::IPluginCreatorV3One;
+using nvinfer1::IPluginResourceContext;
+using nvinfer1::IPluginV3;
+using nvinfer1::IPluginV3OneBuild;
+using nvinfer1::IPluginV3OneCore;
+using nvinfer1::IPluginV3OneRuntime;
+using nvinfer1::PluginCapabilityType;
+using nvinfer1::PluginField;
+using nvinfer1::PluginFieldCollection;
+using nvinfer1::PluginFieldType;
+using nvinfer1::TensorRTPhase;
+
+namespace {
+
+rust::Vec<PluginTensorDesc> to_rust_descs(
+    const nvinfer1::PluginTensorDesc* descs, int32_t count) {
+    rust::Vec<PluginTensorDesc> vec;
+    vec.reserve(count);
+    for (int32_t i = 0; i < count; ++i) {
+        PluginTensorDesc desc{};
+        for (int32_t j = 0; j < descs[i].dims.nbDims; ++j) {
+            desc.dims.push_back(descs[i].dims.d[j]);
+        }
+        desc.dtype = static_cast<int32_t>(descs[i].type);
+        desc.format = static_cast<int32_t>(descs[i].format);
+        vec.push_back(std::move(desc));
+    }
+    return vec;
+}
+
+class RustPluginV3 :
+    public IPluginV3,
+    public IPluginV3OneCore,
+    public IPluginV3OneBuild,
+    public IPluginV3OneRuntime {
+public:
+    RustPluginV3(
+        rust::Box<RustPlugin> plugin,
+        std::string name,
+        std::string version,
+        std::string plugin_namespace)
+        : plugin_(std::move(plugin))
+        , name_(std::move(name))
+        , version_(std::move(version))
+        , namespace_(std::move(plugin_namespace)) {}
+
+    // IPluginV3
+    IPluginCapability* getCapabilityInterface(PluginCapabilityType type) noexcept override {
+        switch (type) {
+        case PluginCapabilityType::kCORE:
+            return static_cast<IPluginV3OneCore*>(this);
+        case PluginCapabilityType::kBUILD:
+            return static_cast<IPluginV3OneBuild*>(this);
+        case PluginCapabilityType::kRUNTIME:
+            return static_cast<IPluginV3OneRuntime*>(this);
+        }
+        return nullptr;
+    }
+
+    IPluginV3* clone() noexcept override {
+        return new RustPluginV3(plugin_clone(*plugin_), name_, version_, namespace_);
+    }
+
+    // IPluginV3OneCore
+    const AsciiChar* getPluginName() const noexcept override {
+        return name_.c_str();
+    }
+
+    const AsciiChar* getPluginVersion() const noexcept override {
+        return version_.c_str();
+    }
+
+    const AsciiChar* getPluginNamespace() const noexcept override {
+        return namespace_.c_str();
+    }
+
+    // IPluginV3OneBuild
+    int32_t configurePlugin(
+        const DynamicPluginTensorDesc* in,
+        int32_t nb_inputs,
+        const DynamicPluginTensorDesc* out,
+        int32_t nb_outputs) noexcept override {
+        return 0;
+    }
+
+    int32_t getOutputDataTypes(
+        DataType* output_types,
+        int32_t nb_outputs,
+        const DataType* input_types,
+        int32_t nb_inputs) const noexcept override {
+        rust::Vec<int32_t> types;
+        for (int32_t i = 0; i < nb_inputs; ++i) {
+            types.push_back(static_cast<int32_t>(input_types[i]));
+        }
+
+        const auto outputs = plugin_output_data_types(
+            *plugin_, rust::Slice<const int32_t>(types.data(), types.size()));
+        if (outputs.size() != static_cast<size_t>(nb_outputs)) {
+            return -1;
+        }
+
+        for (int32_t i = 0; i < nb_outputs; ++i) {
+            output_types[i] = static_cast<DataType>(outputs[i]);
+        }
+        return 0;
+    }
+
+    int32_t getOutputShapes(
+        const DimsExprs* inputs,
+        int32_t nb_inputs,
+        const DimsExprs* shape_inputs,
+        int32_t nb_shape_inputs,
+        DimsExprs* outputs,
+        int32_t nb_outputs,
+        IExprBuilder& expr_builder) noexcept override {
+        rust::Vec<int32_t> ranks;
+        for (int32_t i = 0; i < nb_inputs; ++i) {
+            ranks.push_back(inputs[i].nbDims);
+        }
+
+        const auto dims = plugin_output_dimensions(
+            *plugin_, rust::Slice<const int32_t>(ranks.data(), ranks.size()));
+
+        for (int32_t i = 0; i < nb_outputs; ++i) {
+            outputs[i].nbDims = 0;
+        }
+        for (const auto& dim : dims) {
+            if (dim.output < 0 || dim.output >= nb_outputs) {
+                return -1;
+            }
+            auto& output = outputs[dim.output];
+            if (output.nbDims >= nvinfer1::Dims::MAX_DIMS) {
+                return -1;
+            }
+
+            if (dim.input < 0) {
+                output.d[output.nbDims++] = expr_builder.constant(dim.value);
+            } else {
+                if (dim.input >= nb_inputs || dim.dim < 0 || dim.dim >= inputs[dim.input].nbDims) {
+                    return -1;
+                }
+                output.d[output.nbDims++] = inputs[dim.input].d[dim.dim];
+            }
+        }
+        return 0;
+    }
+
+    bool supportsFormatCombination(
+        int32_t pos,
+        const DynamicPluginTensorDesc* in_out,
+        int32_t nb_inputs,
+        int32_t nb_outputs) noexcept override {
+        std::vector<nvinfer1::PluginTensorDesc> descs;
+        for (int32_t i = 0; i < nb_inputs + nb_outputs; ++i) {
+            descs.push_back(in_out[i].desc);
+        }
+
+        const auto rust_descs = to_rust_descs(descs.data(), descs.size());
+        return plugin_supports_format_combination(
+            *plugin_,
+            pos,
+            rust::Slice<const PluginTensorDesc>(rust_descs.data(), rust_descs.size()),
+            nb_inputs);
+    }
+
+    int32_t getNbOutputs() const noexcept override {
+        return plugin_num_outputs(*plugin_);
+    }
+
+    // IPluginV3OneRuntime
+    int32_t onShapeChange(
+        const nvinfer1::PluginTensorDesc* in,
+        int32_t nb_inputs,
+        const nvinfer1::PluginTensorDesc* out,
+        int32_t nb_outputs) noexcept override {
+        nb_inputs_ = nb_inputs;
+        nb_outputs_ = nb_outputs;
+        return 0;
+    }
+
+    int32_t enqueue(
+        const nvinfer1::PluginTensorDesc* input_desc,
+        const nvinfer1::PluginTensorDesc* output_desc,
+        const void* const* inputs,
+        void* const* outputs,
+        void* workspace,
+        cudaStream_t stream) noexcept override {
+        const auto rust_input_desc = to_rust_descs(input_desc, nb_inputs_);
+        const auto rust_output_desc = to_rust_descs(output_desc, nb_outputs_);
+
+        rust::Vec<size_t> input_ptrs;
+        for (int32_t i = 0; i < nb_inputs_; ++i) {
+            input_ptrs.push_back(reinterpret_cast<size_t>(inputs[i]));
+        }
+        rust::Vec<size_t> output_ptrs;
+        for (int32_t i = 0; i < nb_outputs_; ++i) {
+            output_ptrs.push_back(reinterpret_cast<size_t>(outputs[i]));
+        }
+
+        const auto ok = plugin_enqueue(
+            *plugin_,
+            rust::Slice<const PluginTensorDesc>(rust_input_desc.data(), rust_input_desc.size()),
+            rust::Slice<const PluginTensorDesc>(rust_output_desc.data(), rust_output_desc.size()),
+            rust::Slice<const size_t>(input_ptrs.data(), input_ptrs.size()),
+            rust::Slice<const size_t>(output_ptrs.data(), output_ptrs.size()),
+            reinterpret_cast<size_t>(workspace),
+            reinterpret_cast<size_t>(stream));
+        return ok ? 0 : -1;
+    }
+
+    IPluginV3* attachToContext(IPluginResourceContext* context) noexcept override {
+        return clone();
+    }
+
+    const PluginFieldCollection* getFieldsToSerialize() noexcept override {
+        data_ = plugin_serialize(*plugin_);
+        field_ = PluginField("data", data_.data(), PluginFieldType::kINT8, data_.size());
+        fields_.nbFields = 1;
+        fields_.fields = &field_;
+        return &fields_;
+    }
+
+private:
+    rust::Box<RustPlugin> plugin_;
+    std::string name_;
+    std::string version_;
+    std::string namespace_;
+    int32_t nb_inputs_ = 0;
+    int32_t nb_outputs_ = 0;
+    rust::Vec<uint8_t> data_;
+    PluginField field_;
+    PluginFieldCollection fields_{};
+};
+
+class RustPluginCreatorV3 : public IPluginCreatorV3One {
+public:
+    RustPluginCreatorV3(
+        rust::Box<RustPluginCreator> creator,
+        std::string name,
+        std::string version,
+        std::string plugin_namespace)
+        : creator_(std::move(creator))
+        , name_(std::move(name))
+        , version_(std::move(version))
+        , namespace_(std::move(plugin_namespace)) {
+        field_ = PluginField("data", nullptr, PluginFieldType::kINT8, 0);
+        field_names_.nbFields = 1;
+        field_names_.fields = &field_;
+    }
+
+    IPluginV3* createPlugin(
+        const AsciiChar* name,
+        const PluginFieldCollection* fc,
+        TensorRTPhase phase) noexcept override {
+        rust::Slice<const uint8_t> data;
+        if (fc != nullptr) {
+            for (int32_t i = 0; i < fc->nbFields; ++i) {
+                const auto& field = fc->fields[i];
+                if (std::strcmp(field.name, "data") == 0 && field.data != nullptr && field.length > 0) {
+                    data = rust::Slice<const uint8_t>(
+                        static_cast<const uint8_t*>(field.data), field.length);
+                }
+            }
+        }
+
+        return new RustPluginV3(creator_create_plugin(*creator_, data), name_, version_, namespace_);
+    }
+
+    const PluginFieldCollection* getFieldNames() noexcept override {
+        return &field_names_;
+    }
+
+    const AsciiChar* getPluginName() const noexcept override {
+        return name_.c_str();
+    }
+
+    const AsciiChar* getPluginVersion() const noexcept override {
+        return version_.c_str();
+    }
+
+    const AsciiChar* getPluginNamespace() const noexcept override {
+        return namespace_.c_str();
+    }
+
+private:
+    rust::Box<RustPluginCreator> creator_;
+    std::string name_;
+    std::string version_;
+    std::string namespace_;
+    PluginField field_;
+    PluginFieldCollection field_names_{};
+};
+
+} // namespace
+
+bool register_plugin_creator(
+    rust::Str name,
+    rust::Str version,
+    rust::Str plugin_namespace,
+    rust::Box<RustPluginCreator> creator) noexcept {
+    // the registry only keeps a reference, so creators live until exit
+    static std::mutex mutex;
+    static std::vector<std::unique_ptr<RustPluginCreatorV3>> creators;
+
+    auto plugin_creator = std::make_unique<RustPluginCreatorV3>(
+        std::move(creator), std::string(name), std::string(version), std::string(plugin_namespace));
+
+    std::lock_guard<std::mutex> lock(mutex);
+    if (!getPluginRegistry()->registerCreator(*plugin_creator, plugin_creator->getPluginNamespace())) {
+        return false;
+    }
+    creators.push_back(std::move(plugin_creator));
+    return true;
+}
+
+rust::Vec<rust::String> list_creators() noexcept {
+    rust::Vec<rust::String> names;
+    int32_t num_creators = 0;
+    const auto creators = getPluginRegistry()->getAllCreators(&num_creators);
+    for (int32_t i = 0; i < num_creators; ++i) {
+        const auto kind = std::string(creators[i]->getInterfaceInfo().kind);
+        if (kind == "PLUGIN CREATOR_V1") {
+            names.push_back(rust::String::lossy(
+                static_cast<IPluginCreator*>(creators[i])->getPluginName()));
+        } else if (kind == "PLUGIN CREATOR_V3ONE") {
+            names.push_back(rust::String::lossy(
+                static_cast<IPluginCreatorV3One*>(creators[i])->getPluginName()));
+        }
+    }
+    return names;
+}
+
+#else
+
+bool register_plugin_creator(
+    rust::Str name,
+    rust::Str version,
+    rust::Str plugin_namespace,
+    rust::Box<RustPluginCreator> creator) noexcept {
+    return false;
+}
+
+rust::Vec<rust::String> list_creators() noexcept {
+    rust::Vec<rust::String> names;
+    int32_t num_creators = 0;
+    const auto creators = getPluginRegistry()->getPluginCreatorList(&num_creators);
+    for (int32_t i = 0; i < num_creators; ++i) {
+        names.push_back(rust::String::lossy(creators[i]->getPluginName()));
+    }
+    return names;
+}
+
+#endif
+
+} // namespace trt_rs::plugin
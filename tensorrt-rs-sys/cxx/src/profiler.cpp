@@ -0,0 +1,25 @@
+#include "profiler.h"
+#include "tensorrt-rs-sys/src/lib.rs.h"
+
+namespace trt_rs::profiler {
+
+namespace {
+
+class RustProfilerImpl : public nvinfer1::IProfiler {
+public:
+    RustProfilerImpl(rust::Box<RustProfiler> profiler) : profiler_(std::move(profiler)) {}
+
+    void reportLayerTime(const char* layer_name, float ms) noexcept override {
+        profiler_report_layer_time(*profiler_, rust::String::lossy(layer_name), ms);
+    }
+private:
+    rust::Box<RustProfiler> profiler_;
+};
+
+} // namespace
+
+std::unique_ptr<nvinfer1::IProfiler> create_profiler(rust::Box<RustProfiler> profiler) noexcept {
+    return std::make_unique<RustProfilerImpl>(std::move(profiler));
+}
+
+} // namespace trt_rs::profiler
@@ -0,0 +1,45 @@
+#include "progress.h"
+#include "tensorrt-rs-sys/src/lib.rs.h"
+
+namespace trt_rs::progress {
+
+#if NV_TENSORRT_MAJOR >= 10
+
+namespace {
+
+class RustProgressMonitorImpl : public nvinfer1::IProgressMonitor {
+public:
+    RustProgressMonitorImpl(rust::Box<RustProgressMonitor> monitor)
+        : monitor_(std::move(monitor)) {}
+
+    void phaseStart(
+        const char* phase_name, const char* parent_phase, int32_t nb_steps) noexcept override {
+        // an empty parent stands for a top level phase
+        monitor_phase_start(
+            *monitor_,
+            rust::String::lossy(phase_name),
+            rust::String::lossy(parent_phase ? parent_phase : ""),
+            nb_steps);
+    }
+
+    bool stepComplete(const char* phase_name, int32_t step) noexcept override {
+        return monitor_step_complete(*monitor_, rust::String::lossy(phase_name), step);
+    }
+
+    void phaseFinish(const char* phase_name) noexcept override {
+        monitor_phase_finish(*monitor_, rust::String::lossy(phase_name));
+    }
+private:
+    rust::Box<RustProgressMonitor> monitor_;
+};
+
+} // namespace
+
+std::unique_ptr<nvinfer1::IProgressMonitor> create_progress_monitor(
+    rust::Box<RustProgressMonitor> monitor) noexcept {
+    return std::make_unique<RustProgressMonitorImpl>(std::move(monitor));
+}
+
+#endif
+
+} // namespace trt_rs::progress
@@ -0,0 +1,73 @@
+#include "recorder.h"
+#include "tensorrt-rs-sys/src/lib.rs.h"
+
+namespace trt_rs::recorder {
+
+int32_t ErrorRecorderImpl::getNbErrors() const noexcept {
+    std::lock_guard<std::mutex> lock(mutex_);
+    return static_cast<int32_t>(errors_.size());
+}
+
+ErrorCode ErrorRecorderImpl::getErrorCode(int32_t errorIdx) const noexcept {
+    std::lock_guard<std::mutex> lock(mutex_);
+    if (errorIdx < 0 || static_cast<std::size_t>(errorIdx) >= errors_.size()) {
+        return ErrorCode::kINVALID_ARGUMENT;
+    }
+    return errors_[errorIdx].first;
+}
+
+IErrorRecorder::ErrorDesc ErrorRecorderImpl::getErrorDesc(int32_t errorIdx) const noexcept {
+    std::lock_guard<std::mutex> lock(mutex_);
+    if (errorIdx < 0 || static_cast<std::size_t>(errorIdx) >= errors_.size()) {
+        return "Error index out of range";
+    }
+    // valid until the recorder is cleared
+    return errors_[errorIdx].second.c_str();
+}
+
+bool ErrorRecorderImpl::hasOverflowed() const noexcept {
+    std::lock_guard<std::mutex> lock(mutex_);
+    return overflowed_;
+}
+
+void ErrorRecorderImpl::clear() noexcept {
+    std::lock_guard<std::mutex> lock(mutex_);
+    errors_.clear();
+    overflowed_ = false;
+}
+
+bool ErrorRecorderImpl::reportError(ErrorCode val, ErrorDesc desc) noexcept {
+    {
+        std::lock_guard<std::mutex> lock(mutex_);
+        if (errors_.size() >= kMAX_ERRORS) {
+            overflowed_ = true;
+        } else {
+            errors_.emplace_back(val, desc ? desc : "");
+        }
+    }
+    if (forward_) {
+        forward_->reportError(val, desc);
+    }
+    // no error is fatal, the failing call returns as it would without a recorder
+    return false;
+}
+
+std::vector<std::pair<ErrorCode, std::string>> ErrorRecorderImpl::take() noexcept {
+    std::lock_guard<std::mutex> lock(mutex_);
+    overflowed_ = false;
+    return std::exchange(errors_, {});
+}
+
+rust::Vec<RecordedError> ErrorRecorder::take_errors() const noexcept {
+    auto errors = rust::Vec<RecordedError>();
+    for (auto& [code, desc] : recorder_->take()) {
+        errors.push_back(RecordedError{static_cast<int32_t>(code), rust::String::lossy(desc)});
+    }
+    return errors;
+}
+
+std::unique_ptr<ErrorRecorder> create_error_recorder() noexcept {
+    return std::make_unique<ErrorRecorder>(std::make_shared<ErrorRecorderImpl>());
+}
+
+} // namespace trt_rs::recorder
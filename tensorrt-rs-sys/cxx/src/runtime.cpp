@@ -1,4 +1,5 @@
 #include "runtime.h"
+#include "tensorrt-rs-sys/src/lib.rs.h"
 
 namespace trt_rs::runtime {
 
@@ -7,9 +8,37 @@ Runtime::deserialize(rust::Slice<const std::uint8_t> data) noexcept {
     auto engine = runtime_->deserializeCudaEngine(data.data(), data.size());
     if (!engine) {
         return nullptr;
-    } else {
-        return std::make_unique<CudaEngine>(std::unique_ptr<ICudaEngine>(engine));
     }
+    // the engine frees its memory through the allocator when destroyed, and
+    // reports to the recorder it inherits, or is given later, until then
+    auto slot = std::make_shared<std::shared_ptr<recorder::ErrorRecorderImpl>>(recorder_);
+    auto deleter = [allocator = allocator_, slot](ICudaEngine* engine) {
+        delete engine;
+    };
+    return std::make_unique<CudaEngine>(
+        std::shared_ptr<ICudaEngine>(engine, deleter), std::move(slot));
+}
+
+void Runtime::set_gpu_allocator(rust::Box<allocator::RustGpuAllocator> allocator) noexcept {
+    allocator_ = allocator::create_gpu_allocator(std::move(allocator));
+    runtime_->setGpuAllocator(allocator_.get());
+}
+
+bool Runtime::is_engine_compatible(rust::Slice<const std::uint8_t> data) const noexcept {
+#if NV_TENSORRT_MAJOR > 10 || (NV_TENSORRT_MAJOR == 10 && NV_TENSORRT_MINOR >= 12)
+    // Only inspects the plan header (TensorRT version, target compute
+    // capability, required driver), so nothing is allocated on the device.
+    uint64_t diagnostics = 0;
+    const auto validity = runtime_->getEngineValidity(data.data(), data.size(), &diagnostics);
+    return validity != nvinfer1::EngineValidity::kINVALID;
+#else
+    // Older TensorRT versions have no header check; a mismatched plan makes
+    // deserialization fail cleanly, so try it and throw the engine away. This
+    // costs as much as a real deserialization, device memory included.
+    const auto engine = std::unique_ptr<ICudaEngine>(
+        runtime_->deserializeCudaEngine(data.data(), data.size()));
+    return engine != nullptr;
+#endif
 }
 
 rust::Vec<int32_t> CudaEngine::get_tensor_shape(rust::Str name) const noexcept {
@@ -23,13 +52,31 @@ rust::Vec<int32_t> CudaEngine::get_tensor_shape(rust::Str name) const noexcept {
     return dims_vec;
 }
 
+rust::Vec<int32_t> CudaEngine::get_profile_shape(
+    rust::Str name, int32_t profile_index, int32_t select) const noexcept {
+    const auto name_str = std::string(name);
+    const auto dims = engine_->getProfileShape(
+        name_str.c_str(), profile_index, static_cast<nvinfer1::OptProfileSelector>(select));
+    auto dims_vec = rust::Vec<int32_t>();
+    // nbDims is -1 for outputs, unknown names and invalid profile indices
+    if (dims.nbDims < 0) {
+        return dims_vec;
+    }
+    dims_vec.reserve(dims.nbDims);
+    for (int32_t i = 0; i < dims.nbDims; ++i) {
+        dims_vec.push_back(dims.d[i]);
+    }
+    return dims_vec;
+}
+
 std::unique_ptr<ExecutionContext>
 CudaEngine::create_execution_context() noexcept {
     auto context = engine_->createExecutionContext();
     if (!context) {
         return nullptr;
     } else {
-        return std::make_unique<ExecutionContext>(std::unique_ptr<IExecutionContext>(context));
+        return std::make_unique<ExecutionContext>(
+            std::unique_ptr<IExecutionContext>(context), engine_, *recorder_);
     }
 }
 
@@ -39,8 +86,94 @@ CudaEngine::create_execution_context_without_device_memory() noexcept {
     if (!context) {
         return nullptr;
     } else {
-        return std::make_unique<ExecutionContext>(std::unique_ptr<IExecutionContext>(context));
+        return std::make_unique<ExecutionContext>(
+            std::unique_ptr<IExecutionContext>(context), engine_, *recorder_);
+    }
+}
+
+std::unique_ptr<HostMemory> CudaEngine::serialize() const noexcept {
+    auto memory = engine_->serialize();
+    if (!memory) {
+        return nullptr;
+    } else {
+        return std::make_unique<HostMemory>(std::unique_ptr<IHostMemory>(memory));
+    }
+}
+
+std::unique_ptr<SerializationConfig> CudaEngine::create_serialization_config() const noexcept {
+#if NV_TENSORRT_MAJOR >= 10
+    auto config = engine_->createSerializationConfig();
+    if (!config) {
+        return nullptr;
+    } else {
+        return std::make_unique<SerializationConfig>(
+            std::unique_ptr<nvinfer1::ISerializationConfig>(config));
+    }
+#else
+    return nullptr;
+#endif
+}
+
+std::unique_ptr<HostMemory> CudaEngine::serialize_with_config(SerializationConfig& config) const noexcept {
+#if NV_TENSORRT_MAJOR >= 10
+    auto memory = engine_->serializeWithConfig(config.get());
+    if (!memory) {
+        return nullptr;
+    } else {
+        return std::make_unique<HostMemory>(std::unique_ptr<IHostMemory>(memory));
     }
+#else
+    return nullptr;
+#endif
+}
+
+#if NV_TENSORRT_MAJOR >= 10
+bool SerializationConfig::set_flags(uint32_t flags) noexcept {
+    return config_->setFlags(flags);
+}
+
+uint32_t SerializationConfig::get_flags() const noexcept {
+    return config_->getFlags();
+}
+
+bool SerializationConfig::set_flag(int32_t flag) noexcept {
+    return config_->setFlag(static_cast<nvinfer1::SerializationFlag>(flag));
+}
+
+bool SerializationConfig::clear_flag(int32_t flag) noexcept {
+    return config_->clearFlag(static_cast<nvinfer1::SerializationFlag>(flag));
+}
+
+bool SerializationConfig::get_flag(int32_t flag) const noexcept {
+    return config_->getFlag(static_cast<nvinfer1::SerializationFlag>(flag));
+}
+#else
+bool SerializationConfig::set_flags(uint32_t) noexcept {
+    return false;
+}
+
+uint32_t SerializationConfig::get_flags() const noexcept {
+    return 0;
+}
+
+bool SerializationConfig::set_flag(int32_t) noexcept {
+    return false;
+}
+
+bool SerializationConfig::clear_flag(int32_t) noexcept {
+    return false;
+}
+
+bool SerializationConfig::get_flag(int32_t) const noexcept {
+    return false;
+}
+#endif
+
+void ExecutionContext::set_profiler(rust::Box<profiler::RustProfiler> profiler) noexcept {
+    // the context stops reporting to the old profiler before it is destroyed
+    auto old = std::move(profiler_);
+    profiler_ = profiler::create_profiler(std::move(profiler));
+    context_->setProfiler(profiler_.get());
 }
 
 rust::Vec<int32_t> ExecutionContext::get_tensor_strides(rust::Str name) const noexcept {
@@ -80,6 +213,72 @@ bool ExecutionContext::enqueue_v3(std::size_t stream) noexcept {
     return context_->enqueueV3(reinterpret_cast<cudaStream_t>(stream));
 }
 
+// Records one enqueue into a graph, or returns nullptr if the capture failed.
+static CUgraph capture_enqueue(IExecutionContext& context, std::size_t stream) noexcept {
+    const auto cu_stream = reinterpret_cast<CUstream>(stream);
+    // TensorRT forks work onto its aux streams and joins it back with events;
+    // streams that wait on an event recorded during capture join the capture,
+    // so the fork/join ends up in the graph. Global mode also rejects unsafe
+    // calls from other threads while capturing.
+    if (cuStreamBeginCapture(cu_stream, CU_STREAM_CAPTURE_MODE_GLOBAL) != CUDA_SUCCESS) {
+        return nullptr;
+    }
+    const auto enqueued = context.enqueueV3(reinterpret_cast<cudaStream_t>(stream));
+
+    CUgraph graph = nullptr;
+    const auto captured = cuStreamEndCapture(cu_stream, &graph) == CUDA_SUCCESS;
+    if (!enqueued || !captured) {
+        if (graph) {
+            cuGraphDestroy(graph);
+        }
+        return nullptr;
+    }
+    return graph;
+}
+
+bool CudaGraph::update(CUgraph graph) noexcept {
+#if CUDA_VERSION >= 12000
+    CUgraphExecUpdateResultInfo info;
+    const auto status = cuGraphExecUpdate(exec_, graph, &info);
+#else
+    CUgraphNode error_node = nullptr;
+    CUgraphExecUpdateResult result;
+    const auto status = cuGraphExecUpdate(exec_, graph, &error_node, &result);
+#endif
+    if (status != CUDA_SUCCESS) {
+        return false;
+    }
+    cuGraphDestroy(graph_);
+    graph_ = graph;
+    return true;
+}
+
+std::unique_ptr<CudaGraph> ExecutionContext::capture_graph(std::size_t stream) noexcept {
+    auto graph = capture_enqueue(*context_, stream);
+    if (!graph) {
+        return nullptr;
+    }
+
+    CUgraphExec exec = nullptr;
+    if (cuGraphInstantiateWithFlags(&exec, graph, 0) != CUDA_SUCCESS) {
+        cuGraphDestroy(graph);
+        return nullptr;
+    }
+    return std::make_unique<CudaGraph>(graph, exec);
+}
+
+bool ExecutionContext::update_graph(CudaGraph& graph, std::size_t stream) noexcept {
+    auto captured = capture_enqueue(*context_, stream);
+    if (!captured) {
+        return false;
+    }
+    if (!graph.update(captured)) {
+        cuGraphDestroy(captured);
+        return false;
+    }
+    return true;
+}
+
 std::unique_ptr<Runtime> create_runtime(Logger& logger) {
     auto runtime = nvinfer1::createInferRuntime(logger);
     if (!runtime) {
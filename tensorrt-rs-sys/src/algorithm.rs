@@ -0,0 +1,148 @@
+use crate::ffi;
+
+/// One way TensorRT can implement a layer, as offered to and reported by an
+/// `AlgorithmSelector`. The implementation and tactic identify it across
+/// builds on the same device and TensorRT version.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Algorithm {
+    pub implementation: i64,
+    pub tactic: i64,
+    pub timing_ms: f32,
+    pub workspace_size: usize,
+}
+
+impl From<&ffi::AlgorithmChoice> for Algorithm {
+    fn from(choice: &ffi::AlgorithmChoice) -> Self {
+        Self {
+            implementation: choice.implementation,
+            tactic: choice.tactic,
+            timing_ms: choice.timing_ms,
+            workspace_size: choice.workspace_size,
+        }
+    }
+}
+
+/// Restricts the algorithms the builder picks from, exposed to TensorRT as
+/// an `IAlgorithmSelector`. Pinning the algorithms reported by one build in
+/// later builds reproduces the same engine. Attach it with
+/// `BuilderConfig::set_algorithm_selector`.
+pub trait AlgorithmSelector: Send {
+    /// The indices of the `choices` layer `layer_name` may use; the builder
+    /// times those and keeps the fastest. An empty selection leaves the
+    /// choice to TensorRT; indices past `choices` are ignored.
+    fn select_algorithms(&mut self, layer_name: &str, choices: &[Algorithm]) -> Vec<usize>;
+
+    /// The algorithm the builder ended up using for each layer.
+    fn report_algorithms(&mut self, _selections: &[(String, Algorithm)]) {}
+}
+
+pub(crate) struct RustAlgorithmSelector(pub(crate) Box<dyn AlgorithmSelector>);
+
+pub(crate) fn selector_select_algorithms(
+    selector: &mut RustAlgorithmSelector,
+    layer_name: &str,
+    choices: &[ffi::AlgorithmChoice],
+) -> Vec<i32> {
+    let choices = choices.iter().map(Algorithm::from).collect::<Vec<_>>();
+
+    // the selection TensorRT hands over holds at most one slot per choice
+    let mut selected = selector.0.select_algorithms(layer_name, &choices);
+    selected.retain(|&index| index < choices.len());
+    selected.truncate(choices.len());
+
+    selected.into_iter().map(|index| index as i32).collect()
+}
+
+pub(crate) fn selector_report_algorithms(
+    selector: &mut RustAlgorithmSelector,
+    reports: &[ffi::AlgorithmReport],
+) {
+    let selections = reports
+        .iter()
+        .map(|report| (report.layer_name.clone(), Algorithm::from(&report.choice)))
+        .collect::<Vec<_>>();
+
+    selector.0.report_algorithms(&selections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder::Builder, runtime::DataType};
+    use std::sync::{Arc, Mutex};
+
+    // Picks the first choice of every layer and records what was reported.
+    struct FirstChoiceSelector {
+        selected: Arc<Mutex<Vec<(String, Algorithm)>>>,
+        reported: Arc<Mutex<Vec<(String, Algorithm)>>>,
+    }
+
+    impl AlgorithmSelector for FirstChoiceSelector {
+        fn select_algorithms(&mut self, layer_name: &str, choices: &[Algorithm]) -> Vec<usize> {
+            self.selected.lock().unwrap().push((layer_name.to_string(), choices[0]));
+            vec![0]
+        }
+
+        fn report_algorithms(&mut self, selections: &[(String, Algorithm)]) {
+            self.reported.lock().unwrap().extend_from_slice(selections);
+        }
+    }
+
+    #[test]
+    fn test_algorithm_selector() {
+        let selected = Arc::new(Mutex::new(vec![]));
+        let reported = Arc::new(Mutex::new(vec![]));
+        let selector = FirstChoiceSelector {
+            selected: selected.clone(),
+            reported: reported.clone(),
+        };
+
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        assert!(!config.has_algorithm_selector());
+        config.set_algorithm_selector(selector);
+        assert!(config.has_algorithm_selector());
+
+        let mut network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[1, 2, 8, 8]).unwrap();
+        let output = network
+            .add_convolution("conv", input, 4, &[3, 3], &[1.0f32; 4 * 2 * 3 * 3], &[0.0f32; 4])
+            .unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+        assert!(builder.build_serialized_network(&mut network, &mut config).is_some());
+
+        // every layer ends up with the algorithm it was pinned to
+        let selected = selected.lock().unwrap();
+        for (layer_name, algorithm) in reported.lock().unwrap().iter() {
+            let pinned = selected.iter().rev().find(|(name, _)| name == layer_name);
+            if let Some((_, first)) = pinned {
+                assert_eq!(algorithm.implementation, first.implementation);
+                assert_eq!(algorithm.tactic, first.tactic);
+            }
+        }
+    }
+
+    #[test]
+    fn test_select_out_of_range() {
+        struct AllSelector;
+
+        impl AlgorithmSelector for AllSelector {
+            fn select_algorithms(&mut self, _: &str, choices: &[Algorithm]) -> Vec<usize> {
+                (0..=choices.len()).chain(0..choices.len()).collect()
+            }
+        }
+
+        let mut selector = RustAlgorithmSelector(Box::new(AllSelector));
+        let choice = ffi::AlgorithmChoice {
+            implementation: 1,
+            tactic: 2,
+            timing_ms: 0.5,
+            workspace_size: 0,
+        };
+        assert_eq!(Algorithm::from(&choice).tactic, 2);
+        // the index past the choices is dropped, and so is the repeat that
+        // would overflow the selection
+        assert_eq!(selector_select_algorithms(&mut selector, "conv", &[choice]), vec![0]);
+    }
+}
@@ -0,0 +1,116 @@
+/// Serves the device memory TensorRT allocates, e.g. from an application's
+/// memory pool shared with other CUDA frameworks, exposed to TensorRT as an
+/// `IGpuAllocator`. Set it with `Runtime::set_gpu_allocator` or
+/// `Builder::set_gpu_allocator`.
+///
+/// TensorRT may call the allocator from several threads at once.
+///
+/// # Safety
+///
+/// TensorRT reads and writes the memory `allocate` returns without any
+/// checks. A non-zero address has to point at `size` bytes of device memory
+/// with the requested alignment, which nothing else uses until TensorRT hands
+/// it to `deallocate`.
+pub unsafe trait GpuAllocator: Send + Sync {
+    /// Returns device memory of at least `size` bytes aligned to `alignment`,
+    /// or 0 if it cannot be allocated. `resizable` memory may later be
+    /// reallocated to a different size.
+    fn allocate(&self, size: u64, alignment: u64, resizable: bool) -> usize;
+
+    /// Frees memory returned by `allocate`; returns false if it fails.
+    fn deallocate(&self, ptr: usize) -> bool;
+}
+
+pub(crate) struct RustGpuAllocator(pub(crate) Box<dyn GpuAllocator>);
+
+pub(crate) fn allocator_allocate(
+    allocator: &RustGpuAllocator,
+    size: u64,
+    alignment: u64,
+    resizable: bool,
+) -> usize {
+    allocator.0.allocate(size, alignment, resizable)
+}
+
+pub(crate) fn allocator_deallocate(allocator: &RustGpuAllocator, ptr: usize) -> bool {
+    allocator.0.deallocate(ptr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::Builder,
+        device,
+        runtime::{DataType, Runtime},
+    };
+    use cuda_rs::device::CuDevice;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    // Forwards to `cuMemAlloc` and tracks what is still allocated.
+    #[derive(Clone, Default)]
+    struct TrackingAllocator {
+        live: Arc<Mutex<HashMap<usize, u64>>>,
+        total: Arc<Mutex<u64>>,
+    }
+
+    // SAFETY: the memory comes from `cuMemAlloc` and is only freed when
+    // TensorRT deallocates it
+    unsafe impl GpuAllocator for TrackingAllocator {
+        fn allocate(&self, size: u64, alignment: u64, _resizable: bool) -> usize {
+            // cuMemAlloc aligns to 256 bytes
+            if alignment > 256 {
+                return 0;
+            }
+            match device::mem_alloc(size as usize) {
+                Some(ptr) => {
+                    self.live.lock().unwrap().insert(ptr, size);
+                    *self.total.lock().unwrap() += size;
+                    ptr
+                }
+                None => 0,
+            }
+        }
+
+        fn deallocate(&self, ptr: usize) -> bool {
+            self.live.lock().unwrap().remove(&ptr).is_some() && unsafe { device::mem_free(ptr) }
+        }
+    }
+
+    #[test]
+    fn test_gpu_allocator() {
+        cuda_rs::init().unwrap();
+        let device = CuDevice::new(0).unwrap();
+        let ctx = device.retain_primary_context().unwrap();
+        let _guard = ctx.guard().unwrap();
+
+        let allocator = TrackingAllocator::default();
+
+        let mut builder = Builder::new().unwrap();
+        builder.set_gpu_allocator(allocator.clone());
+        let mut config = builder.create_builder_config().unwrap();
+        let mut network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[1, 2, 8, 8]).unwrap();
+        let output = network
+            .add_convolution("conv", input, 4, &[3, 3], &[1.0f32; 4 * 2 * 3 * 3], &[0.0f32; 4])
+            .unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+        let plan = builder.build_serialized_network(&mut network, &mut config).unwrap();
+        drop((network, config, builder));
+        assert!(*allocator.total.lock().unwrap() > 0);
+        assert!(allocator.live.lock().unwrap().is_empty());
+
+        let mut runtime = Runtime::new().unwrap();
+        runtime.set_gpu_allocator(allocator.clone());
+        let mut engine = runtime.deserialize(plan.as_bytes()).unwrap();
+        let context = engine.create_execution_context().unwrap();
+        let allocated = *allocator.total.lock().unwrap();
+        drop((context, engine, runtime));
+        assert!(allocated > 0);
+        assert!(allocator.live.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,803 @@
+use crate::{
+    algorithm::{AlgorithmSelector, RustAlgorithmSelector},
+    allocator::{GpuAllocator, RustGpuAllocator},
+    calibrator::{Int8Calibrator, RustCalibrator},
+    ffi,
+    logger::Logger,
+    progress::{ProgressMonitor, RustProgressMonitor},
+    runtime::{DataType, HostMemory, OptProfileSelector, ProfilingVerbosity, TensorFormat},
+};
+use cxx::UniquePtr;
+use std::{fs, io, marker::PhantomData, path::Path};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MemoryPoolType {
+    // Scratch memory used by layer implementations while the engine runs.
+    WORKSPACE = 0,
+
+    // Fast software managed RAM used by DLA to communicate within a layer.
+    DLAMANAGEDSRAM = 1,
+
+    // Host RAM used by DLA to share intermediate tensor data across operations.
+    DLALOCALDRAM = 2,
+
+    // Host RAM used by DLA to store weights and metadata for execution.
+    DLAGLOBALDRAM = 3,
+
+    // Device memory used by the builder to time tactics.
+    TACTICDRAM = 4,
+}
+
+impl TryFrom<i32> for MemoryPoolType {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(MemoryPoolType::WORKSPACE),
+            1 => Ok(MemoryPoolType::DLAMANAGEDSRAM),
+            2 => Ok(MemoryPoolType::DLALOCALDRAM),
+            3 => Ok(MemoryPoolType::DLAGLOBALDRAM),
+            4 => Ok(MemoryPoolType::TACTICDRAM),
+            pool => Err(pool),
+        }
+    }
+}
+
+// Mapped to `nvinfer1::BuilderFlag` on the C++ side, as TensorRT 10
+// renumbered the flags.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BuilderFlag {
+    // Enable FP16 layer selection, with FP32 fallback.
+    FP16 = 0,
+
+    // Enable INT8 layer selection, with FP32 fallback with FP16 fallback if kFP16 also specified.
+    INT8 = 1,
+
+    // Enable building a refittable engine.
+    REFIT = 2,
+}
+
+impl TryFrom<i32> for BuilderFlag {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(BuilderFlag::FP16),
+            1 => Ok(BuilderFlag::INT8),
+            2 => Ok(BuilderFlag::REFIT),
+            flag => Err(flag),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NetworkDefinitionCreationFlag {
+    // Mark the network to be an explicit batch network. Always set, as
+    // TensorRT 10 removed implicit batch networks.
+    EXPLICITBATCH = 0,
+
+    // Mark the network to be strongly typed: tensor types follow from the
+    // inputs and the layer specifications, and builder precision flags are
+    // not allowed. Requires TensorRT 9 or later.
+    STRONGLYTYPED = 1,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DeviceType {
+    // Run layers on the GPU.
+    GPU = 0,
+
+    // Run layers on a DLA core, falling back to the GPU if GPUFALLBACK is set.
+    DLA = 1,
+}
+
+impl TryFrom<i32> for DeviceType {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(DeviceType::GPU),
+            1 => Ok(DeviceType::DLA),
+            device_type => Err(device_type),
+        }
+    }
+}
+
+pub struct Builder {
+    builder: UniquePtr<ffi::Builder>,
+    logger: Logger,
+}
+
+impl Builder {
+    pub fn new() -> Option<Self> {
+        let mut logger = Logger::new();
+        let builder = ffi::create_builder(logger.0.pin_mut());
+        if builder.is_null() {
+            None
+        } else {
+            Some(Self { builder, logger })
+        }
+    }
+
+    pub fn logger(&mut self) -> &mut Logger {
+        &mut self.logger
+    }
+
+    /// Creates an explicit-batch network definition.
+    pub fn create_network(&mut self) -> Option<NetworkDefinition> {
+        let network = self.builder.pin_mut().create_network();
+        if network.is_null() {
+            None
+        } else {
+            Some(NetworkDefinition(network))
+        }
+    }
+
+    /// Creates a network with `flags` set; returns `None` for flags the
+    /// linked TensorRT does not support.
+    pub fn create_network_with_flags(
+        &mut self,
+        flags: &[NetworkDefinitionCreationFlag],
+    ) -> Option<NetworkDefinition> {
+        let flags = flags.iter().fold(0u32, |bits, &flag| bits | 1 << flag as u32);
+        let network = self.builder.pin_mut().create_network_v2(flags);
+        if network.is_null() {
+            None
+        } else {
+            Some(NetworkDefinition(network))
+        }
+    }
+
+    pub fn create_builder_config(&mut self) -> Option<BuilderConfig> {
+        let config = self.builder.pin_mut().create_builder_config();
+        if config.is_null() {
+            None
+        } else {
+            Some(BuilderConfig(config))
+        }
+    }
+
+    /// Creates an empty optimization profile, which lives as long as the
+    /// builder; add it to a config once its input shapes are set.
+    pub fn create_optimization_profile(&self) -> Option<OptimizationProfile<'_>> {
+        let profile = self.builder.create_optimization_profile();
+        if profile == 0 {
+            None
+        } else {
+            Some(OptimizationProfile(profile, PhantomData))
+        }
+    }
+
+    /// Builds `network` into a serialized engine, or returns `None` if the
+    /// build fails (the reason is reported through the builder's logger).
+    pub fn build_serialized_network(
+        &mut self,
+        network: &mut NetworkDefinition,
+        config: &mut BuilderConfig,
+    ) -> Option<HostMemory> {
+        let memory = self.builder.pin_mut().build_serialized_network(
+            network.0.pin_mut(),
+            config.0.pin_mut(),
+        );
+        if memory.is_null() {
+            None
+        } else {
+            Some(HostMemory(memory))
+        }
+    }
+
+    /// Serves the device memory used while building, e.g. for timing
+    /// tactics, from `allocator` instead of TensorRT's default allocator.
+    pub fn set_gpu_allocator<A: GpuAllocator + 'static>(&mut self, allocator: A) {
+        let allocator = Box::new(RustGpuAllocator(Box::new(allocator)));
+        self.builder.pin_mut().set_gpu_allocator(allocator)
+    }
+}
+
+/// A tensor owned by a `NetworkDefinition`, which it borrows; it is only
+/// meaningful for the network that produced it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NetworkTensor<'a>(usize, PhantomData<&'a NetworkDefinition>);
+
+impl NetworkTensor<'_> {
+    fn from_raw(tensor: usize) -> Option<Self> {
+        if tensor == 0 {
+            None
+        } else {
+            Some(Self(tensor, PhantomData))
+        }
+    }
+}
+
+pub struct NetworkDefinition(pub(crate) UniquePtr<ffi::NetworkDefinition>);
+
+impl NetworkDefinition {
+    /// The most dimensions a tensor can have (`Dims::MAX_DIMS`).
+    pub const MAX_DIMS: usize = 8;
+
+    /// `None` if TensorRT rejects the input, or for more than
+    /// `MAX_DIMS` dimensions.
+    pub fn add_input(
+        &self,
+        name: &str,
+        dtype: DataType,
+        dims: &[i32],
+    ) -> Option<NetworkTensor<'_>> {
+        NetworkTensor::from_raw(self.0.add_input(name, dtype as _, dims))
+    }
+
+    pub fn add_identity(&self, input: NetworkTensor<'_>) -> Option<NetworkTensor<'_>> {
+        NetworkTensor::from_raw(self.0.add_identity(input.0))
+    }
+
+    /// Adds a layer returning the indices of the non-zero elements of
+    /// `input`, an output with a data-dependent shape. TensorRT 8.5 and
+    /// later only.
+    pub fn add_non_zero(&self, input: NetworkTensor<'_>) -> Option<NetworkTensor<'_>> {
+        NetworkTensor::from_raw(self.0.add_non_zero(input.0))
+    }
+
+    /// Adds a constant float tensor; the network keeps its own copy of
+    /// `weights` until it is dropped. `None` unless `weights` holds exactly
+    /// one value per element of `dims`.
+    pub fn add_constant(&self, dims: &[i32], weights: &[f32]) -> Option<NetworkTensor<'_>> {
+        NetworkTensor::from_raw(self.0.add_constant(dims, weights))
+    }
+
+    /// Adds a float convolution layer named `name` with `nb_output_maps`
+    /// output channels; `bias` may be empty. The network keeps its own copy
+    /// of the weights. `None` if TensorRT rejects the layer, or for a
+    /// `kernel_size` of more than `MAX_DIMS` dimensions.
+    pub fn add_convolution(
+        &self,
+        name: &str,
+        input: NetworkTensor<'_>,
+        nb_output_maps: i32,
+        kernel_size: &[i32],
+        kernel: &[f32],
+        bias: &[f32],
+    ) -> Option<NetworkTensor<'_>> {
+        NetworkTensor::from_raw(self.0.add_convolution(
+            name,
+            input.0,
+            nb_output_maps,
+            kernel_size,
+            kernel,
+            bias,
+        ))
+    }
+
+    pub fn mark_output(&self, tensor: NetworkTensor<'_>) {
+        self.0.mark_output(tensor.0)
+    }
+
+    pub fn unmark_output(&self, tensor: NetworkTensor<'_>) {
+        self.0.unmark_output(tensor.0)
+    }
+
+    pub fn get_nb_layers(&self) -> i32 {
+        self.0.get_nb_layers()
+    }
+
+    pub fn get_nb_inputs(&self) -> i32 {
+        self.0.get_nb_inputs()
+    }
+
+    pub fn get_input(&self, index: i32) -> Option<NetworkTensor<'_>> {
+        NetworkTensor::from_raw(self.0.get_input(index))
+    }
+
+    pub fn get_nb_outputs(&self) -> i32 {
+        self.0.get_nb_outputs()
+    }
+
+    pub fn get_output(&self, index: i32) -> Option<NetworkTensor<'_>> {
+        NetworkTensor::from_raw(self.0.get_output(index))
+    }
+
+    pub fn set_tensor_name(&self, tensor: NetworkTensor<'_>, name: &str) {
+        self.0.set_tensor_name(tensor.0, name)
+    }
+
+    pub fn get_tensor_name(&self, tensor: NetworkTensor<'_>) -> String {
+        self.0.get_tensor_name(tensor.0)
+    }
+
+    pub fn get_tensor_dimensions(&self, tensor: NetworkTensor<'_>) -> Vec<i32> {
+        self.0.get_tensor_dimensions(tensor.0)
+    }
+
+    pub fn get_tensor_dtype(&self, tensor: NetworkTensor<'_>) -> DataType {
+        let dtype = self.0.get_tensor_dtype(tensor.0);
+        DataType::try_from(dtype).unwrap_or_else(|dtype| panic!("Invalid data type: {}", dtype))
+    }
+
+    /// Restricts the formats TensorRT may pick for a network input or output,
+    /// e.g. to make it accept a vectorized layout like `CHW32` directly.
+    pub fn set_tensor_allowed_formats(&self, tensor: NetworkTensor<'_>, formats: &[TensorFormat]) {
+        let formats = formats.iter().fold(0u32, |mask, &format| mask | 1 << format as u32);
+        self.0.set_tensor_allowed_formats(tensor.0, formats)
+    }
+
+    pub fn is_network_input(&self, tensor: NetworkTensor<'_>) -> bool {
+        self.0.is_network_input(tensor.0)
+    }
+
+    pub fn is_network_output(&self, tensor: NetworkTensor<'_>) -> bool {
+        self.0.is_network_output(tensor.0)
+    }
+
+    pub fn get_flag(&self, flag: NetworkDefinitionCreationFlag) -> bool {
+        self.0.get_flag(flag as _)
+    }
+}
+
+/// The allowed shapes of a network's dynamic inputs, owned by the `Builder`
+/// that created it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OptimizationProfile<'a>(usize, PhantomData<&'a Builder>);
+
+impl OptimizationProfile<'_> {
+    /// Sets the `select` shape of input `name`; returns false if it is
+    /// invalid, e.g. a MIN above the OPT or MAX already set, or has more than
+    /// `NetworkDefinition::MAX_DIMS` dimensions.
+    pub fn set_dimensions(&mut self, name: &str, select: OptProfileSelector, dims: &[i32]) -> bool {
+        ffi::set_profile_dimensions(self.0, name, select as _, dims)
+    }
+}
+
+/// The tactic timings of earlier builds, which let rebuilds skip timing the
+/// same layers again. Configs the cache is set on share it, and builds add
+/// their timings to it.
+pub struct TimingCache(pub(crate) UniquePtr<ffi::TimingCache>);
+
+impl TimingCache {
+    /// Loads the cache saved at `path` with `save_to_file`, or creates an
+    /// empty one if there is no file yet. Returns `None` if the file is not
+    /// a valid cache.
+    pub fn load_from_file<P: AsRef<Path>>(
+        config: &BuilderConfig,
+        path: P,
+    ) -> io::Result<Option<Self>> {
+        let blob = match fs::read(path) {
+            Ok(blob) => blob,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+            Err(err) => return Err(err),
+        };
+
+        Ok(config.create_timing_cache(&blob))
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        match self.serialize() {
+            Some(blob) => fs::write(path, blob.as_bytes()),
+            None => Err(io::Error::other("failed to serialize the timing cache")),
+        }
+    }
+
+    pub fn serialize(&self) -> Option<HostMemory> {
+        let memory = self.0.serialize();
+        if memory.is_null() {
+            None
+        } else {
+            Some(HostMemory(memory))
+        }
+    }
+
+    /// Adds the timings of `other`; returns false if it was created on a
+    /// different device or TensorRT version, unless `ignore_mismatch`.
+    pub fn combine(&mut self, other: &TimingCache, ignore_mismatch: bool) -> bool {
+        self.0.pin_mut().combine(&other.0, ignore_mismatch)
+    }
+
+    pub fn reset(&mut self) -> bool {
+        self.0.pin_mut().reset()
+    }
+}
+
+pub struct BuilderConfig(pub(crate) UniquePtr<ffi::BuilderConfig>);
+
+impl BuilderConfig {
+    pub const MAX_OPTIMIZATION_LEVEL: u8 = 5;
+
+    /// Caps the memory the builder and the built engine may use from `pool`.
+    /// Layers whose tactics need more than the limit fall back to slower
+    /// ones, and the build fails if none fits.
+    pub fn set_memory_pool_limit(&mut self, pool: MemoryPoolType, size: usize) {
+        self.0.pin_mut().set_memory_pool_limit(pool as _, size)
+    }
+
+    pub fn get_memory_pool_limit(&self, pool: MemoryPoolType) -> usize {
+        self.0.get_memory_pool_limit(pool as _)
+    }
+
+    /// Trades build time for engine performance: level 0 builds fastest,
+    /// level 5 searches the most tactics, and TensorRT defaults to 3.
+    /// Returns the rejected level if it is above `MAX_OPTIMIZATION_LEVEL`.
+    pub fn set_optimization_level(&mut self, level: u8) -> Result<(), u8> {
+        if level > Self::MAX_OPTIMIZATION_LEVEL {
+            return Err(level);
+        }
+        self.0.pin_mut().set_builder_optimization_level(level as _);
+        Ok(())
+    }
+
+    pub fn get_optimization_level(&self) -> u8 {
+        self.0.get_builder_optimization_level() as _
+    }
+
+    /// Caps how many auxiliary streams the built engine may run layers on
+    /// in parallel; 0 keeps every layer on the enqueue stream.
+    pub fn set_max_aux_streams(&mut self, nb_streams: i32) {
+        self.0.pin_mut().set_max_aux_streams(nb_streams)
+    }
+
+    pub fn get_max_aux_streams(&self) -> i32 {
+        self.0.get_max_aux_streams()
+    }
+
+    pub fn set_flag(&mut self, flag: BuilderFlag) {
+        self.0.pin_mut().set_flag(flag as _)
+    }
+
+    pub fn clear_flag(&mut self, flag: BuilderFlag) {
+        self.0.pin_mut().clear_flag(flag as _)
+    }
+
+    pub fn get_flag(&self, flag: BuilderFlag) -> bool {
+        self.0.get_flag(flag as _)
+    }
+
+    /// How much layer information the built engine keeps for profilers and
+    /// the engine inspector; also the default NVTX verbosity of its contexts.
+    pub fn set_profiling_verbosity(&mut self, verbosity: ProfilingVerbosity) {
+        self.0.pin_mut().set_profiling_verbosity(verbosity as _)
+    }
+
+    pub fn get_profiling_verbosity(&self) -> ProfilingVerbosity {
+        let verbosity = self.0.get_profiling_verbosity();
+        ProfilingVerbosity::try_from(verbosity)
+            .unwrap_or_else(|verbosity| panic!("Invalid profiling verbosity: {}", verbosity))
+    }
+
+    /// Adds `profile` and returns its index, or `None` if it is invalid,
+    /// e.g. because an input has no shapes set.
+    pub fn add_optimization_profile(&mut self, profile: OptimizationProfile<'_>) -> Option<i32> {
+        match self.0.pin_mut().add_optimization_profile(profile.0) {
+            -1 => None,
+            index => Some(index),
+        }
+    }
+
+    /// The device layers run on unless they are assigned one explicitly.
+    pub fn set_default_device_type(&mut self, device_type: DeviceType) {
+        self.0.pin_mut().set_default_device_type(device_type as _)
+    }
+
+    pub fn get_default_device_type(&self) -> DeviceType {
+        let device_type = self.0.get_default_device_type();
+        DeviceType::try_from(device_type)
+            .unwrap_or_else(|device_type| panic!("Invalid device type: {}", device_type))
+    }
+
+    /// Sets the calibrator that provides the data INT8 builds calibrate the
+    /// dynamic ranges with, which non-QAT models need. Replaces any previous
+    /// one; the config keeps it alive.
+    pub fn set_int8_calibrator<C: Int8Calibrator + 'static>(&mut self, calibrator: C) {
+        let calibrator = Box::new(RustCalibrator(Box::new(calibrator)));
+        self.0.pin_mut().set_int8_calibrator(calibrator)
+    }
+
+    pub fn has_int8_calibrator(&self) -> bool {
+        self.0.has_int8_calibrator()
+    }
+
+    /// Sets the selector that restricts the algorithms each layer may use,
+    /// e.g. to pin the tactics of an earlier build. Replaces any previous
+    /// one; the config keeps it alive.
+    pub fn set_algorithm_selector<S: AlgorithmSelector + 'static>(&mut self, selector: S) {
+        let selector = Box::new(RustAlgorithmSelector(Box::new(selector)));
+        self.0.pin_mut().set_algorithm_selector(selector)
+    }
+
+    pub fn has_algorithm_selector(&self) -> bool {
+        self.0.has_algorithm_selector()
+    }
+
+    /// Sets the monitor that follows the progress of builds with this config
+    /// and can cancel them. Replaces any previous one; the config keeps it
+    /// alive. Returns false before TensorRT 10.
+    pub fn set_progress_monitor<M: ProgressMonitor + 'static>(&mut self, monitor: M) -> bool {
+        let monitor = Box::new(RustProgressMonitor(Box::new(monitor)));
+        self.0.pin_mut().set_progress_monitor(monitor)
+    }
+
+    pub fn has_progress_monitor(&self) -> bool {
+        self.0.has_progress_monitor()
+    }
+
+    /// Creates a timing cache from a serialized one, or an empty cache if
+    /// `blob` is empty.
+    pub fn create_timing_cache(&self, blob: &[u8]) -> Option<TimingCache> {
+        let cache = self.0.create_timing_cache(blob);
+        if cache.is_null() {
+            None
+        } else {
+            Some(TimingCache(cache))
+        }
+    }
+
+    /// Makes builds with this config use and update `cache`. Returns false
+    /// if it was created on a different device or TensorRT version, unless
+    /// `ignore_mismatch`.
+    pub fn set_timing_cache(&mut self, cache: &TimingCache, ignore_mismatch: bool) -> bool {
+        self.0.pin_mut().set_timing_cache(&cache.0, ignore_mismatch)
+    }
+
+    pub fn has_timing_cache(&self) -> bool {
+        self.0.has_timing_cache()
+    }
+
+    /// The cache set with `set_timing_cache`, shared with the config, e.g.
+    /// to save it once the builds that update it are done.
+    pub fn get_timing_cache(&self) -> Option<TimingCache> {
+        let cache = self.0.get_timing_cache();
+        if cache.is_null() {
+            None
+        } else {
+            Some(TimingCache(cache))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{Runtime, SerializationFlag};
+
+    fn build_identity(builder: &mut Builder, config: &mut BuilderConfig) -> Option<HostMemory> {
+        let mut network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[1, 3, 32, 32]).unwrap();
+        let output = network.add_identity(input).unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+
+        assert_eq!(network.get_nb_inputs(), 1);
+        assert_eq!(network.get_nb_outputs(), 1);
+        assert_eq!(network.get_tensor_name(network.get_output(0).unwrap()), "y");
+
+        builder.build_serialized_network(&mut network, config)
+    }
+
+    #[test]
+    fn test_memory_pool_type_try_from() {
+        assert_eq!(MemoryPoolType::try_from(0), Ok(MemoryPoolType::WORKSPACE));
+        assert_eq!(MemoryPoolType::try_from(4), Ok(MemoryPoolType::TACTICDRAM));
+        assert_eq!(MemoryPoolType::try_from(42), Err(42));
+    }
+
+    #[test]
+    fn test_memory_pool_limit() {
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+
+        config.set_memory_pool_limit(MemoryPoolType::WORKSPACE, 1 << 20);
+        assert_eq!(config.get_memory_pool_limit(MemoryPoolType::WORKSPACE), 1 << 20);
+
+        // an identity layer needs no workspace, so a small pool still builds
+        let plan = build_identity(&mut builder, &mut config).unwrap();
+        let mut runtime = Runtime::new().unwrap();
+        let engine = runtime.deserialize(plan.as_bytes()).unwrap();
+        assert_eq!(engine.get_num_io_tensors(), 2);
+
+        // without any workspace the build either still succeeds or fails cleanly
+        config.set_memory_pool_limit(MemoryPoolType::WORKSPACE, 0);
+        assert_eq!(config.get_memory_pool_limit(MemoryPoolType::WORKSPACE), 0);
+        if let Some(plan) = build_identity(&mut builder, &mut config) {
+            assert!(runtime.deserialize(plan.as_bytes()).is_some());
+        }
+    }
+
+    #[test]
+    fn test_optimization_level() {
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        let mut runtime = Runtime::new().unwrap();
+
+        assert_eq!(config.set_optimization_level(6), Err(6));
+
+        config.set_max_aux_streams(0);
+        assert_eq!(config.get_max_aux_streams(), 0);
+
+        for level in [0, BuilderConfig::MAX_OPTIMIZATION_LEVEL] {
+            config.set_optimization_level(level).unwrap();
+            assert_eq!(config.get_optimization_level(), level);
+
+            let plan = build_identity(&mut builder, &mut config).unwrap();
+            let engine = runtime.deserialize(plan.as_bytes()).unwrap();
+            assert_eq!(engine.get_num_aux_streams(), 0);
+        }
+    }
+
+    #[test]
+    fn test_network_io_access() {
+        let mut builder = Builder::new().unwrap();
+        let network = builder.create_network_with_flags(&[]).unwrap();
+        let input = network.add_input("x", DataType::HALF, &[2, 8]).unwrap();
+        let hidden = network.add_identity(input).unwrap();
+        let output = network.add_identity(hidden).unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(hidden);
+        network.mark_output(output);
+        assert_eq!(network.get_nb_layers(), 2);
+
+        assert_eq!(network.get_nb_inputs(), 1);
+        let input = network.get_input(0).unwrap();
+        assert_eq!(network.get_tensor_name(input), "x");
+        assert_eq!(network.get_tensor_dimensions(input), vec![2, 8]);
+        assert_eq!(network.get_tensor_dtype(input), DataType::HALF);
+        assert!(network.is_network_input(input) && !network.is_network_output(input));
+        assert_eq!(network.get_input(1), None);
+        let too_many = [1; NetworkDefinition::MAX_DIMS + 1];
+        assert_eq!(network.add_input("z", DataType::FLOAT, &too_many), None);
+        assert_eq!(network.get_nb_inputs(), 1);
+
+        assert_eq!(network.get_nb_outputs(), 2);
+        network.unmark_output(hidden);
+        assert!(!network.is_network_output(hidden));
+        assert_eq!(network.get_nb_outputs(), 1);
+        assert_eq!(network.get_output(0), Some(output));
+        assert_eq!(network.get_tensor_dimensions(output), vec![2, 8]);
+    }
+
+    #[test]
+    fn test_profiling_verbosity_and_device_type() {
+        assert_eq!(DeviceType::try_from(1), Ok(DeviceType::DLA));
+        assert_eq!(DeviceType::try_from(2), Err(2));
+
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        assert_eq!(config.get_default_device_type(), DeviceType::GPU);
+
+        config.set_profiling_verbosity(ProfilingVerbosity::DETAILED);
+        assert_eq!(config.get_profiling_verbosity(), ProfilingVerbosity::DETAILED);
+        config.set_default_device_type(DeviceType::DLA);
+        assert_eq!(config.get_default_device_type(), DeviceType::DLA);
+    }
+
+    #[test]
+    fn test_optimization_profile() {
+        let mut builder = Builder::new().unwrap();
+        let mut network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[-1, 4]).unwrap();
+        let output = network.add_identity(input).unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+
+        let mut config = builder.create_builder_config().unwrap();
+        let empty = builder.create_optimization_profile().unwrap();
+        assert_eq!(config.add_optimization_profile(empty), None);
+
+        let mut profile = builder.create_optimization_profile().unwrap();
+        let too_many = [1; NetworkDefinition::MAX_DIMS + 1];
+        assert!(!profile.set_dimensions("x", OptProfileSelector::MIN, &too_many));
+        assert!(profile.set_dimensions("x", OptProfileSelector::MIN, &[1, 4]));
+        assert!(profile.set_dimensions("x", OptProfileSelector::OPT, &[4, 4]));
+        assert!(profile.set_dimensions("x", OptProfileSelector::MAX, &[8, 4]));
+        assert_eq!(config.add_optimization_profile(profile), Some(0));
+
+        let plan = builder.build_serialized_network(&mut network, &mut config).unwrap();
+        let mut runtime = Runtime::new().unwrap();
+        let engine = runtime.deserialize(plan.as_bytes()).unwrap();
+        assert_eq!(engine.get_profile_shape("x", 0, OptProfileSelector::MAX), vec![8, 4]);
+    }
+
+    #[test]
+    fn test_builder_flag_try_from() {
+        assert_eq!(BuilderFlag::try_from(0), Ok(BuilderFlag::FP16));
+        assert_eq!(BuilderFlag::try_from(2), Ok(BuilderFlag::REFIT));
+        assert_eq!(BuilderFlag::try_from(42), Err(42));
+    }
+
+    #[test]
+    fn test_strongly_typed_network() {
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        let flags = [NetworkDefinitionCreationFlag::STRONGLYTYPED];
+        let mut network = match builder.create_network_with_flags(&flags) {
+            Some(network) => network,
+            None => {
+                println!("Strongly typed networks are not supported! Skip test!");
+                return;
+            }
+        };
+        assert!(network.get_flag(NetworkDefinitionCreationFlag::STRONGLYTYPED));
+
+        let input = network.add_input("x", DataType::HALF, &[1, 3, 32, 32]).unwrap();
+        let output = network.add_identity(input).unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+
+        let plan = builder.build_serialized_network(&mut network, &mut config).unwrap();
+        let mut runtime = Runtime::new().unwrap();
+        let engine = runtime.deserialize(plan.as_bytes()).unwrap();
+        assert_eq!(engine.get_tensor_dtype("y"), DataType::HALF);
+    }
+
+    #[test]
+    fn test_serialize_without_weights() {
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        config.set_flag(BuilderFlag::REFIT);
+        assert!(config.get_flag(BuilderFlag::REFIT));
+
+        let mut network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[1, 4]).unwrap();
+        let output = network.add_identity(input).unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+        let weights = (0..1 << 16).map(|i| i as f32).collect::<Vec<_>>();
+        assert_eq!(network.add_constant(&[1, 1 << 17], &weights), None);
+        assert_eq!(network.add_constant(&[1; NetworkDefinition::MAX_DIMS + 1], &[0.0]), None);
+        let constant = network.add_constant(&[1, 1 << 16], &weights).unwrap();
+        network.set_tensor_name(constant, "c");
+        network.mark_output(constant);
+
+        let plan = builder.build_serialized_network(&mut network, &mut config).unwrap();
+        let mut runtime = Runtime::new().unwrap();
+        let engine = runtime.deserialize(plan.as_bytes()).unwrap();
+        assert!(engine.is_refittable());
+
+        let full = engine.serialize().unwrap();
+        let stripped = match engine.serialize_with_flags(&[SerializationFlag::EXCLUDEWEIGHTS]) {
+            Some(stripped) => stripped,
+            None => {
+                println!("serializeWithConfig is not supported! Skip test!");
+                return;
+            }
+        };
+        assert!(stripped.as_bytes().len() < full.as_bytes().len());
+
+        let mut serialization_config = engine.create_serialization_config().unwrap();
+        assert!(serialization_config.get_flags().is_empty());
+        assert!(serialization_config.set_flag(SerializationFlag::EXCLUDEWEIGHTS));
+        assert!(serialization_config.get_flag(SerializationFlag::EXCLUDEWEIGHTS));
+        assert_eq!(serialization_config.get_flags(), vec![SerializationFlag::EXCLUDEWEIGHTS]);
+        let with_config = engine.serialize_with_config(&mut serialization_config).unwrap();
+        assert_eq!(with_config.as_bytes().len(), stripped.as_bytes().len());
+        assert!(serialization_config.clear_flag(SerializationFlag::EXCLUDEWEIGHTS));
+        assert!(serialization_config.get_flags().is_empty());
+
+        config.clear_flag(BuilderFlag::REFIT);
+        assert!(!config.get_flag(BuilderFlag::REFIT));
+    }
+
+    #[test]
+    fn test_timing_cache() {
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        assert!(config.create_timing_cache(b"not a timing cache").is_none());
+        assert!(config.get_timing_cache().is_none());
+
+        let path = std::env::temp_dir().join("tensorrt-rs-timing.cache");
+        let _ = fs::remove_file(&path);
+        let cache = TimingCache::load_from_file(&config, &path).unwrap().unwrap();
+        assert!(config.set_timing_cache(&cache, false));
+        assert!(config.has_timing_cache());
+
+        build_identity(&mut builder, &mut config).unwrap();
+        // the config hands out the cache it was given, with the new timings
+        let attached = config.get_timing_cache().unwrap();
+        assert_eq!(
+            attached.serialize().unwrap().as_bytes(),
+            cache.serialize().unwrap().as_bytes(),
+        );
+        cache.save_to_file(&path).unwrap();
+
+        let mut loaded = TimingCache::load_from_file(&config, &path).unwrap().unwrap();
+        assert!(loaded.combine(&cache, false));
+        assert!(loaded.reset());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,303 @@
+use crate::builder::NetworkDefinition;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Feeds calibration batches to an INT8 build, exposed to TensorRT as an
+/// `IInt8EntropyCalibrator2`. Attach it with `BuilderConfig::set_int8_calibrator`.
+///
+/// # Safety
+///
+/// TensorRT reads the batches through the raw pointers `get_batch` returns
+/// without any checks. Each has to be the device address of a buffer of at
+/// least the input's full size, and stay valid until the next call.
+pub unsafe trait Int8Calibrator: Send {
+    /// The batch size of the batches `get_batch` provides; 1 for networks
+    /// with an explicit batch dimension.
+    fn get_batch_size(&self) -> i32 {
+        1
+    }
+
+    /// Points `bindings[i]` at device memory holding the next batch of input
+    /// `names[i]`, see the trait's safety section. Returns false once there
+    /// are no batches left.
+    fn get_batch(&mut self, names: &[String], bindings: &mut [usize]) -> bool;
+
+    /// A cache written by an earlier calibration, which skips calibrating
+    /// again if it is still valid.
+    fn read_calibration_cache(&mut self) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn write_calibration_cache(&mut self, _cache: &[u8]) {}
+}
+
+pub(crate) struct RustCalibrator(pub(crate) Box<dyn Int8Calibrator>);
+
+pub(crate) fn calibrator_get_batch_size(calibrator: &RustCalibrator) -> i32 {
+    calibrator.0.get_batch_size()
+}
+
+pub(crate) fn calibrator_get_batch(
+    calibrator: &mut RustCalibrator,
+    names: &[String],
+    bindings: &mut [usize],
+) -> bool {
+    calibrator.0.get_batch(names, bindings)
+}
+
+pub(crate) fn calibrator_read_cache(calibrator: &mut RustCalibrator) -> Vec<u8> {
+    calibrator.0.read_calibration_cache().unwrap_or_default()
+}
+
+pub(crate) fn calibrator_write_cache(calibrator: &mut RustCalibrator, cache: &[u8]) {
+    calibrator.0.write_calibration_cache(cache)
+}
+
+/// Wraps a calibrator to keep its calibration table in a file, so repeated
+/// INT8 builds of the same network skip calibration. A table is only used if
+/// an entropy calibrator wrote it and it covers the network's inputs and
+/// outputs; otherwise the network is calibrated again and the file replaced.
+pub struct FileCalibrationCache<C> {
+    calibrator: C,
+    path: PathBuf,
+    tensor_names: Vec<String>,
+}
+
+impl<C: Int8Calibrator> FileCalibrationCache<C> {
+    pub fn new<P: AsRef<Path>>(calibrator: C, path: P, network: &NetworkDefinition) -> Self {
+        let inputs = (0..network.get_nb_inputs()).filter_map(|index| network.get_input(index));
+        let outputs = (0..network.get_nb_outputs()).filter_map(|index| network.get_output(index));
+        let tensor_names = inputs
+            .chain(outputs)
+            .map(|tensor| network.get_tensor_name(tensor))
+            .collect();
+
+        Self {
+            calibrator,
+            path: path.as_ref().to_path_buf(),
+            tensor_names,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn into_inner(self) -> C {
+        self.calibrator
+    }
+
+    /// Whether `table` is an entropy calibration table with a scale for each
+    /// of the network's inputs and outputs.
+    pub fn is_valid(&self, table: &[u8]) -> bool {
+        let table = match std::str::from_utf8(table) {
+            Ok(table) => table,
+            Err(_) => return false,
+        };
+        let mut lines = table.lines();
+        match lines.next() {
+            Some(header)
+                if header.starts_with("TRT-") && header.ends_with("EntropyCalibration2") => {}
+            _ => return false,
+        }
+
+        let names = lines
+            .filter_map(|line| line.rsplit_once(": "))
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        self.tensor_names.iter().all(|name| names.contains(&name.as_str()))
+    }
+}
+
+// SAFETY: the batches are the wrapped calibrator's
+unsafe impl<C: Int8Calibrator> Int8Calibrator for FileCalibrationCache<C> {
+    fn get_batch_size(&self) -> i32 {
+        self.calibrator.get_batch_size()
+    }
+
+    fn get_batch(&mut self, names: &[String], bindings: &mut [usize]) -> bool {
+        self.calibrator.get_batch(names, bindings)
+    }
+
+    fn read_calibration_cache(&mut self) -> Option<Vec<u8>> {
+        let table = fs::read(&self.path).ok()?;
+        if self.is_valid(&table) {
+            Some(table)
+        } else {
+            None
+        }
+    }
+
+    // Failing to write the table only means the next build calibrates again.
+    fn write_calibration_cache(&mut self, cache: &[u8]) {
+        let tmp_path = self.path.with_extension("tmp");
+        if fs::write(&tmp_path, cache).is_ok() {
+            let _ = fs::rename(&tmp_path, &self.path);
+        }
+        self.calibrator.write_calibration_cache(cache)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Builder, BuilderFlag},
+        runtime::DataType,
+    };
+    use cuda_rs::{device::CuDevice, memory::DeviceMemory, stream::CuStream};
+    use std::sync::{Arc, Mutex};
+
+    struct CountingCalibrator {
+        batches: usize,
+        cache: Vec<u8>,
+    }
+
+    // SAFETY: only attached to a config once it has no batches left
+    unsafe impl Int8Calibrator for CountingCalibrator {
+        fn get_batch(&mut self, _names: &[String], bindings: &mut [usize]) -> bool {
+            if self.batches == 0 {
+                return false;
+            }
+            self.batches -= 1;
+            bindings.fill(0x1000);
+            true
+        }
+
+        fn read_calibration_cache(&mut self) -> Option<Vec<u8>> {
+            Some(self.cache.clone())
+        }
+
+        fn write_calibration_cache(&mut self, cache: &[u8]) {
+            self.cache = cache.to_vec();
+        }
+    }
+
+    #[test]
+    fn test_calibrator_callbacks() {
+        let counting = CountingCalibrator { batches: 1, cache: vec![] };
+        let mut calibrator = RustCalibrator(Box::new(counting));
+        assert_eq!(calibrator_get_batch_size(&calibrator), 1);
+
+        let names = vec!["x".to_string()];
+        let mut bindings = [0];
+        assert!(calibrator_get_batch(&mut calibrator, &names, &mut bindings));
+        assert_eq!(bindings, [0x1000]);
+        assert!(!calibrator_get_batch(&mut calibrator, &names, &mut bindings));
+
+        assert!(calibrator_read_cache(&mut calibrator).is_empty());
+        calibrator_write_cache(&mut calibrator, b"cache");
+        assert_eq!(calibrator_read_cache(&mut calibrator), b"cache");
+    }
+
+    // Serves `batches` copies of one device buffer and records what TensorRT
+    // asked for and wrote back.
+    struct DeviceCalibrator {
+        batch: usize,
+        batches: usize,
+        requested: Arc<Mutex<Vec<Vec<String>>>>,
+        cache: Arc<Mutex<Vec<u8>>>,
+    }
+
+    // SAFETY: `batch` is a live buffer of the input's size
+    unsafe impl Int8Calibrator for DeviceCalibrator {
+        fn get_batch(&mut self, names: &[String], bindings: &mut [usize]) -> bool {
+            self.requested.lock().unwrap().push(names.to_vec());
+            if self.batches == 0 {
+                return false;
+            }
+            self.batches -= 1;
+            bindings.fill(self.batch);
+            true
+        }
+
+        fn write_calibration_cache(&mut self, cache: &[u8]) {
+            *self.cache.lock().unwrap() = cache.to_vec();
+        }
+    }
+
+    #[test]
+    fn test_int8_calibration_build() {
+        cuda_rs::init().unwrap();
+        let device = CuDevice::new(0).unwrap();
+        let ctx = device.retain_primary_context().unwrap();
+        let _guard = ctx.guard().unwrap();
+        let stream = CuStream::new().unwrap();
+
+        let input = vec![1.0f32; 2 * 8 * 8];
+        let batch = DeviceMemory::new(input.len() * 4, &stream).unwrap();
+        batch.copy_from_raw(input.as_ptr() as _, input.len() * 4, None).unwrap();
+        stream.synchronize().unwrap();
+
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        config.set_flag(BuilderFlag::INT8);
+        let requested = Arc::new(Mutex::new(vec![]));
+        let cache = Arc::new(Mutex::new(vec![]));
+        config.set_int8_calibrator(DeviceCalibrator {
+            batch: unsafe { batch.get_raw() } as usize,
+            batches: 2,
+            requested: requested.clone(),
+            cache: cache.clone(),
+        });
+
+        let mut network = builder.create_network().unwrap();
+        let x = network.add_input("x", DataType::FLOAT, &[1, 2, 8, 8]).unwrap();
+        let kernel = vec![1.0f32; 4 * 2 * 3 * 3];
+        let y = network.add_convolution("conv", x, 4, &[3, 3], &kernel, &[0.0f32; 4]).unwrap();
+        network.set_tensor_name(y, "y");
+        network.mark_output(y);
+
+        assert!(builder.build_serialized_network(&mut network, &mut config).is_some());
+
+        // both batches and the final request that ends calibration
+        let requested = requested.lock().unwrap();
+        assert_eq!(requested.len(), 3);
+        assert!(requested.iter().all(|names| names == &["x"]));
+
+        let cache = cache.lock().unwrap();
+        let table = std::str::from_utf8(&cache).unwrap();
+        assert!(table.lines().next().unwrap().ends_with("EntropyCalibration2"));
+        assert!(table.lines().any(|line| line.starts_with("y: ")));
+    }
+
+    #[test]
+    fn test_set_int8_calibrator() {
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        assert!(!config.has_int8_calibrator());
+
+        config.set_int8_calibrator(CountingCalibrator { batches: 0, cache: vec![] });
+        assert!(config.has_int8_calibrator());
+    }
+
+    #[test]
+    fn test_file_calibration_cache() {
+        let mut builder = Builder::new().unwrap();
+        let network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[1, 4]).unwrap();
+        let output = network.add_identity(input).unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+
+        let path = std::env::temp_dir().join("tensorrt-rs-calibration.cache");
+        let _ = fs::remove_file(&path);
+        let inner = CountingCalibrator { batches: 0, cache: vec![] };
+        let mut cache = FileCalibrationCache::new(inner, &path, &network);
+        assert_eq!(cache.read_calibration_cache(), None);
+
+        let table = b"TRT-8601-EntropyCalibration2\nx: 3c010a14\ny: 3c010a14\n";
+        cache.write_calibration_cache(table);
+        assert_eq!(cache.read_calibration_cache(), Some(table.to_vec()));
+        assert!(!cache.is_valid(b"TRT-8601-EntropyCalibration2\nx: 3c010a14\n"));
+        assert!(!cache.is_valid(b"TRT-8601-MinMaxCalibration\nx: 3c010a14\ny: 3c010a14\n"));
+
+        fs::write(&path, b"garbage").unwrap();
+        assert_eq!(cache.read_calibration_cache(), None);
+        assert_eq!(cache.into_inner().cache, table.to_vec());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,89 @@
+use crate::ffi;
+use cuda_rs::stream::CuStream;
+
+pub fn get_compute_capability(device: i32) -> Option<(i32, i32)> {
+    match ffi::get_compute_capability(device) {
+        -1 => None,
+        cc => Some((cc / 10, cc % 10)),
+    }
+}
+
+pub fn get_current_device() -> Option<i32> {
+    match ffi::get_current_device() {
+        -1 => None,
+        device => Some(device),
+    }
+}
+
+/// Returns the memory that `device`'s default stream-ordered pool keeps
+/// cached after async frees to the driver, keeping at most
+/// `min_bytes_to_keep` reserved. Frees still pending on a stream are not
+/// released until that stream reaches them.
+pub fn trim_default_mem_pool(device: i32, min_bytes_to_keep: usize) -> bool {
+    ffi::trim_default_mem_pool(device, min_bytes_to_keep)
+}
+
+/// The `(least, greatest)` stream priorities of the current context, e.g.
+/// `(0, -5)`: lower numbers mean higher priority, 0 is the default, and
+/// priorities outside the range are clamped to it. `None` without a context.
+pub fn get_stream_priority_range() -> Option<(i32, i32)> {
+    match ffi::get_stream_priority_range().as_slice() {
+        &[least, greatest] => Some((least, greatest)),
+        _ => None,
+    }
+}
+
+/// Allocates `size` bytes of device memory in the current context, aligned to
+/// at least 256 bytes, e.g. for a `GpuAllocator` that only tracks TensorRT's
+/// allocations. Free it with `mem_free`.
+pub fn mem_alloc(size: usize) -> Option<usize> {
+    match ffi::mem_alloc(size) {
+        0 => None,
+        ptr => Some(ptr),
+    }
+}
+
+/// Frees memory returned by `mem_alloc`; returns false if it fails.
+///
+/// # Safety
+///
+/// `ptr` has to come from `mem_alloc` and must not be used afterwards, also
+/// not by a tensor or TensorRT still pointing at it.
+pub unsafe fn mem_free(ptr: usize) -> bool {
+    ffi::mem_free(ptr)
+}
+
+/// A CUDA stream created with explicit flags and destroyed on drop.
+/// `CuStream::new` always creates blocking streams, which synchronize with
+/// the legacy default stream; non-blocking ones can overlap with it.
+pub struct OwnedStream(usize);
+
+impl OwnedStream {
+    pub fn new(non_blocking: bool) -> Option<Self> {
+        Self::with_priority(non_blocking, 0)
+    }
+
+    /// Lower numbers mean higher priority; see `get_stream_priority_range`.
+    pub fn with_priority(non_blocking: bool, priority: i32) -> Option<Self> {
+        match ffi::create_stream(non_blocking, priority) {
+            0 => None,
+            stream => Some(Self(stream)),
+        }
+    }
+
+    /// A borrowed handle to this stream, e.g. for APIs taking a `CuStream`.
+    ///
+    /// # Safety
+    ///
+    /// The handle, and every clone of it, must not be used after this stream
+    /// is dropped.
+    pub unsafe fn as_cu_stream(&self) -> CuStream {
+        unsafe { CuStream::from_raw(self.0 as _) }
+    }
+}
+
+impl Drop for OwnedStream {
+    fn drop(&mut self) {
+        ffi::destroy_stream(self.0)
+    }
+}
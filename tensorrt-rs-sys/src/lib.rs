@@ -11,6 +11,56 @@ pub(crate) mod ffi {
         fn log(self: Pin<&mut Logger>, severity: i32, msg: &str);
 
         fn set_level(self: Pin<&mut Logger>, severity: i32);
+
+        fn get_level(self: &Logger) -> i32;
+
+        fn set_capture(self: Pin<&mut Logger>, capture: bool);
+
+        fn take_captured(self: Pin<&mut Logger>) -> Vec<String>;
+
+        fn take_last_error(self: Pin<&mut Logger>) -> String;
+
+        fn set_internal_error_handler(self: Pin<&mut Logger>, handler: Box<InternalErrorHandler>);
+
+        fn set_callback(self: Pin<&mut Logger>, callback: Box<LogCallback>);
+    }
+
+    #[namespace = "trt_rs::logger"]
+    extern "Rust" {
+        type InternalErrorHandler;
+
+        fn handle_internal_error(handler: &InternalErrorHandler, msg: &str);
+
+        type LogCallback;
+
+        fn log_callback(callback: &LogCallback, severity: i32, msg: &str);
+    }
+
+    #[namespace = "trt_rs::recorder"]
+    struct RecordedError {
+        code: i32,
+        description: String,
+    }
+
+    #[namespace = "trt_rs::recorder"]
+    unsafe extern "C++" {
+        include!("tensorrt-rs-sys/cxx/include/recorder.h");
+
+        type ErrorRecorder;
+
+        fn create_error_recorder() -> UniquePtr<ErrorRecorder>;
+
+        fn get_nb_errors(self: &ErrorRecorder) -> i32;
+
+        fn has_overflowed(self: &ErrorRecorder) -> bool;
+
+        fn clear(self: &ErrorRecorder);
+
+        fn take_errors(self: &ErrorRecorder) -> Vec<RecordedError>;
+
+        fn clone(self: &ErrorRecorder) -> UniquePtr<ErrorRecorder>;
+
+        fn forwarding(self: &ErrorRecorder) -> UniquePtr<ErrorRecorder>;
     }
 
     #[namespace = "trt_rs::runtime"]
@@ -23,11 +73,19 @@ pub(crate) mod ffi {
 
         type ExecutionContext;
 
+        type HostMemory;
+
+        type SerializationConfig;
+
+        type CudaGraph;
+
         // Runtime
         fn create_runtime(logger: Pin<&mut Logger>) -> UniquePtr<Runtime>;
 
         fn deserialize(self: Pin<&mut Runtime>, data: &[u8]) -> UniquePtr<CudaEngine>;
 
+        fn is_engine_compatible(self: &Runtime, data: &[u8]) -> bool;
+
         fn set_max_threads(self: Pin<&mut Runtime>, max_threads: i32) -> bool;
 
         fn get_max_threads(self: &Runtime) -> i32;
@@ -36,6 +94,12 @@ pub(crate) mod ffi {
 
         fn get_engine_host_code_allowed(self: &Runtime) -> bool;
 
+        fn set_gpu_allocator(self: Pin<&mut Runtime>, allocator: Box<RustGpuAllocator>);
+
+        fn set_error_recorder(self: Pin<&mut Runtime>, recorder: &ErrorRecorder);
+
+        fn has_error_recorder(self: &Runtime) -> bool;
+
         // CudaEngine
         fn get_tensor_shape(self: &CudaEngine, name: &str) -> Vec<i32>;
 
@@ -53,6 +117,10 @@ pub(crate) mod ffi {
 
         fn get_device_memory_size(self: &CudaEngine) -> usize;
 
+        fn get_device_memory_size_v2(self: &CudaEngine) -> usize;
+
+        fn get_device_memory_size_for_profile_v2(self: &CudaEngine, profile_index: i32) -> usize;
+
         fn is_refittable(self: &CudaEngine) -> bool;
 
         fn get_tensor_bytes_per_component(self: &CudaEngine, name: &str) -> i32;
@@ -61,12 +129,16 @@ pub(crate) mod ffi {
 
         fn get_tensor_format(self: &CudaEngine, name: &str) -> i32;
 
+        fn get_tensor_format_desc(self: &CudaEngine, name: &str) -> String;
+
         fn get_tensor_vectorized_dim(self: &CudaEngine, name: &str) -> i32;
 
         fn get_name(self: &CudaEngine) -> &str;
 
         fn get_num_optimization_profiles(self: &CudaEngine) -> i32;
 
+        fn get_profile_shape(self: &CudaEngine, name: &str, profile_index: i32, select: i32) -> Vec<i32>;
+
         fn get_engine_capability(self: &CudaEngine) -> i32;
 
         fn has_implicit_batch_dimension(self: &CudaEngine) -> bool;
@@ -79,6 +151,38 @@ pub(crate) mod ffi {
 
         fn get_num_aux_streams(self: &CudaEngine) -> i32;
 
+        fn serialize(self: &CudaEngine) -> UniquePtr<HostMemory>;
+
+        fn create_serialization_config(self: &CudaEngine) -> UniquePtr<SerializationConfig>;
+
+        fn serialize_with_config(
+            self: &CudaEngine,
+            config: Pin<&mut SerializationConfig>,
+        ) -> UniquePtr<HostMemory>;
+
+        // SerializationConfig
+        fn set_flags(self: Pin<&mut SerializationConfig>, flags: u32) -> bool;
+
+        fn get_flags(self: &SerializationConfig) -> u32;
+
+        fn set_flag(self: Pin<&mut SerializationConfig>, flag: i32) -> bool;
+
+        fn clear_flag(self: Pin<&mut SerializationConfig>, flag: i32) -> bool;
+
+        fn get_flag(self: &SerializationConfig, flag: i32) -> bool;
+
+        fn get_raw(self: &CudaEngine) -> usize;
+
+        fn set_error_recorder(self: Pin<&mut CudaEngine>, recorder: &ErrorRecorder);
+
+        fn has_error_recorder(self: &CudaEngine) -> bool;
+
+        // HostMemory
+        fn data(self: &HostMemory) -> &[u8];
+
+        // CudaGraph
+        fn launch(self: Pin<&mut CudaGraph>, stream: usize) -> bool;
+
         // ExecutionContext
         fn set_debug_sync(self: Pin<&mut ExecutionContext>, sync: bool);
 
@@ -90,6 +194,8 @@ pub(crate) mod ffi {
 
         fn set_device_memory(self: Pin<&mut ExecutionContext>, memory: usize);
 
+        fn set_device_memory_v2(self: Pin<&mut ExecutionContext>, memory: usize, size: i64);
+
         fn get_tensor_strides(self: &ExecutionContext, name: &str) -> Vec<i32>;
 
         fn get_optimization_profile(self: &ExecutionContext) -> i32;
@@ -102,6 +208,8 @@ pub(crate) mod ffi {
 
         fn all_input_shapes_specified(self: &ExecutionContext) -> bool;
 
+        fn infer_shapes(self: Pin<&mut ExecutionContext>) -> i32;
+
         fn set_optimization_profile_async(self: Pin<&mut ExecutionContext>, profile_index: i32, stream: usize) -> bool;
 
         fn set_enqueue_emits_profile(self: Pin<&mut ExecutionContext>, emits: bool);
@@ -110,6 +218,10 @@ pub(crate) mod ffi {
 
         fn report_to_profiler(self: Pin<&mut ExecutionContext>);
 
+        fn set_profiler(self: Pin<&mut ExecutionContext>, profiler: Box<RustProfiler>);
+
+        fn has_profiler(self: &ExecutionContext) -> bool;
+
         fn set_tensor_address(self: Pin<&mut ExecutionContext>, name: &str, address: usize) -> bool;
 
         fn get_tensor_address(self: &ExecutionContext, name: &str) -> usize;
@@ -126,6 +238,10 @@ pub(crate) mod ffi {
 
         fn enqueue_v3(self: Pin<&mut ExecutionContext>, stream: usize) -> bool;
 
+        fn capture_graph(self: Pin<&mut ExecutionContext>, stream: usize) -> UniquePtr<CudaGraph>;
+
+        fn update_graph(self: Pin<&mut ExecutionContext>, graph: Pin<&mut CudaGraph>, stream: usize) -> bool;
+
         fn set_persistent_cache_limit(self: Pin<&mut ExecutionContext>, limit: usize);
 
         fn get_persistent_cache_limit(self: &ExecutionContext) -> usize;
@@ -133,18 +249,427 @@ pub(crate) mod ffi {
         fn set_nvtx_verbosity(self: Pin<&mut ExecutionContext>, verbosity: i32);
 
         fn set_aux_streams(self: Pin<&mut ExecutionContext>, streams: &[usize]);
+
+        fn get_raw(self: &ExecutionContext) -> usize;
+
+        fn set_error_recorder(self: Pin<&mut ExecutionContext>, recorder: &ErrorRecorder);
+
+        fn has_error_recorder(self: &ExecutionContext) -> bool;
+    }
+
+    #[namespace = "trt_rs::builder"]
+    unsafe extern "C++" {
+        include!("tensorrt-rs-sys/cxx/include/builder.h");
+
+        type Builder;
+
+        type NetworkDefinition;
+
+        type BuilderConfig;
+
+        type TimingCache;
+
+        // Builder
+        fn create_builder(logger: Pin<&mut Logger>) -> UniquePtr<Builder>;
+
+        fn create_network(self: Pin<&mut Builder>) -> UniquePtr<NetworkDefinition>;
+
+        fn create_network_v2(self: Pin<&mut Builder>, flags: u32) -> UniquePtr<NetworkDefinition>;
+
+        fn create_builder_config(self: Pin<&mut Builder>) -> UniquePtr<BuilderConfig>;
+
+        fn create_optimization_profile(self: &Builder) -> usize;
+
+        fn set_gpu_allocator(self: Pin<&mut Builder>, allocator: Box<RustGpuAllocator>);
+
+        fn set_profile_dimensions(profile: usize, name: &str, select: i32, dims: &[i32]) -> bool;
+
+        fn build_serialized_network(
+            self: Pin<&mut Builder>,
+            network: Pin<&mut NetworkDefinition>,
+            config: Pin<&mut BuilderConfig>,
+        ) -> UniquePtr<HostMemory>;
+
+        // NetworkDefinition
+        fn add_input(self: &NetworkDefinition, name: &str, dtype: i32, dims: &[i32]) -> usize;
+
+        fn add_identity(self: &NetworkDefinition, input: usize) -> usize;
+
+        fn add_non_zero(self: &NetworkDefinition, input: usize) -> usize;
+
+        fn add_constant(self: &NetworkDefinition, dims: &[i32], weights: &[f32]) -> usize;
+
+        fn add_convolution(
+            self: &NetworkDefinition,
+            name: &str,
+            input: usize,
+            nb_output_maps: i32,
+            kernel_size: &[i32],
+            kernel: &[f32],
+            bias: &[f32],
+        ) -> usize;
+
+        fn mark_output(self: &NetworkDefinition, tensor: usize);
+
+        fn unmark_output(self: &NetworkDefinition, tensor: usize);
+
+        fn get_nb_layers(self: &NetworkDefinition) -> i32;
+
+        fn get_nb_inputs(self: &NetworkDefinition) -> i32;
+
+        fn get_input(self: &NetworkDefinition, index: i32) -> usize;
+
+        fn get_nb_outputs(self: &NetworkDefinition) -> i32;
+
+        fn get_output(self: &NetworkDefinition, index: i32) -> usize;
+
+        fn set_tensor_name(self: &NetworkDefinition, tensor: usize, name: &str);
+
+        fn get_tensor_name(self: &NetworkDefinition, tensor: usize) -> String;
+
+        fn get_tensor_dimensions(self: &NetworkDefinition, tensor: usize) -> Vec<i32>;
+
+        fn get_tensor_dtype(self: &NetworkDefinition, tensor: usize) -> i32;
+
+        fn set_tensor_allowed_formats(self: &NetworkDefinition, tensor: usize, formats: u32);
+
+        fn is_network_input(self: &NetworkDefinition, tensor: usize) -> bool;
+
+        fn is_network_output(self: &NetworkDefinition, tensor: usize) -> bool;
+
+        fn get_flag(self: &NetworkDefinition, flag: i32) -> bool;
+
+        // BuilderConfig
+        fn set_memory_pool_limit(self: Pin<&mut BuilderConfig>, pool: i32, size: usize);
+
+        fn get_memory_pool_limit(self: &BuilderConfig, pool: i32) -> usize;
+
+        fn set_builder_optimization_level(self: Pin<&mut BuilderConfig>, level: i32);
+
+        fn get_builder_optimization_level(self: &BuilderConfig) -> i32;
+
+        fn set_max_aux_streams(self: Pin<&mut BuilderConfig>, nb_streams: i32);
+
+        fn get_max_aux_streams(self: &BuilderConfig) -> i32;
+
+        fn set_flag(self: Pin<&mut BuilderConfig>, flag: i32);
+
+        fn clear_flag(self: Pin<&mut BuilderConfig>, flag: i32);
+
+        fn get_flag(self: &BuilderConfig, flag: i32) -> bool;
+
+        fn set_profiling_verbosity(self: Pin<&mut BuilderConfig>, verbosity: i32);
+
+        fn get_profiling_verbosity(self: &BuilderConfig) -> i32;
+
+        fn set_default_device_type(self: Pin<&mut BuilderConfig>, device_type: i32);
+
+        fn get_default_device_type(self: &BuilderConfig) -> i32;
+
+        fn add_optimization_profile(self: Pin<&mut BuilderConfig>, profile: usize) -> i32;
+
+        fn set_int8_calibrator(self: Pin<&mut BuilderConfig>, calibrator: Box<RustCalibrator>);
+
+        fn has_int8_calibrator(self: &BuilderConfig) -> bool;
+
+        fn set_algorithm_selector(
+            self: Pin<&mut BuilderConfig>,
+            selector: Box<RustAlgorithmSelector>,
+        );
+
+        fn has_algorithm_selector(self: &BuilderConfig) -> bool;
+
+        fn set_progress_monitor(
+            self: Pin<&mut BuilderConfig>,
+            monitor: Box<RustProgressMonitor>,
+        ) -> bool;
+
+        fn has_progress_monitor(self: &BuilderConfig) -> bool;
+
+        fn create_timing_cache(self: &BuilderConfig, blob: &[u8]) -> UniquePtr<TimingCache>;
+
+        fn set_timing_cache(
+            self: Pin<&mut BuilderConfig>,
+            cache: &TimingCache,
+            ignore_mismatch: bool,
+        ) -> bool;
+
+        fn has_timing_cache(self: &BuilderConfig) -> bool;
+
+        fn get_timing_cache(self: &BuilderConfig) -> UniquePtr<TimingCache>;
+
+        // TimingCache
+        fn serialize(self: &TimingCache) -> UniquePtr<HostMemory>;
+
+        fn combine(self: Pin<&mut TimingCache>, other: &TimingCache, ignore_mismatch: bool) -> bool;
+
+        fn reset(self: Pin<&mut TimingCache>) -> bool;
+    }
+
+    #[namespace = "trt_rs::allocator"]
+    extern "Rust" {
+        type RustGpuAllocator;
+
+        fn allocator_allocate(
+            allocator: &RustGpuAllocator,
+            size: u64,
+            alignment: u64,
+            resizable: bool,
+        ) -> usize;
+
+        fn allocator_deallocate(allocator: &RustGpuAllocator, ptr: usize) -> bool;
+    }
+
+    #[namespace = "trt_rs::algorithm"]
+    struct AlgorithmChoice {
+        implementation: i64,
+        tactic: i64,
+        timing_ms: f32,
+        workspace_size: usize,
+    }
+
+    #[namespace = "trt_rs::algorithm"]
+    struct AlgorithmReport {
+        layer_name: String,
+        choice: AlgorithmChoice,
+    }
+
+    #[namespace = "trt_rs::algorithm"]
+    extern "Rust" {
+        type RustAlgorithmSelector;
+
+        fn selector_select_algorithms(
+            selector: &mut RustAlgorithmSelector,
+            layer_name: &str,
+            choices: &[AlgorithmChoice],
+        ) -> Vec<i32>;
+
+        fn selector_report_algorithms(
+            selector: &mut RustAlgorithmSelector,
+            reports: &[AlgorithmReport],
+        );
+    }
+
+    // built by the selector trampolines, which only pass them to Rust as slices
+    impl Vec<AlgorithmChoice> {}
+
+    impl Vec<AlgorithmReport> {}
+
+    #[namespace = "trt_rs::progress"]
+    extern "Rust" {
+        type RustProgressMonitor;
+
+        fn monitor_phase_start(
+            monitor: &mut RustProgressMonitor,
+            phase_name: &str,
+            parent_phase: &str,
+            nb_steps: i32,
+        );
+
+        fn monitor_step_complete(
+            monitor: &mut RustProgressMonitor,
+            phase_name: &str,
+            step: i32,
+        ) -> bool;
+
+        fn monitor_phase_finish(monitor: &mut RustProgressMonitor, phase_name: &str);
+    }
+
+    #[namespace = "trt_rs::profiler"]
+    extern "Rust" {
+        type RustProfiler;
+
+        fn profiler_report_layer_time(profiler: &mut RustProfiler, layer_name: &str, ms: f32);
+    }
+
+    #[namespace = "trt_rs::calibrator"]
+    extern "Rust" {
+        type RustCalibrator;
+
+        fn calibrator_get_batch_size(calibrator: &RustCalibrator) -> i32;
+
+        fn calibrator_get_batch(
+            calibrator: &mut RustCalibrator,
+            names: &[String],
+            bindings: &mut [usize],
+        ) -> bool;
+
+        fn calibrator_read_cache(calibrator: &mut RustCalibrator) -> Vec<u8>;
+
+        fn calibrator_write_cache(calibrator: &mut RustCalibrator, cache: &[u8]);
+    }
+
+    #[namespace = "trt_rs::device"]
+    unsafe extern "C++" {
+        include!("tensorrt-rs-sys/cxx/include/device.h");
+
+        fn get_compute_capability(device: i32) -> i32;
+
+        fn get_current_device() -> i32;
+
+        fn trim_default_mem_pool(device: i32, min_bytes_to_keep: usize) -> bool;
+
+        fn get_stream_priority_range() -> Vec<i32>;
+
+        fn create_stream(non_blocking: bool, priority: i32) -> usize;
+
+        fn destroy_stream(stream: usize);
+
+        fn mem_alloc(size: usize) -> usize;
+
+        fn mem_free(ptr: usize) -> bool;
+    }
+
+    #[namespace = "trt_rs::refitter"]
+    unsafe extern "C++" {
+        include!("tensorrt-rs-sys/cxx/include/refitter.h");
+
+        type Refitter;
+
+        fn create_refitter(engine: Pin<&mut CudaEngine>, logger: Pin<&mut Logger>) -> UniquePtr<Refitter>;
+
+        fn set_weights(
+            self: Pin<&mut Refitter>,
+            layer_name: &str,
+            role: i32,
+            dtype: i32,
+            data: &[u8],
+            count: i64,
+        ) -> bool;
+
+        fn set_named_weights(
+            self: Pin<&mut Refitter>,
+            name: &str,
+            dtype: i32,
+            data: &[u8],
+            count: i64,
+        ) -> bool;
+
+        fn set_named_weights_from_ptr(
+            self: Pin<&mut Refitter>,
+            name: &str,
+            dtype: i32,
+            ptr: usize,
+            count: i64,
+            location: i32,
+        ) -> bool;
+
+        fn get_all_weights(self: &Refitter) -> Vec<String>;
+
+        fn get_missing_weights(self: &Refitter) -> Vec<String>;
+
+        fn refit_cuda_engine(self: Pin<&mut Refitter>) -> bool;
+
+        fn refit_cuda_engine_async(self: Pin<&mut Refitter>, stream: usize) -> bool;
     }
 
     #[namespace = "trt_rs::plugin"]
     unsafe extern "C++" {
         include!("tensorrt-rs-sys/cxx/include/plugin.h");
 
-        fn load_library(plugin_path: &str) -> usize;
+        fn load_library(plugin_path: &str, error: &mut String) -> usize;
 
         fn unload_library(handle: usize);
+
+        fn init_lib_nvinfer_plugins(logger: Pin<&mut Logger>, plugin_namespace: &str) -> bool;
+
+        fn register_plugin_creator(
+            name: &str,
+            version: &str,
+            plugin_namespace: &str,
+            creator: Box<RustPluginCreator>,
+        ) -> bool;
+
+        fn list_creators() -> Vec<String>;
+    }
+
+    #[namespace = "trt_rs::plugin"]
+    struct PluginTensorDesc {
+        dims: Vec<i32>,
+        dtype: i32,
+        format: i32,
+    }
+
+    // One dimension of a plugin output: `input < 0` means the constant `value`,
+    // otherwise dimension `dim` of input `input`.
+    #[namespace = "trt_rs::plugin"]
+    struct PluginOutputDim {
+        output: i32,
+        input: i32,
+        dim: i32,
+        value: i32,
+    }
+
+    #[namespace = "trt_rs::plugin"]
+    extern "Rust" {
+        type RustPlugin;
+
+        type RustPluginCreator;
+
+        fn plugin_clone(plugin: &RustPlugin) -> Box<RustPlugin>;
+
+        fn plugin_num_outputs(plugin: &RustPlugin) -> i32;
+
+        fn plugin_output_data_types(plugin: &RustPlugin, input_types: &[i32]) -> Vec<i32>;
+
+        fn plugin_output_dimensions(plugin: &RustPlugin, input_ranks: &[i32]) -> Vec<PluginOutputDim>;
+
+        fn plugin_supports_format_combination(
+            plugin: &RustPlugin,
+            pos: i32,
+            in_out: &[PluginTensorDesc],
+            num_inputs: i32,
+        ) -> bool;
+
+        fn plugin_enqueue(
+            plugin: &mut RustPlugin,
+            input_desc: &[PluginTensorDesc],
+            output_desc: &[PluginTensorDesc],
+            inputs: &[usize],
+            outputs: &[usize],
+            workspace: usize,
+            stream: usize,
+        ) -> bool;
+
+        fn plugin_serialize(plugin: &RustPlugin) -> Vec<u8>;
+
+        fn creator_create_plugin(creator: &RustPluginCreator, data: &[u8]) -> Box<RustPlugin>;
     }
+
+    // built by the plugin trampolines, which only pass it to Rust as a slice
+    impl Vec<PluginTensorDesc> {}
 }
 
+use algorithm::{
+    selector_report_algorithms, selector_select_algorithms, RustAlgorithmSelector,
+};
+use allocator::{allocator_allocate, allocator_deallocate, RustGpuAllocator};
+use calibrator::{
+    calibrator_get_batch, calibrator_get_batch_size, calibrator_read_cache,
+    calibrator_write_cache, RustCalibrator,
+};
+use logger::{handle_internal_error, log_callback, InternalErrorHandler, LogCallback};
+use plugin::{
+    creator_create_plugin, plugin_clone, plugin_enqueue, plugin_num_outputs,
+    plugin_output_data_types, plugin_output_dimensions, plugin_serialize,
+    plugin_supports_format_combination, RustPlugin, RustPluginCreator,
+};
+use profiler::{profiler_report_layer_time, RustProfiler};
+use progress::{
+    monitor_phase_finish, monitor_phase_start, monitor_step_complete, RustProgressMonitor,
+};
+
+pub mod algorithm;
+pub mod allocator;
+pub mod builder;
+pub mod calibrator;
+pub mod device;
 pub mod logger;
+#[cfg(feature = "onnx")]
+pub mod parser;
 pub mod plugin;
+pub mod profiler;
+pub mod progress;
+pub mod recorder;
+pub mod refitter;
 pub mod runtime;
@@ -3,7 +3,7 @@ use cxx::UniquePtr;
 
 pub struct Logger(pub(crate) UniquePtr<ffi::Logger>);
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Severity {
     InternalError = 0,
     Error = 1,
@@ -12,11 +12,58 @@ pub enum Severity {
     Verbose = 4,
 }
 
+impl TryFrom<i32> for Severity {
+    type Error = i32;
+
+    // `Self::Error` would be ambiguous with the `Error` variant
+    fn try_from(value: i32) -> Result<Self, i32> {
+        match value {
+            0 => Ok(Severity::InternalError),
+            1 => Ok(Severity::Error),
+            2 => Ok(Severity::Warning),
+            3 => Ok(Severity::Info),
+            4 => Ok(Severity::Verbose),
+            severity => Err(severity),
+        }
+    }
+}
+
 impl Logger {
     pub fn new() -> Self {
         Self(ffi::create_logger())
     }
 
+    /// Creates a logger that hands every message passing its level to
+    /// `callback` instead of printing it, e.g. to route TensorRT's messages
+    /// into the application's own logging. It may be called from TensorRT's
+    /// own threads.
+    pub fn with_callback<F>(callback: F) -> Self
+    where
+        F: Fn(Severity, &str) + Send + Sync + 'static,
+    {
+        let mut logger = Self::new();
+        logger.set_callback(callback);
+        logger
+    }
+
+    /// Creates a logger that emits TensorRT's messages as `tracing` events
+    /// with target `tensorrt`. Verbose messages become debug events and
+    /// internal errors error events with `internal = true`. Messages of every
+    /// severity are passed on, and the subscriber's filters decide which are
+    /// recorded, also when they change after the logger is created.
+    #[cfg(feature = "tracing")]
+    pub fn tracing() -> Self {
+        let mut logger = Self::with_callback(trace_message);
+        logger.set_level(Severity::Verbose);
+        logger
+    }
+
+    pub fn with_min_severity(min: Severity) -> Self {
+        let mut logger = Self::new();
+        logger.set_level(min);
+        logger
+    }
+
     pub fn log(&mut self, severity: Severity, msg: &str) {
         self.0.pin_mut().log(severity as _, msg);
     }
@@ -25,6 +72,64 @@ impl Logger {
         self.0.pin_mut().set_level(severity as _);
     }
 
+    pub fn level(&self) -> Severity {
+        match self.0.get_level() {
+            0 => Severity::InternalError,
+            1 => Severity::Error,
+            2 => Severity::Warning,
+            3 => Severity::Info,
+            4 => Severity::Verbose,
+            level => panic!("Invalid severity: {}", level),
+        }
+    }
+
+    pub fn is_enabled(&self, severity: Severity) -> bool {
+        severity <= self.level()
+    }
+
+    /// Keeps a copy of every message that passes the level filter, e.g. to
+    /// inspect what TensorRT reported while deserializing an engine.
+    /// Disabling the capture drops the messages not taken yet.
+    pub fn set_capture(&mut self, capture: bool) {
+        self.0.pin_mut().set_capture(capture)
+    }
+
+    /// Returns the captured messages in order and clears them.
+    pub fn take_captured(&mut self) -> Vec<String> {
+        self.0.pin_mut().take_captured()
+    }
+
+    /// Returns the last error or internal error message, whatever the level,
+    /// and clears it.
+    pub fn take_last_error(&mut self) -> Option<String> {
+        let msg = self.0.pin_mut().take_last_error();
+        if msg.is_empty() {
+            None
+        } else {
+            Some(msg)
+        }
+    }
+
+    /// Runs `handler` on every `InternalError` message before it is logged,
+    /// e.g. to record context before TensorRT brings the process down. It may
+    /// be called from TensorRT's own threads, and replaces any earlier one.
+    pub fn set_internal_error_handler(&mut self, handler: Box<dyn Fn(&str) + Send + Sync>) {
+        self.0.pin_mut().set_internal_error_handler(Box::new(InternalErrorHandler(handler)))
+    }
+
+    /// Hands the messages to `callback` from now on, replacing any earlier
+    /// one; they are no longer printed.
+    pub fn set_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(Severity, &str) + Send + Sync + 'static,
+    {
+        self.0.pin_mut().set_callback(Box::new(LogCallback(Box::new(callback))))
+    }
+
+    pub fn internal_error(&mut self, msg: &str) {
+        self.log(Severity::InternalError, msg);
+    }
+
     pub fn error(&mut self, msg: &str) {
         self.log(Severity::Error, msg);
     }
@@ -42,6 +147,33 @@ impl Logger {
     }
 }
 
+pub(crate) struct InternalErrorHandler(Box<dyn Fn(&str) + Send + Sync>);
+
+pub(crate) fn handle_internal_error(handler: &InternalErrorHandler, msg: &str) {
+    (handler.0)(msg)
+}
+
+#[cfg(feature = "tracing")]
+fn trace_message(severity: Severity, msg: &str) {
+    match severity {
+        Severity::InternalError => tracing::error!(target: "tensorrt", internal = true, "{}", msg),
+        Severity::Error => tracing::error!(target: "tensorrt", "{}", msg),
+        Severity::Warning => tracing::warn!(target: "tensorrt", "{}", msg),
+        Severity::Info => tracing::info!(target: "tensorrt", "{}", msg),
+        Severity::Verbose => tracing::debug!(target: "tensorrt", "{}", msg),
+    }
+}
+
+type Callback = dyn Fn(Severity, &str) + Send + Sync;
+
+pub(crate) struct LogCallback(Box<Callback>);
+
+pub(crate) fn log_callback(callback: &LogCallback, severity: i32, msg: &str) {
+    // TensorRT only logs the severities it declares
+    let severity = Severity::try_from(severity).unwrap_or(Severity::Verbose);
+    (callback.0)(severity, msg)
+}
+
 impl Default for Logger {
     fn default() -> Self {
         Self::new()
@@ -51,6 +183,7 @@ impl Default for Logger {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::runtime::Runtime;
 
     #[test]
     fn test_logger() {
@@ -61,5 +194,151 @@ mod tests {
         logger.warning("Hello, world!");
         logger.error("Hello, world!");
         logger.log(Severity::InternalError, "Hello, world!");
+        logger.internal_error("Hello, world!");
+    }
+
+    #[test]
+    fn test_severity_order() {
+        assert!(Severity::InternalError < Severity::Error);
+        assert!(Severity::Error < Severity::Warning);
+        assert!(Severity::Warning < Severity::Info);
+        assert!(Severity::Info < Severity::Verbose);
+    }
+
+    #[test]
+    fn test_logger_min_severity() {
+        let mut logger = Logger::with_min_severity(Severity::Warning);
+        assert_eq!(logger.level(), Severity::Warning);
+        assert!(logger.is_enabled(Severity::InternalError));
+        assert!(logger.is_enabled(Severity::Error));
+        assert!(logger.is_enabled(Severity::Warning));
+        assert!(!logger.is_enabled(Severity::Info));
+        assert!(!logger.is_enabled(Severity::Verbose));
+
+        logger.set_level(Severity::Verbose);
+        assert!(logger.is_enabled(Severity::Verbose));
+    }
+
+    #[test]
+    fn test_logger_capture() {
+        let mut logger = Logger::with_min_severity(Severity::Warning);
+        logger.info("dropped");
+        assert!(logger.take_captured().is_empty());
+
+        logger.set_capture(true);
+        logger.info("filtered");
+        logger.warning("first");
+        logger.error("second");
+        assert_eq!(logger.take_captured(), vec!["first", "second"]);
+        assert!(logger.take_captured().is_empty());
+
+        logger.warning("pending");
+        logger.set_capture(false);
+        logger.warning("ignored");
+        assert!(logger.take_captured().is_empty());
+    }
+
+    #[test]
+    fn test_take_last_error() {
+        let mut logger = Logger::with_min_severity(Severity::InternalError);
+        assert_eq!(logger.take_last_error(), None);
+
+        logger.error("first");
+        logger.warning("not an error");
+        logger.error("second");
+        assert_eq!(logger.take_last_error().as_deref(), Some("second"));
+        assert_eq!(logger.take_last_error(), None);
+    }
+
+    #[test]
+    fn test_internal_error_handler() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(vec![]));
+        let mut logger = Logger::new();
+        let handler_seen = seen.clone();
+        logger.set_internal_error_handler(Box::new(move |msg| {
+            handler_seen.lock().unwrap().push(msg.to_string());
+        }));
+
+        logger.error("not an internal error");
+        logger.internal_error("out of memory");
+        assert_eq!(*seen.lock().unwrap(), vec!["out of memory".to_string()]);
+    }
+
+    #[test]
+    fn test_logger_callback() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(vec![]));
+        let callback_seen = seen.clone();
+        let mut logger = Logger::with_callback(move |severity, msg| {
+            callback_seen.lock().unwrap().push((severity, msg.to_string()));
+        });
+        logger.set_level(Severity::Warning);
+        logger.info("filtered");
+        logger.warning("first");
+        logger.internal_error("second");
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                (Severity::Warning, "first".to_string()),
+                (Severity::InternalError, "second".to_string()),
+            ]
+        );
+
+        // TensorRT's own messages go through the callback as well
+        seen.lock().unwrap().clear();
+        logger.set_level(Severity::Verbose);
+        let mut runtime = Runtime::with_logger(logger).unwrap();
+        assert!(runtime.deserialize(b"not an engine").is_none());
+        assert!(seen.lock().unwrap().iter().any(|(severity, _)| *severity == Severity::Error));
+
+        assert_eq!(Severity::try_from(2), Ok(Severity::Warning));
+        assert_eq!(Severity::try_from(42), Err(42));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_logger() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{span, Event, Level, Metadata, Subscriber};
+
+        // Records the levels of the events up to info.
+        struct InfoRecorder(Arc<Mutex<Vec<Level>>>);
+
+        impl Subscriber for InfoRecorder {
+            fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+                *metadata.level() <= Level::INFO
+            }
+
+            fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+
+            fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                self.0.lock().unwrap().push(*event.metadata().level());
+            }
+
+            fn enter(&self, _span: &span::Id) {}
+
+            fn exit(&self, _span: &span::Id) {}
+        }
+
+        let mut logger = Logger::tracing();
+        assert_eq!(logger.level(), Severity::Verbose);
+
+        // scoped to this thread, so other tests' subscribers don't interfere
+        let levels = Arc::new(Mutex::new(vec![]));
+        tracing::subscriber::with_default(InfoRecorder(levels.clone()), || {
+            logger.verbose("dropped by the subscriber");
+            logger.info("info");
+            logger.internal_error("internal");
+        });
+        assert_eq!(*levels.lock().unwrap(), vec![Level::INFO, Level::ERROR]);
     }
 }
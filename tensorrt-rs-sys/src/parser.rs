@@ -0,0 +1,197 @@
+use crate::{builder::NetworkDefinition, logger::{Logger, Severity}};
+use cxx::UniquePtr;
+
+#[cxx::bridge]
+mod ffi {
+    #[namespace = "trt_rs::logger"]
+    unsafe extern "C++" {
+        include!("tensorrt-rs-sys/cxx/include/logger.h");
+
+        type Logger = crate::ffi::Logger;
+    }
+
+    #[namespace = "trt_rs::builder"]
+    unsafe extern "C++" {
+        include!("tensorrt-rs-sys/cxx/include/builder.h");
+
+        type NetworkDefinition = crate::ffi::NetworkDefinition;
+    }
+
+    #[namespace = "trt_rs::parser"]
+    unsafe extern "C++" {
+        include!("tensorrt-rs-sys/cxx/include/parser.h");
+
+        type Parser;
+
+        fn create_parser(
+            network: Pin<&mut NetworkDefinition>,
+            logger: Pin<&mut Logger>,
+        ) -> UniquePtr<Parser>;
+
+        fn parse(self: Pin<&mut Parser>, data: &[u8]) -> bool;
+
+        fn parse_from_file(self: Pin<&mut Parser>, path: &str, verbosity: i32) -> bool;
+
+        fn get_nb_errors(self: &Parser) -> i32;
+
+        fn get_error(self: &Parser, index: i32) -> String;
+
+        fn clear_errors(self: Pin<&mut Parser>);
+    }
+}
+
+/// Populates a network from an ONNX model.
+///
+/// The parser takes the network, since the weights it parses are only
+/// referenced by the network and must stay alive until it has been built;
+/// build through `network`.
+pub struct Parser {
+    parser: UniquePtr<ffi::Parser>,
+    network: NetworkDefinition,
+    logger: Logger,
+}
+
+impl Parser {
+    pub fn new(mut network: NetworkDefinition) -> Option<Self> {
+        let mut logger = Logger::new();
+        let parser = ffi::create_parser(network.0.pin_mut(), logger.0.pin_mut());
+        if parser.is_null() {
+            None
+        } else {
+            Some(Self { parser, network, logger })
+        }
+    }
+
+    pub fn logger(&mut self) -> &mut Logger {
+        &mut self.logger
+    }
+
+    pub fn network(&mut self) -> &mut NetworkDefinition {
+        &mut self.network
+    }
+
+    /// Parses a serialized ONNX model; on failure the reasons are available
+    /// through `errors`.
+    pub fn parse(&mut self, data: &[u8]) -> bool {
+        self.parser.pin_mut().parse(data)
+    }
+
+    /// Like `parse`, but reads the model from `path`, which also resolves
+    /// weights stored in external files next to it. Messages less severe
+    /// than `verbosity` are not logged.
+    pub fn parse_from_file(&mut self, path: &str, verbosity: Severity) -> bool {
+        self.parser.pin_mut().parse_from_file(path, verbosity as _)
+    }
+
+    pub fn get_nb_errors(&self) -> i32 {
+        self.parser.get_nb_errors()
+    }
+
+    pub fn get_error(&self, index: i32) -> Option<String> {
+        if index < 0 || index >= self.get_nb_errors() {
+            None
+        } else {
+            Some(self.parser.get_error(index))
+        }
+    }
+
+    pub fn errors(&self) -> Vec<String> {
+        (0..self.get_nb_errors()).filter_map(|index| self.get_error(index)).collect()
+    }
+
+    pub fn clear_errors(&mut self) {
+        self.parser.pin_mut().clear_errors()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder::Builder, runtime::{DataType, Runtime}};
+
+    fn varint(mut value: usize, out: &mut Vec<u8>) {
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn int_field(field: usize, value: usize) -> Vec<u8> {
+        let mut out = vec![];
+        varint(field << 3, &mut out);
+        varint(value, &mut out);
+        out
+    }
+
+    fn bytes_field(field: usize, bytes: &[u8]) -> Vec<u8> {
+        let mut out = vec![];
+        varint(field << 3 | 2, &mut out);
+        varint(bytes.len(), &mut out);
+        out.extend_from_slice(bytes);
+        out
+    }
+
+    // ValueInfoProto of a float tensor
+    fn value_info(name: &str, dims: &[usize]) -> Vec<u8> {
+        let shape = dims
+            .iter()
+            .flat_map(|&dim| bytes_field(1, &int_field(1, dim)))
+            .collect::<Vec<_>>();
+        let tensor_type = [int_field(1, 1), bytes_field(2, &shape)].concat();
+        [bytes_field(1, name.as_bytes()), bytes_field(2, &bytes_field(1, &tensor_type))].concat()
+    }
+
+    // ModelProto of a single Identity node from `x` to `y`
+    fn identity_model() -> Vec<u8> {
+        let node = [
+            bytes_field(1, b"x"),
+            bytes_field(2, b"y"),
+            bytes_field(4, b"Identity"),
+        ].concat();
+        let graph = [
+            bytes_field(1, &node),
+            bytes_field(2, b"identity"),
+            bytes_field(11, &value_info("x", &[1, 4])),
+            bytes_field(12, &value_info("y", &[1, 4])),
+        ].concat();
+        [int_field(1, 7), bytes_field(7, &graph), bytes_field(8, &int_field(2, 13))].concat()
+    }
+
+    #[test]
+    fn test_parse_and_build() {
+        let mut builder = Builder::new().unwrap();
+        let network = builder.create_network().unwrap();
+        let mut parser = Parser::new(network).unwrap();
+        assert!(parser.parse(&identity_model()), "{:?}", parser.errors());
+        assert_eq!(parser.get_nb_errors(), 0);
+
+        let network = parser.network();
+        assert_eq!(network.get_nb_inputs(), 1);
+        let input = network.get_input(0).unwrap();
+        assert_eq!(network.get_tensor_name(input), "x");
+        assert_eq!(network.get_tensor_dimensions(input), vec![1, 4]);
+
+        let mut config = builder.create_builder_config().unwrap();
+        let plan = builder.build_serialized_network(parser.network(), &mut config).unwrap();
+        let mut runtime = Runtime::new().unwrap();
+        let engine = runtime.deserialize(plan.as_bytes()).unwrap();
+        assert_eq!(engine.get_tensor_dtype("y"), DataType::FLOAT);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut builder = Builder::new().unwrap();
+        let network = builder.create_network().unwrap();
+        let mut parser = Parser::new(network).unwrap();
+
+        assert!(!parser.parse(b"not an onnx model"));
+        assert!(parser.get_nb_errors() > 0);
+        assert_eq!(parser.errors().len(), parser.get_nb_errors() as usize);
+        assert_eq!(parser.get_error(parser.get_nb_errors()), None);
+
+        parser.clear_errors();
+        assert_eq!(parser.get_nb_errors(), 0);
+        assert!(!parser.parse_from_file("/nonexistent/model.onnx", Severity::Warning));
+    }
+}
@@ -1,11 +1,317 @@
-use crate::ffi;
+use crate::{
+    ffi,
+    logger::Logger,
+    runtime::{DataType, TensorFormat},
+};
+use cuda_rs::stream::CuStream;
 
 pub type PluginLibraryHandle = usize;
 
-pub fn load_library(plugin_path: &str) -> PluginLibraryHandle {
-    ffi::load_library(plugin_path)
+/// Loads a plugin library into the global plugin registry. `plugin_path` is
+/// resolved like `dlopen` does, so bare sonames are searched for as well.
+///
+/// On failure, returns why: the `dlerror` message, or missing plugin entry
+/// points.
+pub fn load_library(plugin_path: &str) -> Result<PluginLibraryHandle, String> {
+    let mut error = String::new();
+    match ffi::load_library(plugin_path, &mut error) {
+        0 => Err(error),
+        handle => Ok(handle),
+    }
 }
 
 pub fn unload_library(handle: PluginLibraryHandle) {
     ffi::unload_library(handle)
 }
+
+/// Registers TensorRT's standard plugins (`initLibNvInferPlugins`), which
+/// engines using e.g. `EfficientNMS_TRT` need before they can be deserialized.
+///
+/// TensorRT keeps a pointer to `logger` for as long as the plugins are
+/// registered, which is the rest of the process, hence the `'static` borrow.
+pub fn init_standard_plugins(logger: &'static mut Logger, namespace: &str) -> bool {
+    ffi::init_lib_nvinfer_plugins(logger.0.pin_mut(), namespace)
+}
+
+/// Names of all plugin creators in the global plugin registry.
+pub fn list_creators() -> Vec<String> {
+    ffi::list_creators()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginTensorDesc {
+    pub dims: Vec<i32>,
+    pub dtype: DataType,
+    pub format: TensorFormat,
+}
+
+// None if TensorRT passes a type or format this crate does not know, which
+// must not unwind into TensorRT.
+fn plugin_tensor_descs(descs: &[ffi::PluginTensorDesc]) -> Option<Vec<PluginTensorDesc>> {
+    descs
+        .iter()
+        .map(|desc| {
+            Some(PluginTensorDesc {
+                dims: desc.dims.clone(),
+                dtype: DataType::try_from(desc.dtype).ok()?,
+                format: TensorFormat::try_from(desc.format).ok()?,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum OutputDim {
+    // A fixed extent.
+    Constant(i32),
+
+    // The extent of dimension `dim` of input `index`.
+    Input { index: usize, dim: usize },
+}
+
+/// A TensorRT plugin implemented in Rust, exposed to TensorRT as an
+/// `IPluginV3` with the core, build and runtime capabilities.
+pub trait PluginV3: Send {
+    fn clone_plugin(&self) -> Box<dyn PluginV3>;
+
+    fn num_outputs(&self) -> i32;
+
+    fn get_output_data_types(&self, input_types: &[DataType]) -> Vec<DataType>;
+
+    fn get_output_dimensions(&self, input_ranks: &[usize]) -> Vec<Vec<OutputDim>>;
+
+    /// Whether `in_out[pos]` may use its type and format; `in_out` holds the
+    /// inputs followed by the outputs. Defaults to linear tensors that all
+    /// share the type of the first input.
+    fn supports_format_combination(
+        &self,
+        pos: usize,
+        in_out: &[PluginTensorDesc],
+        _num_inputs: usize,
+    ) -> bool {
+        in_out[pos].format == TensorFormat::LINEAR && in_out[pos].dtype == in_out[0].dtype
+    }
+
+    fn enqueue(
+        &mut self,
+        input_desc: &[PluginTensorDesc],
+        output_desc: &[PluginTensorDesc],
+        inputs: &[usize],
+        outputs: &[usize],
+        workspace: usize,
+        stream: &CuStream,
+    ) -> bool;
+
+    /// Bytes handed back to the creator's factory when the plugin is
+    /// deserialized.
+    fn serialize(&self) -> Vec<u8>;
+}
+
+/// Registers a plugin creator whose `factory` builds plugins from the bytes
+/// produced by `PluginV3::serialize` (empty when created from a network).
+///
+/// Only TensorRT 10 and later support `IPluginV3`; returns false otherwise or
+/// if a creator with the same name, version and namespace already exists.
+pub fn register_plugin_creator<F>(name: &str, version: &str, namespace: &str, factory: F) -> bool
+where
+    F: Fn(&[u8]) -> Box<dyn PluginV3> + Send + Sync + 'static,
+{
+    let creator = Box::new(RustPluginCreator(Box::new(factory)));
+
+    ffi::register_plugin_creator(name, version, namespace, creator)
+}
+
+pub(crate) struct RustPlugin(Box<dyn PluginV3>);
+
+type PluginFactory = dyn Fn(&[u8]) -> Box<dyn PluginV3> + Send + Sync;
+
+pub(crate) struct RustPluginCreator(Box<PluginFactory>);
+
+pub(crate) fn plugin_clone(plugin: &RustPlugin) -> Box<RustPlugin> {
+    Box::new(RustPlugin(plugin.0.clone_plugin()))
+}
+
+pub(crate) fn plugin_num_outputs(plugin: &RustPlugin) -> i32 {
+    plugin.0.num_outputs()
+}
+
+pub(crate) fn plugin_output_data_types(plugin: &RustPlugin, input_types: &[i32]) -> Vec<i32> {
+    let input_types = match input_types
+        .iter()
+        .map(|&dtype| DataType::try_from(dtype))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(input_types) => input_types,
+        // no output types fails the call
+        Err(_) => return vec![],
+    };
+
+    plugin.0
+        .get_output_data_types(&input_types)
+        .into_iter()
+        .map(|dtype| dtype as i32)
+        .collect()
+}
+
+pub(crate) fn plugin_output_dimensions(
+    plugin: &RustPlugin,
+    input_ranks: &[i32],
+) -> Vec<ffi::PluginOutputDim> {
+    let input_ranks = input_ranks.iter().map(|&rank| rank as usize).collect::<Vec<_>>();
+
+    let mut dims = vec![];
+    for (output, output_dims) in plugin.0.get_output_dimensions(&input_ranks).into_iter().enumerate() {
+        for dim in output_dims {
+            dims.push(match dim {
+                OutputDim::Constant(value) => ffi::PluginOutputDim {
+                    output: output as i32,
+                    input: -1,
+                    dim: -1,
+                    value,
+                },
+                OutputDim::Input { index, dim } => ffi::PluginOutputDim {
+                    output: output as i32,
+                    input: index as i32,
+                    dim: dim as i32,
+                    value: 0,
+                },
+            });
+        }
+    }
+
+    dims
+}
+
+pub(crate) fn plugin_supports_format_combination(
+    plugin: &RustPlugin,
+    pos: i32,
+    in_out: &[ffi::PluginTensorDesc],
+    num_inputs: i32,
+) -> bool {
+    let in_out = match plugin_tensor_descs(in_out) {
+        Some(in_out) => in_out,
+        None => return false,
+    };
+
+    plugin.0.supports_format_combination(pos as usize, &in_out, num_inputs as usize)
+}
+
+pub(crate) fn plugin_enqueue(
+    plugin: &mut RustPlugin,
+    input_desc: &[ffi::PluginTensorDesc],
+    output_desc: &[ffi::PluginTensorDesc],
+    inputs: &[usize],
+    outputs: &[usize],
+    workspace: usize,
+    stream: usize,
+) -> bool {
+    let (input_desc, output_desc) =
+        match (plugin_tensor_descs(input_desc), plugin_tensor_descs(output_desc)) {
+            (Some(input_desc), Some(output_desc)) => (input_desc, output_desc),
+            _ => return false,
+        };
+    let stream = unsafe { CuStream::from_raw(stream as _) };
+
+    plugin.0.enqueue(&input_desc, &output_desc, inputs, outputs, workspace, &stream)
+}
+
+pub(crate) fn plugin_serialize(plugin: &RustPlugin) -> Vec<u8> {
+    plugin.0.serialize()
+}
+
+pub(crate) fn creator_create_plugin(creator: &RustPluginCreator, data: &[u8]) -> Box<RustPlugin> {
+    Box::new(RustPlugin((creator.0)(data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cuda_rs::memory::DeviceMemory;
+
+    #[derive(Clone)]
+    struct IdentityPlugin;
+
+    impl PluginV3 for IdentityPlugin {
+        fn clone_plugin(&self) -> Box<dyn PluginV3> {
+            Box::new(self.clone())
+        }
+
+        fn num_outputs(&self) -> i32 {
+            1
+        }
+
+        fn get_output_data_types(&self, input_types: &[DataType]) -> Vec<DataType> {
+            vec![input_types[0]]
+        }
+
+        fn get_output_dimensions(&self, input_ranks: &[usize]) -> Vec<Vec<OutputDim>> {
+            vec![(0..input_ranks[0]).map(|dim| OutputDim::Input { index: 0, dim }).collect()]
+        }
+
+        fn enqueue(
+            &mut self,
+            input_desc: &[PluginTensorDesc],
+            _output_desc: &[PluginTensorDesc],
+            inputs: &[usize],
+            outputs: &[usize],
+            _workspace: usize,
+            stream: &CuStream,
+        ) -> bool {
+            let desc = &input_desc[0];
+            let size = desc.dims.iter().product::<i32>() as usize * desc.dtype.get_elem_size();
+            let output = unsafe { DeviceMemory::from_raw(outputs[0] as _, size, stream) };
+            let res = output.copy_from_raw(inputs[0] as _, size, Some(stream));
+            // the output buffer is owned by TensorRT
+            std::mem::forget(output);
+
+            res.is_ok()
+        }
+
+        fn serialize(&self) -> Vec<u8> {
+            vec![]
+        }
+    }
+
+    #[test]
+    fn test_unknown_data_type() {
+        let desc = |dtype| ffi::PluginTensorDesc {
+            dims: vec![1, 4],
+            dtype,
+            format: TensorFormat::LINEAR as i32,
+        };
+        let descs = plugin_tensor_descs(&[desc(DataType::FLOAT as i32)]).unwrap();
+        assert_eq!(descs[0].dtype, DataType::FLOAT);
+        assert!(plugin_tensor_descs(&[desc(DataType::FLOAT as i32), desc(-1)]).is_none());
+
+        let plugin = RustPlugin(Box::new(IdentityPlugin));
+        assert_eq!(plugin_output_data_types(&plugin, &[DataType::HALF as i32]), vec![
+            DataType::HALF as i32
+        ]);
+        assert!(plugin_output_data_types(&plugin, &[-1]).is_empty());
+    }
+
+    #[test]
+    fn test_init_standard_plugins() {
+        let logger = Box::leak(Box::new(Logger::new()));
+        assert!(init_standard_plugins(logger, ""));
+
+        let creators = list_creators();
+        assert!(creators.iter().any(|name| name == "EfficientNMS_TRT"));
+    }
+
+    #[test]
+    fn test_register_plugin_creator() {
+        let registered = register_plugin_creator(
+            "RustIdentity",
+            "1",
+            "",
+            |_data| Box::new(IdentityPlugin),
+        );
+        if !registered {
+            println!("IPluginV3 is not supported! Skip test!");
+            return;
+        }
+
+        assert!(list_creators().iter().any(|name| name == "RustIdentity"));
+    }
+}
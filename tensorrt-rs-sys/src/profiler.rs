@@ -0,0 +1,89 @@
+/// Receives the time each layer took in an inference, exposed to TensorRT as
+/// an `IProfiler`. Set it with `ExecutionContext::set_profiler`.
+///
+/// While `ExecutionContext::get_enqueue_emits_profile` is true, the default,
+/// every enqueue reports the layer times itself, which waits for the
+/// inference to finish. Otherwise, and for CUDA graphs, which can only be
+/// captured that way, call `ExecutionContext::report_to_profiler` once the
+/// stream is synchronized.
+pub trait Profiler: Send {
+    /// Layer `layer_name` ran for `ms` milliseconds; called for every layer
+    /// in execution order.
+    fn report_layer_time(&mut self, layer_name: &str, ms: f32);
+}
+
+pub(crate) struct RustProfiler(pub(crate) Box<dyn Profiler>);
+
+pub(crate) fn profiler_report_layer_time(profiler: &mut RustProfiler, layer_name: &str, ms: f32) {
+    profiler.0.report_layer_time(layer_name, ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::Builder,
+        device,
+        runtime::{DataType, Runtime},
+    };
+    use cuda_rs::{device::CuDevice, stream::CuStream};
+    use std::sync::{Arc, Mutex};
+
+    struct LayerTimes(Arc<Mutex<Vec<(String, f32)>>>);
+
+    impl Profiler for LayerTimes {
+        fn report_layer_time(&mut self, layer_name: &str, ms: f32) {
+            self.0.lock().unwrap().push((layer_name.to_string(), ms));
+        }
+    }
+
+    #[test]
+    fn test_profiler() {
+        cuda_rs::init().unwrap();
+        let device = CuDevice::new(0).unwrap();
+        let ctx = device.retain_primary_context().unwrap();
+        let _guard = ctx.guard().unwrap();
+        let stream = CuStream::new().unwrap();
+
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        let mut network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[1, 2, 8, 8]).unwrap();
+        let output = network
+            .add_convolution("conv", input, 4, &[3, 3], &[1.0f32; 4 * 2 * 3 * 3], &[0.0f32; 4])
+            .unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+        let plan = builder.build_serialized_network(&mut network, &mut config).unwrap();
+
+        let mut runtime = Runtime::new().unwrap();
+        let mut engine = runtime.deserialize(plan.as_bytes()).unwrap();
+        let mut context = engine.create_execution_context().unwrap();
+        let x = device::mem_alloc(2 * 8 * 8 * 4).unwrap();
+        let y = device::mem_alloc(4 * 6 * 6 * 4).unwrap();
+        assert!(context.set_tensor_address("x", x));
+        assert!(context.set_tensor_address("y", y));
+
+        let times = Arc::new(Mutex::new(vec![]));
+        assert!(!context.has_profiler());
+        context.set_profiler(LayerTimes(times.clone()));
+        assert!(context.has_profiler());
+
+        // reported by the enqueue itself
+        assert!(context.enqueue_v3(&stream));
+        let reported = times.lock().unwrap().len();
+        assert!(reported > 0);
+        assert!(times.lock().unwrap().iter().all(|(_, ms)| *ms >= 0.0));
+
+        // reported on demand
+        context.set_enqueue_emits_profile(false);
+        assert!(context.enqueue_v3(&stream));
+        stream.synchronize().unwrap();
+        assert_eq!(times.lock().unwrap().len(), reported);
+        context.report_to_profiler();
+        assert_eq!(times.lock().unwrap().len(), 2 * reported);
+
+        drop(context);
+        assert!(unsafe { device::mem_free(x) && device::mem_free(y) });
+    }
+}
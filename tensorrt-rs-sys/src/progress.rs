@@ -0,0 +1,135 @@
+/// Follows the progress of an engine build, exposed to TensorRT as an
+/// `IProgressMonitor`. A build runs in nested phases, each made of a known
+/// number of steps. Attach it with `BuilderConfig::set_progress_monitor`.
+///
+/// The callbacks run on the thread that builds the engine; to cancel from
+/// another one, e.g. a UI thread, share an `AtomicBool` with the monitor and
+/// check it in `step_complete`.
+pub trait ProgressMonitor: Send {
+    /// Phase `phase_name` of `parent_phase`, or of the whole build if `None`,
+    /// starts and will take `nb_steps` steps.
+    fn phase_start(&mut self, _phase_name: &str, _parent_phase: Option<&str>, _nb_steps: i32) {}
+
+    /// Step `step` of phase `phase_name` is done. Returning false cancels the
+    /// build, which then fails.
+    fn step_complete(&mut self, phase_name: &str, step: i32) -> bool;
+
+    fn phase_finish(&mut self, _phase_name: &str) {}
+}
+
+pub(crate) struct RustProgressMonitor(pub(crate) Box<dyn ProgressMonitor>);
+
+pub(crate) fn monitor_phase_start(
+    monitor: &mut RustProgressMonitor,
+    phase_name: &str,
+    parent_phase: &str,
+    nb_steps: i32,
+) {
+    let parent_phase = if parent_phase.is_empty() { None } else { Some(parent_phase) };
+
+    monitor.0.phase_start(phase_name, parent_phase, nb_steps)
+}
+
+pub(crate) fn monitor_step_complete(
+    monitor: &mut RustProgressMonitor,
+    phase_name: &str,
+    step: i32,
+) -> bool {
+    monitor.0.step_complete(phase_name, step)
+}
+
+pub(crate) fn monitor_phase_finish(monitor: &mut RustProgressMonitor, phase_name: &str) {
+    monitor.0.phase_finish(phase_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder::Builder, runtime::DataType};
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    struct CancellableMonitor {
+        steps: Arc<AtomicUsize>,
+        cancelled: Arc<AtomicBool>,
+    }
+
+    impl ProgressMonitor for CancellableMonitor {
+        fn step_complete(&mut self, _phase_name: &str, _step: i32) -> bool {
+            self.steps.fetch_add(1, Ordering::Relaxed);
+            !self.cancelled.load(Ordering::Relaxed)
+        }
+    }
+
+    // Whether the build succeeded and how many steps it completed, or `None`
+    // if progress monitors are not supported.
+    fn build_with_monitor(cancel: bool) -> Option<(bool, usize)> {
+        let steps = Arc::new(AtomicUsize::new(0));
+        let monitor = CancellableMonitor {
+            steps: steps.clone(),
+            cancelled: Arc::new(AtomicBool::new(cancel)),
+        };
+
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        if !config.set_progress_monitor(monitor) {
+            return None;
+        }
+        assert!(config.has_progress_monitor());
+
+        let mut network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[1, 3, 32, 32]).unwrap();
+        let output = network.add_identity(input).unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+        let built = builder.build_serialized_network(&mut network, &mut config).is_some();
+
+        Some((built, steps.load(Ordering::Relaxed)))
+    }
+
+    #[test]
+    fn test_monitor_callbacks() {
+        struct PhaseMonitor(Arc<Mutex<Vec<String>>>);
+
+        impl ProgressMonitor for PhaseMonitor {
+            fn phase_start(&mut self, phase_name: &str, parent_phase: Option<&str>, _: i32) {
+                let phase = format!("{:?}/{}", parent_phase, phase_name);
+                self.0.lock().unwrap().push(phase);
+            }
+
+            fn step_complete(&mut self, _phase_name: &str, step: i32) -> bool {
+                step < 1
+            }
+        }
+
+        let phases = Arc::new(Mutex::new(vec![]));
+        let mut monitor = RustProgressMonitor(Box::new(PhaseMonitor(phases.clone())));
+        monitor_phase_start(&mut monitor, "build", "", 2);
+        monitor_phase_start(&mut monitor, "tactics", "build", 4);
+        assert!(monitor_step_complete(&mut monitor, "tactics", 0));
+        assert!(!monitor_step_complete(&mut monitor, "tactics", 1));
+        monitor_phase_finish(&mut monitor, "tactics");
+
+        assert_eq!(*phases.lock().unwrap(), vec!["None/build", "Some(\"build\")/tactics"]);
+    }
+
+    #[test]
+    fn test_cancel_build() {
+        let (built, steps) = match build_with_monitor(false) {
+            Some(res) => res,
+            None => {
+                println!("Progress monitors are not supported! Skip test!");
+                return;
+            }
+        };
+        assert!(built);
+        assert!(steps > 0);
+
+        // the first completed step cancels the build
+        let (built, steps) = build_with_monitor(true).unwrap();
+        assert!(!built);
+        assert!(steps > 0);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::ffi;
+use cxx::UniquePtr;
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ErrorCode {
+    // The execution succeeded.
+    SUCCESS = 0,
+
+    // An error that does not fall into any other category.
+    UNSPECIFIEDERROR = 1,
+
+    // A non-recoverable TensorRT error occurred.
+    INTERNALERROR = 2,
+
+    // An argument passed to the function is invalid in isolation.
+    INVALIDARGUMENT = 3,
+
+    // An error occurred when comparing the state of an argument relative to other arguments.
+    INVALIDCONFIG = 4,
+
+    // An error occurred when performing an allocation of memory on the host or the device.
+    FAILEDALLOCATION = 5,
+
+    // One, or more, of the components that TensorRT relies on did not initialize correctly.
+    FAILEDINITIALIZATION = 6,
+
+    // An error occurred during execution that caused TensorRT to end prematurely.
+    FAILEDEXECUTION = 7,
+
+    // An error occurred during execution that caused the data to become corrupted.
+    FAILEDCOMPUTATION = 8,
+
+    // TensorRT was put into a bad state by incorrect sequence of function calls.
+    INVALIDSTATE = 9,
+
+    // An error occurred due to the network not being supported on the device.
+    UNSUPPORTEDSTATE = 10,
+}
+
+impl TryFrom<i32> for ErrorCode {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ErrorCode::SUCCESS),
+            1 => Ok(ErrorCode::UNSPECIFIEDERROR),
+            2 => Ok(ErrorCode::INTERNALERROR),
+            3 => Ok(ErrorCode::INVALIDARGUMENT),
+            4 => Ok(ErrorCode::INVALIDCONFIG),
+            5 => Ok(ErrorCode::FAILEDALLOCATION),
+            6 => Ok(ErrorCode::FAILEDINITIALIZATION),
+            7 => Ok(ErrorCode::FAILEDEXECUTION),
+            8 => Ok(ErrorCode::FAILEDCOMPUTATION),
+            9 => Ok(ErrorCode::INVALIDSTATE),
+            10 => Ok(ErrorCode::UNSUPPORTEDSTATE),
+            code => Err(code),
+        }
+    }
+}
+
+/// An error TensorRT reported to an `ErrorRecorder`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedError {
+    pub code: ErrorCode,
+    pub description: String,
+}
+
+impl fmt::Display for RecordedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.code, self.description)
+    }
+}
+
+/// Collects the errors TensorRT reports, exposed to it as an
+/// `IErrorRecorder`. Set it on a `Runtime`, `CudaEngine` or
+/// `ExecutionContext`; engines inherit the recorder of the runtime that
+/// deserialized them, and contexts the one of their engine.
+///
+/// While a recorder is set, TensorRT reports errors to it instead of the
+/// logger. Clones share the recorded errors, which are kept until taken; past
+/// a limit, further errors are dropped and `has_overflowed` is set. It can be
+/// read from any thread while TensorRT reports to it.
+pub struct ErrorRecorder(pub(crate) UniquePtr<ffi::ErrorRecorder>);
+
+// SAFETY: the recorded errors are guarded by a mutex on the C++ side, as
+// TensorRT itself reports them from several threads.
+unsafe impl Send for ErrorRecorder {}
+unsafe impl Sync for ErrorRecorder {}
+
+impl ErrorRecorder {
+    pub fn new() -> Self {
+        Self(ffi::create_error_recorder())
+    }
+
+    pub fn num_errors(&self) -> usize {
+        self.0.get_nb_errors() as usize
+    }
+
+    pub fn has_overflowed(&self) -> bool {
+        self.0.has_overflowed()
+    }
+
+    pub fn clear(&self) {
+        self.0.clear()
+    }
+
+    /// Returns the recorded errors in order and clears them.
+    pub fn take_errors(&self) -> Vec<RecordedError> {
+        self.0
+            .take_errors()
+            .into_iter()
+            .map(|error| RecordedError {
+                code: ErrorCode::try_from(error.code).unwrap_or(ErrorCode::UNSPECIFIEDERROR),
+                description: error.description,
+            })
+            .collect()
+    }
+
+    /// A new recorder that keeps its own errors and also reports each of them
+    /// to this one, e.g. so that one user of a shared recorder can clear and
+    /// take its errors without touching the others'.
+    pub fn forwarding(&self) -> ErrorRecorder {
+        Self(self.0.forwarding())
+    }
+}
+
+impl Clone for ErrorRecorder {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl Default for ErrorRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::Builder,
+        runtime::{DataType, Runtime},
+    };
+
+    #[test]
+    fn test_error_code_try_from() {
+        assert_eq!(ErrorCode::try_from(3), Ok(ErrorCode::INVALIDARGUMENT));
+        assert_eq!(ErrorCode::try_from(10), Ok(ErrorCode::UNSUPPORTEDSTATE));
+        assert_eq!(ErrorCode::try_from(42), Err(42));
+    }
+
+    #[test]
+    fn test_error_recorder() {
+        let recorder = ErrorRecorder::new();
+        let mut runtime = Runtime::new().unwrap();
+        assert!(!runtime.has_error_recorder());
+        runtime.set_error_recorder(&recorder);
+        assert!(runtime.has_error_recorder());
+
+        assert!(runtime.deserialize(b"not an engine").is_none());
+        assert!(recorder.num_errors() > 0);
+        assert!(!recorder.has_overflowed());
+        let errors = recorder.clone().take_errors();
+        assert!(!errors.is_empty());
+        assert!(errors.iter().all(|error| error.code != ErrorCode::SUCCESS));
+        assert_eq!(recorder.num_errors(), 0);
+
+        // engines and their contexts inherit the runtime's recorder
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        let mut network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[1, 4]).unwrap();
+        let output = network.add_identity(input).unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+        let plan = builder.build_serialized_network(&mut network, &mut config).unwrap();
+        let mut engine = runtime.deserialize(plan.as_bytes()).unwrap();
+        assert!(engine.has_error_recorder());
+
+        let mut context = engine.create_execution_context().unwrap();
+        assert!(context.has_error_recorder());
+        let own = ErrorRecorder::new();
+        context.set_error_recorder(&own);
+        assert!(!context.set_tensor_address("unknown", 0x1000));
+        assert_eq!(recorder.num_errors(), 0);
+        assert!(own.num_errors() > 0);
+        own.clear();
+        assert_eq!(own.num_errors(), 0);
+
+        // a recorder set on the engine stays alive while a context created
+        // afterwards reports to it, even once the engine is dropped
+        let engine_recorder = ErrorRecorder::new();
+        engine.set_error_recorder(&engine_recorder.clone());
+        let mut context = engine.create_execution_context().unwrap();
+        drop(engine);
+        assert!(!context.set_tensor_address("unknown", 0x1000));
+        assert!(engine_recorder.num_errors() > 0);
+    }
+
+    #[test]
+    fn test_forwarding_recorder() {
+        let shared = ErrorRecorder::new();
+        let forwarding = shared.forwarding();
+        let mut runtime = Runtime::new().unwrap();
+        runtime.set_error_recorder(&forwarding);
+
+        assert!(runtime.deserialize(b"not an engine").is_none());
+        let num_errors = forwarding.num_errors();
+        assert!(num_errors > 0);
+        assert_eq!(shared.num_errors(), num_errors);
+
+        // clearing or taking one side leaves the other alone
+        forwarding.clear();
+        assert_eq!(shared.num_errors(), num_errors);
+        assert!(runtime.deserialize(b"not an engine").is_none());
+        assert_eq!(forwarding.take_errors().len(), num_errors);
+        assert!(shared.num_errors() > num_errors);
+    }
+}
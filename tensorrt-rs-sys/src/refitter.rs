@@ -0,0 +1,313 @@
+use crate::{
+    ffi,
+    logger::Logger,
+    runtime::{CudaEngine, DataType},
+};
+use cxx::UniquePtr;
+use cuda_rs::stream::CuStream;
+use std::marker::PhantomData;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WeightsRole {
+    // kernel for IConvolutionLayer or IDeconvolutionLayer
+    KERNEL = 0,
+
+    // bias for IConvolutionLayer or IDeconvolutionLayer
+    BIAS = 1,
+
+    // shift part of IScaleLayer
+    SHIFT = 2,
+
+    // scale part of IScaleLayer
+    SCALE = 3,
+
+    // weights for IConstantLayer
+    CONSTANT = 4,
+
+    // Any other weights role
+    ANY = 5,
+}
+
+impl TryFrom<i32> for WeightsRole {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(WeightsRole::KERNEL),
+            1 => Ok(WeightsRole::BIAS),
+            2 => Ok(WeightsRole::SHIFT),
+            3 => Ok(WeightsRole::SCALE),
+            4 => Ok(WeightsRole::CONSTANT),
+            5 => Ok(WeightsRole::ANY),
+            role => Err(role),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TensorLocation {
+    // Data stored on device.
+    DEVICE = 0,
+
+    // Data stored on host.
+    HOST = 1,
+}
+
+impl TryFrom<i32> for TensorLocation {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TensorLocation::DEVICE),
+            1 => Ok(TensorLocation::HOST),
+            location => Err(location),
+        }
+    }
+}
+
+/// Updates the weights of a refittable engine in place, which stays
+/// borrowed until the refitter is dropped.
+pub struct Refitter<'a> {
+    refitter: UniquePtr<ffi::Refitter>,
+    logger: Logger,
+    engine: PhantomData<&'a mut CudaEngine>,
+}
+
+impl<'a> Refitter<'a> {
+    pub fn new(engine: &'a mut CudaEngine) -> Option<Self> {
+        let mut logger = Logger::new();
+        let refitter = ffi::create_refitter(engine.0.pin_mut(), logger.0.pin_mut());
+        if refitter.is_null() {
+            None
+        } else {
+            Some(Self { refitter, logger, engine: PhantomData })
+        }
+    }
+
+    pub fn logger(&mut self) -> &mut Logger {
+        &mut self.logger
+    }
+
+    /// Stages new weights for the `role` weights of layer `layer_name`, the
+    /// location-based path that also works for engines without named
+    /// weights. `data` is copied and holds elements of `dtype`. Returns false
+    /// if the layer has no such weights or their count or type differs.
+    pub fn set_weights<T: Copy>(
+        &mut self,
+        layer_name: &str,
+        role: WeightsRole,
+        data: &[T],
+        dtype: DataType,
+    ) -> bool {
+        let (bytes, count) = as_weights(data, dtype);
+
+        self.refitter
+            .pin_mut()
+            .set_weights(layer_name, role as _, dtype as _, bytes, count as _)
+    }
+
+    /// Stages new weights by name, as listed by `get_all_weights`, e.g. the
+    /// initializer names of an ONNX model. `data` is copied and holds
+    /// elements of `dtype`. Returns false for unknown names or mismatched
+    /// counts or types, and before TensorRT 8.5.
+    pub fn set_named_weights<T: Copy>(&mut self, name: &str, data: &[T], dtype: DataType) -> bool {
+        let (bytes, count) = as_weights(data, dtype);
+
+        self.refitter
+            .pin_mut()
+            .set_named_weights(name, dtype as _, bytes, count as _)
+    }
+
+    /// Like `set_named_weights`, but `count` elements of `dtype` are read
+    /// from `ptr` in `location` directly instead of being copied, so weights
+    /// already on the GPU skip the round trip through host memory. Returns
+    /// false before TensorRT 10.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` has to point at `count` elements of `dtype` in `location` that
+    /// stay valid until the engine has been refitted, for
+    /// `refit_cuda_engine_async` until its stream has been synchronized.
+    pub unsafe fn set_named_weights_from_ptr(
+        &mut self,
+        name: &str,
+        ptr: usize,
+        count: usize,
+        dtype: DataType,
+        location: TensorLocation,
+    ) -> bool {
+        self.refitter.pin_mut().set_named_weights_from_ptr(
+            name,
+            dtype as _,
+            ptr,
+            count as _,
+            location as _,
+        )
+    }
+
+    /// Names of all weights that can be refitted by name.
+    pub fn get_all_weights(&self) -> Vec<String> {
+        self.refitter.get_all_weights()
+    }
+
+    /// Names of the weights that still have to be set before
+    /// `refit_cuda_engine` succeeds, e.g. a bias fused with a kernel that was
+    /// set.
+    pub fn get_missing_weights(&self) -> Vec<String> {
+        self.refitter.get_missing_weights()
+    }
+
+    /// Applies the staged weights. Fails if weights the engine needs are
+    /// still missing, or while the engine runs inferences.
+    pub fn refit_cuda_engine(&mut self) -> bool {
+        self.refitter.pin_mut().refit_cuda_engine()
+    }
+
+    /// Like `refit_cuda_engine`, but updates the engine on `stream`, ordered
+    /// with the work that produced device weights. Returns false before
+    /// TensorRT 10.
+    ///
+    /// # Safety
+    ///
+    /// The refit is still running when this returns: the engine must not run
+    /// inferences, and the weights set from pointers must stay valid, until
+    /// `stream` has been synchronized.
+    pub unsafe fn refit_cuda_engine_async(&mut self, stream: &CuStream) -> bool {
+        let stream_raw = stream.get_raw();
+        self.refitter.pin_mut().refit_cuda_engine_async(stream_raw as usize)
+    }
+}
+
+// The bytes of `data` and how many `dtype` elements they hold.
+fn as_weights<T: Copy>(data: &[T], dtype: DataType) -> (&[u8], usize) {
+    let bytes = unsafe {
+        std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data))
+    };
+
+    (bytes, dtype.get_num_elems(bytes.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Builder, BuilderFlag, NetworkDefinition},
+        runtime::Runtime,
+    };
+    use cuda_rs::{device::CuDevice, memory::DeviceMemory};
+
+    // Runs the 2x8x8 -> 4x6x6 convolution of the tests on an input of ones.
+    fn run_convolution(engine: &mut CudaEngine, stream: &CuStream) -> Vec<f32> {
+        let mut context = engine.create_execution_context().unwrap();
+        let input = vec![1.0f32; 2 * 8 * 8];
+        let x = DeviceMemory::new(input.len() * 4, stream).unwrap();
+        x.copy_from_raw(input.as_ptr() as _, input.len() * 4, None).unwrap();
+        let y = DeviceMemory::new(4 * 6 * 6 * 4, stream).unwrap();
+        assert!(context.set_tensor_address("x", unsafe { x.get_raw() } as usize));
+        assert!(context.set_tensor_address("y", unsafe { y.get_raw() } as usize));
+
+        assert!(context.enqueue_v3(stream));
+        let output = y.to_host().unwrap();
+        stream.synchronize().unwrap();
+
+        output.as_slice::<f32>().to_vec()
+    }
+
+    #[test]
+    fn test_weights_role_try_from() {
+        assert_eq!(WeightsRole::try_from(0), Ok(WeightsRole::KERNEL));
+        assert_eq!(WeightsRole::try_from(4), Ok(WeightsRole::CONSTANT));
+        assert_eq!(WeightsRole::try_from(42), Err(42));
+    }
+
+    #[test]
+    fn test_tensor_location_try_from() {
+        assert_eq!(TensorLocation::try_from(0), Ok(TensorLocation::DEVICE));
+        assert_eq!(TensorLocation::try_from(1), Ok(TensorLocation::HOST));
+        assert_eq!(TensorLocation::try_from(2), Err(2));
+    }
+
+    #[test]
+    fn test_refit_convolution_kernel() {
+        cuda_rs::init().unwrap();
+        let device = CuDevice::new(0).unwrap();
+        let ctx = device.retain_primary_context().unwrap();
+        let _guard = ctx.guard().unwrap();
+        let stream = CuStream::new().unwrap();
+
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        config.set_flag(BuilderFlag::REFIT);
+
+        let mut network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[1, 2, 8, 8]).unwrap();
+        let kernel = vec![1.0f32; 4 * 2 * 3 * 3];
+        let bias = vec![0.0f32; 4];
+        let too_many = [3; NetworkDefinition::MAX_DIMS + 1];
+        assert!(network.add_convolution("conv", input, 4, &too_many, &kernel, &bias).is_none());
+        let output = network
+            .add_convolution("conv", input, 4, &[3, 3], &kernel, &bias)
+            .unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+
+        let plan = builder.build_serialized_network(&mut network, &mut config).unwrap();
+        let mut runtime = Runtime::new().unwrap();
+        let mut engine = runtime.deserialize(plan.as_bytes()).unwrap();
+        assert!(engine.is_refittable());
+        // 2 channels of 3x3 ones
+        assert!(run_convolution(&mut engine, &stream).iter().all(|&y| y == 18.0));
+
+        let mut refitter = Refitter::new(&mut engine).unwrap();
+        let kernel = vec![0.5f32; kernel.len()];
+        assert!(refitter.set_weights("conv", WeightsRole::KERNEL, &kernel, DataType::FLOAT));
+        assert!(refitter.set_weights("conv", WeightsRole::BIAS, &[1.0f32; 4], DataType::FLOAT));
+        assert!(refitter.refit_cuda_engine());
+
+        // an unknown layer or a wrong weight count is rejected
+        assert!(!refitter.set_weights("missing", WeightsRole::KERNEL, &kernel, DataType::FLOAT));
+        assert!(!refitter.set_weights("conv", WeightsRole::KERNEL, &kernel[..3], DataType::FLOAT));
+        drop(refitter);
+
+        assert!(run_convolution(&mut engine, &stream).iter().all(|&y| y == 10.0));
+    }
+
+    #[test]
+    fn test_refit_named_weights() {
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        config.set_flag(BuilderFlag::REFIT);
+
+        let mut network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[1, 2, 8, 8]).unwrap();
+        let kernel = vec![1.0f32; 4 * 2 * 3 * 3];
+        let output = network
+            .add_convolution("conv", input, 4, &[3, 3], &kernel, &[0.0f32; 4])
+            .unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+
+        let plan = builder.build_serialized_network(&mut network, &mut config).unwrap();
+        let mut runtime = Runtime::new().unwrap();
+        let mut engine = runtime.deserialize(plan.as_bytes()).unwrap();
+
+        let mut refitter = Refitter::new(&mut engine).unwrap();
+        let mut names = refitter.get_all_weights();
+        if names.is_empty() {
+            println!("Named weights are not supported! Skip test!");
+            return;
+        }
+        names.sort();
+        assert_eq!(names, vec!["conv.bias", "conv.weight"]);
+
+        let kernel = vec![0.5f32; kernel.len()];
+        assert!(refitter.set_named_weights("conv.weight", &kernel, DataType::FLOAT));
+        assert!(!refitter.set_named_weights("missing", &kernel, DataType::FLOAT));
+        for name in refitter.get_missing_weights() {
+            assert_eq!(name, "conv.bias");
+            assert!(refitter.set_named_weights(&name, &[1.0f32; 4], DataType::FLOAT));
+        }
+        assert!(refitter.get_missing_weights().is_empty());
+        assert!(refitter.refit_cuda_engine());
+    }
+}
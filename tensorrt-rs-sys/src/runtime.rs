@@ -1,6 +1,13 @@
-use crate::{ffi, logger::Logger};
+use crate::{
+    allocator::{GpuAllocator, RustGpuAllocator},
+    ffi,
+    logger::Logger,
+    profiler::{Profiler, RustProfiler},
+    recorder::ErrorRecorder,
+};
 use cxx::UniquePtr;
 use cuda_rs::{event::CuEvent, stream::CuStream};
+use std::{collections::HashMap, ffi::c_void, fmt, thread, time::Duration};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum DataType {
@@ -36,7 +43,17 @@ pub enum DataType {
     // Signed 8-bit floating point with
     // 1 sign bit, 4 exponent bits, 3 mantissa bits, and exponent-bias 7.
     // \warning kFP8 is not supported yet and will result in an error or undefined behavior.
-    FP8 = 6
+    FP8 = 6,
+
+    // Brain float, with 8 exponent bits and 7 mantissa bits (TensorRT 9+).
+    BF16 = 7,
+
+    // Signed 64-bit integer format (TensorRT 10+).
+    INT64 = 8,
+
+    // Signed 4-bit integer format, packed two elements per byte (TensorRT 10+).
+    // Used for weight-only quantization.
+    INT4 = 9,
 }
 
 impl DataType {
@@ -44,11 +61,68 @@ impl DataType {
         match self {
             DataType::FLOAT => 4,
             DataType::HALF => 2,
+            DataType::BF16 => 2,
             DataType::INT8 => 1,
             DataType::INT32 => 4,
             DataType::BOOL => 1,
             DataType::UINT8 => 1,
             DataType::FP8 => 1,
+            DataType::INT64 => 8,
+            // a lone element still takes a whole byte, see `get_byte_size`
+            DataType::INT4 => 1,
+        }
+    }
+
+    /// Bytes taken by `numel` densely packed elements. Sub-byte types round
+    /// up to a whole byte, so 3 INT4 elements take 2 bytes.
+    pub fn get_byte_size(&self, numel: usize) -> usize {
+        match self {
+            DataType::INT4 => numel.div_ceil(2),
+            dtype => numel * dtype.get_elem_size(),
+        }
+    }
+
+    /// Number of elements that fit in `byte_size` bytes.
+    pub fn get_num_elems(&self, byte_size: usize) -> usize {
+        match self {
+            DataType::INT4 => byte_size * 2,
+            dtype => byte_size / dtype.get_elem_size(),
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, DataType::FLOAT | DataType::HALF | DataType::BF16 | DataType::FP8)
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            DataType::INT8 | DataType::INT32 | DataType::UINT8 | DataType::INT64 | DataType::INT4
+        )
+    }
+
+    // INT8, FP8 and INT4 carry quantized floating-point values.
+    pub fn is_quantized(&self) -> bool {
+        matches!(self, DataType::INT8 | DataType::FP8 | DataType::INT4)
+    }
+}
+
+impl TryFrom<i32> for DataType {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(DataType::FLOAT),
+            1 => Ok(DataType::HALF),
+            2 => Ok(DataType::INT8),
+            3 => Ok(DataType::INT32),
+            4 => Ok(DataType::BOOL),
+            5 => Ok(DataType::UINT8),
+            6 => Ok(DataType::FP8),
+            7 => Ok(DataType::BF16),
+            8 => Ok(DataType::INT64),
+            9 => Ok(DataType::INT4),
+            dtype => Err(dtype),
         }
     }
 }
@@ -71,6 +145,21 @@ impl TensorIOMode {
     pub fn is_output(&self) -> bool {
         *self == TensorIOMode::OUTPUT
     }
+
+    pub fn is_none(&self) -> bool {
+        *self == TensorIOMode::NONE
+    }
+}
+
+impl fmt::Display for TensorIOMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TensorIOMode::NONE => "NONE",
+            TensorIOMode::INPUT => "INPUT",
+            TensorIOMode::OUTPUT => "OUTPUT",
+        };
+        f.write_str(name)
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -198,6 +287,51 @@ pub enum TensorFormat {
     DHWC = 12
 }
 
+impl TryFrom<i32> for TensorFormat {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TensorFormat::LINEAR),
+            1 => Ok(TensorFormat::CHW2),
+            2 => Ok(TensorFormat::HWC8),
+            3 => Ok(TensorFormat::CHW4),
+            4 => Ok(TensorFormat::CHW16),
+            5 => Ok(TensorFormat::CHW32),
+            6 => Ok(TensorFormat::DHWC8),
+            7 => Ok(TensorFormat::CDHW32),
+            8 => Ok(TensorFormat::HWC),
+            9 => Ok(TensorFormat::DLALINEAR),
+            10 => Ok(TensorFormat::DLAHWC4),
+            11 => Ok(TensorFormat::HWC16),
+            12 => Ok(TensorFormat::DHWC),
+            format => Err(format),
+        }
+    }
+}
+
+impl fmt::Display for TensorFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TensorFormat::LINEAR => "LINEAR",
+            TensorFormat::CHW2 => "CHW2",
+            TensorFormat::HWC8 => "HWC8",
+            TensorFormat::CHW4 => "CHW4",
+            TensorFormat::CHW16 => "CHW16",
+            TensorFormat::CHW32 => "CHW32",
+            TensorFormat::DHWC8 => "DHWC8",
+            TensorFormat::CDHW32 => "CDHW32",
+            TensorFormat::HWC => "HWC",
+            TensorFormat::DLALINEAR => "DLA_LINEAR",
+            TensorFormat::DLAHWC4 => "DLA_HWC4",
+            TensorFormat::HWC16 => "HWC16",
+            TensorFormat::DHWC => "DHWC",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum EngineCapability {
     //
     // Standard: TensorRT flow without targeting the safety runtime.
@@ -241,19 +375,56 @@ pub enum ProfilingVerbosity {
     DETAILED = 2,           //< Print detailed layer information including layer names and layer parameters.
 }
 
+impl TryFrom<i32> for ProfilingVerbosity {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ProfilingVerbosity::LAYERNAMESONLY),
+            1 => Ok(ProfilingVerbosity::NONE),
+            2 => Ok(ProfilingVerbosity::DETAILED),
+            verbosity => Err(verbosity),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum OptProfileSelector {
+    // This is used to set or get the minimum permitted value for dynamic dimensions etc.
+    MIN = 0,
+    // This is used to set or get the value that is used in the optimization (kernel selection).
+    OPT = 1,
+    // This is used to set or get the maximum permitted value for dynamic dimensions etc.
+    MAX = 2,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SerializationFlag {
+    // Exclude the weights that can be refitted.
+    EXCLUDEWEIGHTS = 0,
+    // Exclude the lean runtime from a version compatible plan.
+    EXCLUDELEANRUNTIME = 1,
+}
+
 pub struct Runtime {
     pub(crate) runtime: UniquePtr<ffi::Runtime>,
     logger: Logger,
+    last_loaded_engine_size: Option<usize>,
 }
 
 impl Runtime {
     pub fn new() -> Option<Self> {
-        let mut logger = Logger::new();
+        Self::with_logger(Logger::new())
+    }
+
+    /// Creates a runtime that reports through `logger`, including the
+    /// messages TensorRT emits while deserializing engines.
+    pub fn with_logger(mut logger: Logger) -> Option<Self> {
         let runtime = ffi::create_runtime(logger.0.pin_mut());
         if runtime.is_null() {
             None
         } else {
-            Some(Self { runtime, logger })
+            Some(Self { runtime, logger, last_loaded_engine_size: None })
         }
     }
 
@@ -266,10 +437,55 @@ impl Runtime {
         if engine.is_null() {
             None
         } else {
-            Some(CudaEngine(engine))
+            let engine = CudaEngine(engine);
+            self.last_loaded_engine_size = Some(data.len() + engine.device_memory_size());
+            Some(engine)
         }
     }
 
+    /// Like `deserialize`, but retries up to `attempts` times in total while
+    /// the failure is reported as running out of GPU memory, e.g. when other
+    /// processes spike briefly. The wait starts at `backoff` and doubles.
+    ///
+    /// On failure, returns the error TensorRT logged for the last attempt.
+    pub fn deserialize_with_retry(
+        &mut self,
+        data: &[u8],
+        attempts: usize,
+        backoff: Duration,
+    ) -> Result<CudaEngine, String> {
+        retry_on_oom(attempts, backoff, || {
+            self.logger.take_last_error();
+            match self.deserialize(data) {
+                Some(engine) => Ok(engine),
+                None => Err(self
+                    .logger
+                    .take_last_error()
+                    .unwrap_or_else(|| "failed to deserialize the engine".to_string())),
+            }
+        })
+    }
+
+    /// Device memory the last engine deserialized by this runtime takes with
+    /// one execution context: its plan, which TensorRT loads onto the device
+    /// (the "Loaded engine size" it logs), plus the context's scratch memory.
+    /// `None` until an engine was loaded.
+    pub fn last_loaded_engine_size(&self) -> Option<usize> {
+        self.last_loaded_engine_size
+    }
+
+    /// Whether `data` is a plan this runtime can load on the current device,
+    /// e.g. one built for the same compute capability (or with a hardware
+    /// compatibility level covering it) by a matching TensorRT version.
+    ///
+    /// On TensorRT 10.12+ only the plan header is inspected. Older versions
+    /// fall back to a trial deserialization, which takes as long as loading the
+    /// engine and briefly needs its device memory, so prefer deserializing
+    /// directly there instead of checking first.
+    pub fn is_engine_compatible(&self, data: &[u8]) -> bool {
+        self.runtime.is_engine_compatible(data)
+    }
+
     pub fn set_max_threads(&mut self, max_threads: i32) -> bool {
         self.runtime.pin_mut().set_max_threads(max_threads)
     }
@@ -285,32 +501,100 @@ impl Runtime {
     pub fn get_engine_host_code_allowed(&self) -> bool {
         self.runtime.get_engine_host_code_allowed()
     }
+
+    /// Serves the device memory of engines deserialized from now on, and of
+    /// their execution contexts, from `allocator`. Those engines keep the
+    /// allocator alive after the runtime is dropped.
+    pub fn set_gpu_allocator<A: GpuAllocator + 'static>(&mut self, allocator: A) {
+        let allocator = Box::new(RustGpuAllocator(Box::new(allocator)));
+        self.runtime.pin_mut().set_gpu_allocator(allocator)
+    }
+
+    /// Reports this runtime's errors to `recorder` instead of the logger,
+    /// including those of the engines it deserializes from now on.
+    pub fn set_error_recorder(&mut self, recorder: &ErrorRecorder) {
+        self.runtime.pin_mut().set_error_recorder(&recorder.0)
+    }
+
+    pub fn has_error_recorder(&self) -> bool {
+        self.runtime.has_error_recorder()
+    }
+}
+
+fn is_out_of_memory(msg: &str) -> bool {
+    let msg = msg.to_lowercase();
+    ["out of memory", "out_of_memory", "could not be allocated", "memoryallocation"]
+        .iter()
+        .any(|pattern| msg.contains(pattern))
+}
+
+// Runs `attempt` until it succeeds, fails for another reason than running
+// out of memory, or `attempts` runs out, returning the last error then.
+fn retry_on_oom<T, F>(attempts: usize, backoff: Duration, mut attempt: F) -> Result<T, String>
+where
+    F: FnMut() -> Result<T, String>,
+{
+    let mut delay = backoff;
+    let mut i = 1;
+    loop {
+        match attempt() {
+            Err(msg) if is_out_of_memory(&msg) && i < attempts => {
+                thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+                i += 1;
+            }
+            res => return res,
+        }
+    }
 }
 
 pub struct CudaEngine(pub(crate) UniquePtr<ffi::CudaEngine>);
 
 impl CudaEngine {
+    /// A stable 64-bit hash of a serialized plan, to check that a loaded
+    /// engine is the expected artifact. Not a cryptographic hash.
+    ///
+    /// Engines hash their plan on every load, so this is FNV-1a over 64-bit
+    /// little-endian words rather than bytes: the last word is zero-padded,
+    /// and the plan's length is mixed in last to tell the padding apart.
+    pub fn plan_hash(data: &[u8]) -> u64 {
+        const PRIME: u64 = 0x0100_0000_01b3;
+
+        let chunks = data.chunks_exact(8);
+        let remainder = chunks.remainder();
+        let mut hash = chunks.fold(0xcbf2_9ce4_8422_2325, |hash, chunk| {
+            (hash ^ u64::from_le_bytes(chunk.try_into().unwrap())).wrapping_mul(PRIME)
+        });
+        if !remainder.is_empty() {
+            let mut last = [0; 8];
+            last[..remainder.len()].copy_from_slice(remainder);
+            hash = (hash ^ u64::from_le_bytes(last)).wrapping_mul(PRIME);
+        }
+
+        (hash ^ data.len() as u64).wrapping_mul(PRIME)
+    }
+
     pub fn get_tensor_shape(&self, name: &str) -> Vec<i32> {
         self.0.get_tensor_shape(name)
     }
 
     pub fn get_tensor_dtype(&self, name: &str) -> DataType {
-        match self.0.get_tensor_dtype(name) {
-            0 => DataType::FLOAT as _,
-            1 => DataType::HALF as _,
-            2 => DataType::INT8 as _,
-            3 => DataType::INT32 as _,
-            4 => DataType::BOOL as _,
-            5 => DataType::UINT8 as _,
-            6 => DataType::FP8 as _,
-            dtype => panic!("Invalid data type: {}", dtype),
+        match self.try_get_tensor_dtype(name) {
+            Ok(dtype) => dtype,
+            Err(dtype) => panic!("Invalid data type {} for tensor '{}'", dtype, name),
         }
     }
 
+    pub fn try_get_tensor_dtype(&self, name: &str) -> Result<DataType, i32> {
+        DataType::try_from(self.0.get_tensor_dtype(name))
+    }
+
     pub fn get_num_layers(&self) -> i32 {
         self.0.get_num_layers()
     }
 
+    /// Creates a context that shares ownership of the engine, so it stays
+    /// usable even if this `CudaEngine` is dropped first.
     pub fn create_execution_context(&mut self) -> Option<ExecutionContext> {
         let context = self.0.pin_mut().create_execution_context();
         if context.is_null() {
@@ -347,6 +631,23 @@ impl CudaEngine {
         self.0.get_device_memory_size()
     }
 
+    #[cfg(trt_major = "10")]
+    pub fn device_memory_size_v2(&self) -> usize {
+        self.0.get_device_memory_size_v2()
+    }
+
+    /// Device memory an execution context needs. The wrapper picks
+    /// `getDeviceMemorySizeV2` when built against TensorRT 10.
+    pub fn device_memory_size(&self) -> usize {
+        self.0.get_device_memory_size_v2()
+    }
+
+    /// Device memory an execution context needs while `profile_index` is
+    /// selected. Before TensorRT 10 this is the profile-agnostic size.
+    pub fn device_memory_size_for_profile(&self, profile_index: i32) -> usize {
+        self.0.get_device_memory_size_for_profile_v2(profile_index)
+    }
+
     pub fn is_refittable(&self) -> bool {
         self.0.is_refittable()
     }
@@ -360,24 +661,16 @@ impl CudaEngine {
     }
 
     pub fn get_tensor_format(&self, name: &str) -> TensorFormat {
-        match self.0.get_tensor_format(name) {
-            0 => TensorFormat::LINEAR,
-            1 => TensorFormat::CHW2,
-            2 => TensorFormat::HWC8,
-            3 => TensorFormat::CHW4,
-            4 => TensorFormat::CHW16,
-            5 => TensorFormat::CHW32,
-            6 => TensorFormat::DHWC8,
-            7 => TensorFormat::CDHW32,
-            8 => TensorFormat::HWC,
-            9 => TensorFormat::DLALINEAR,
-            10 => TensorFormat::DLAHWC4,
-            11 => TensorFormat::HWC16,
-            12 => TensorFormat::DHWC,
-            format => panic!("Invalid tensor format: {}", format),
+        match TensorFormat::try_from(self.0.get_tensor_format(name)) {
+            Ok(format) => format,
+            Err(format) => panic!("Invalid tensor format: {}", format),
         }
     }
 
+    pub fn get_tensor_format_desc(&self, name: &str) -> String {
+        self.0.get_tensor_format_desc(name)
+    }
+
     pub fn get_tensor_vectorized_dim(&self, name: &str) -> i32 {
         self.0.get_tensor_vectorized_dim(name)
     }
@@ -390,6 +683,16 @@ impl CudaEngine {
         self.0.get_num_optimization_profiles()
     }
 
+    /// Empty for outputs, unknown tensors and out-of-range profile indices.
+    pub fn get_profile_shape(
+        &self,
+        name: &str,
+        profile_index: i32,
+        select: OptProfileSelector,
+    ) -> Vec<i32> {
+        self.0.get_profile_shape(name, profile_index, select as _)
+    }
+
     pub fn get_engine_capability(&self) -> EngineCapability {
         match self.0.get_engine_capability() {
             0 => EngineCapability::STANDARD,
@@ -411,6 +714,53 @@ impl CudaEngine {
         self.0.get_io_tensor_name(index)
     }
 
+    /// The IO tensors as an aligned ASCII table with their mode, data type,
+    /// shape and format, for logs and command line tools.
+    pub fn io_summary(&self) -> String {
+        let mut rows = vec![["name", "mode", "dtype", "shape", "format"].map(String::from)];
+        for i in 0..self.get_num_io_tensors() {
+            let name = self.get_io_tensor_name(i);
+            let dtype = match self.try_get_tensor_dtype(name) {
+                Ok(dtype) => format!("{:?}", dtype),
+                Err(dtype) => dtype.to_string(),
+            };
+            rows.push([
+                name.to_string(),
+                self.get_tensor_io_mode(name).to_string(),
+                dtype,
+                format!("{:?}", self.get_tensor_shape(name)),
+                self.get_tensor_format(name).to_string(),
+            ]);
+        }
+
+        let widths: Vec<usize> = (0..rows[0].len())
+            .map(|col| rows.iter().map(|row| row[col].len()).max().unwrap_or(0))
+            .collect();
+        let rule = widths
+            .iter()
+            .map(|width| "-".repeat(width + 2))
+            .collect::<Vec<_>>()
+            .join("+");
+        let rule = format!("+{}+\n", rule);
+
+        let mut table = rule.clone();
+        for (i, row) in rows.iter().enumerate() {
+            let cells = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!(" {:<width$} ", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("|");
+            table.push_str(&format!("|{}|\n", cells));
+            if i == 0 {
+                table.push_str(&rule);
+            }
+        }
+        table.push_str(&rule);
+
+        table
+    }
+
     pub fn get_hardware_compatibility_level(&self) -> HardwareCompatibilityLevel {
         match self.0.get_hardware_compatibility_level() {
             0 => HardwareCompatibilityLevel::NONE,
@@ -422,6 +772,118 @@ impl CudaEngine {
     pub fn get_num_aux_streams(&self) -> i32 {
         self.0.get_num_aux_streams()
     }
+
+    pub fn serialize(&self) -> Option<HostMemory> {
+        let memory = self.0.serialize();
+        if memory.is_null() {
+            None
+        } else {
+            Some(HostMemory(memory))
+        }
+    }
+
+    /// Serializes the engine without the parts named by `flags`, e.g. the
+    /// weights of a refittable engine that get refitted after loading anyway.
+    /// Returns `None` before TensorRT 10.
+    pub fn serialize_with_flags(&self, flags: &[SerializationFlag]) -> Option<HostMemory> {
+        let mut config = self.create_serialization_config()?;
+        if !config.set_flags(flags) {
+            return None;
+        }
+
+        self.serialize_with_config(&mut config)
+    }
+
+    /// Returns `None` before TensorRT 10.
+    pub fn create_serialization_config(&self) -> Option<SerializationConfig> {
+        let config = self.0.create_serialization_config();
+        if config.is_null() {
+            None
+        } else {
+            Some(SerializationConfig(config))
+        }
+    }
+
+    pub fn serialize_with_config(&self, config: &mut SerializationConfig) -> Option<HostMemory> {
+        let memory = self.0.serialize_with_config(config.0.pin_mut());
+        if memory.is_null() {
+            None
+        } else {
+            Some(HostMemory(memory))
+        }
+    }
+
+    /// Returns the underlying `nvinfer1::ICudaEngine*`, an escape hatch for
+    /// handing the engine to other C++ code.
+    ///
+    /// # Safety
+    ///
+    /// The pointer is owned by this `CudaEngine`: it is only valid while the
+    /// engine lives and must not be deleted.
+    pub unsafe fn as_raw_ptr(&self) -> *mut c_void {
+        self.0.get_raw() as _
+    }
+
+    /// Reports this engine's errors to `recorder`, including those of the
+    /// execution contexts created from now on.
+    pub fn set_error_recorder(&mut self, recorder: &ErrorRecorder) {
+        self.0.pin_mut().set_error_recorder(&recorder.0)
+    }
+
+    /// Whether errors go to a recorder, set on the engine or inherited from
+    /// its runtime.
+    pub fn has_error_recorder(&self) -> bool {
+        self.0.has_error_recorder()
+    }
+}
+
+pub struct HostMemory(pub(crate) UniquePtr<ffi::HostMemory>);
+
+impl HostMemory {
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.data()
+    }
+}
+
+/// What `CudaEngine::serialize_with_config` leaves out of the plan. The
+/// setters return false if the flag is not supported.
+pub struct SerializationConfig(pub(crate) UniquePtr<ffi::SerializationConfig>);
+
+impl SerializationConfig {
+    /// Replaces all flags with `flags`.
+    pub fn set_flags(&mut self, flags: &[SerializationFlag]) -> bool {
+        let flags = flags.iter().fold(0u32, |bits, &flag| bits | 1 << flag as u32);
+        self.0.pin_mut().set_flags(flags)
+    }
+
+    pub fn set_flag(&mut self, flag: SerializationFlag) -> bool {
+        self.0.pin_mut().set_flag(flag as _)
+    }
+
+    pub fn clear_flag(&mut self, flag: SerializationFlag) -> bool {
+        self.0.pin_mut().clear_flag(flag as _)
+    }
+
+    pub fn get_flag(&self, flag: SerializationFlag) -> bool {
+        self.0.get_flag(flag as _)
+    }
+
+    pub fn get_flags(&self) -> Vec<SerializationFlag> {
+        let bits = self.0.get_flags();
+        [SerializationFlag::EXCLUDEWEIGHTS, SerializationFlag::EXCLUDELEANRUNTIME]
+            .into_iter()
+            .filter(|&flag| bits & 1 << flag as u32 != 0)
+            .collect()
+    }
+}
+
+pub struct CudaGraph(pub(crate) UniquePtr<ffi::CudaGraph>);
+
+impl CudaGraph {
+    pub fn launch(&mut self, stream: &CuStream) -> bool {
+        let stream_raw = unsafe { stream.get_raw() };
+        self.0.pin_mut().launch(stream_raw as usize)
+    }
 }
 
 pub struct ExecutionContext(pub(crate) UniquePtr<ffi::ExecutionContext>);
@@ -447,6 +909,13 @@ impl ExecutionContext {
         self.0.pin_mut().set_device_memory(memory)
     }
 
+    /// Like `set_device_memory`, but tells TensorRT the buffer's `size` so it
+    /// can check it against the selected profile. Falls back to
+    /// `set_device_memory` before TensorRT 10.1.
+    pub fn set_device_memory_v2(&mut self, memory: usize, size: usize) {
+        self.0.pin_mut().set_device_memory_v2(memory, size as _)
+    }
+
     pub fn get_tensor_strides(&self, name: &str) -> Vec<i32> {
         self.0.get_tensor_strides(name)
     }
@@ -471,6 +940,12 @@ impl ExecutionContext {
         self.0.all_input_shapes_specified()
     }
 
+    /// Propagates the input shapes to all outputs. Returns false if some input
+    /// shapes are still missing or shape inference failed.
+    pub fn infer_shapes(&mut self) -> bool {
+        self.0.pin_mut().infer_shapes() == 0
+    }
+
     pub fn set_optimization_profile_async(
         &mut self,
         profile_index: i32,
@@ -490,10 +965,23 @@ impl ExecutionContext {
         self.0.get_enqueue_emits_profile()
     }
 
+    /// Reports the layer times of the last enqueue to the profiler; the
+    /// stream must be synchronized first.
     pub fn report_to_profiler(&mut self) {
         self.0.pin_mut().report_to_profiler()
     }
 
+    /// Reports layer times to `profiler` from now on, replacing any earlier
+    /// one. See `Profiler` for when they are reported.
+    pub fn set_profiler<P: Profiler + 'static>(&mut self, profiler: P) {
+        let profiler = Box::new(RustProfiler(Box::new(profiler)));
+        self.0.pin_mut().set_profiler(profiler)
+    }
+
+    pub fn has_profiler(&self) -> bool {
+        self.0.has_profiler()
+    }
+
     pub fn set_tensor_address(&mut self, name: &str, address: usize) -> bool {
         self.0.pin_mut().set_tensor_address(name, address)
     }
@@ -502,6 +990,24 @@ impl ExecutionContext {
         self.0.get_tensor_address(name)
     }
 
+    pub fn get_all_tensor_addresses<'a>(&self, engine: &'a CudaEngine) -> HashMap<&'a str, usize> {
+        (0..engine.get_num_io_tensors())
+            .map(|i| engine.get_io_tensor_name(i))
+            .map(|name| (name, self.get_tensor_address(name)))
+            .collect()
+    }
+
+    // Returns the name of the first tensor whose address was rejected.
+    pub fn set_all_tensor_addresses(&mut self, addresses: &HashMap<&str, usize>) -> Result<(), String> {
+        for (name, address) in addresses {
+            if !self.set_tensor_address(name, *address) {
+                return Err(name.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn set_input_tensor_address(&mut self, name: &str, address: usize) -> bool {
         self.0.pin_mut().set_input_tensor_address(name, address)
     }
@@ -529,6 +1035,30 @@ impl ExecutionContext {
         self.0.pin_mut().enqueue_v3(stream_raw as usize)
     }
 
+    /// Records one `enqueue_v3` on `stream` into a CUDA graph, including the
+    /// work TensorRT forks onto its aux streams. Nothing runs while capturing.
+    ///
+    /// Enqueue once before capturing so TensorRT can finish its deferred
+    /// setup. The graph replays with the shapes and tensor addresses bound at
+    /// capture time.
+    pub fn capture_graph(&mut self, stream: &CuStream) -> Option<CudaGraph> {
+        let stream_raw = unsafe { stream.get_raw() };
+        let graph = self.0.pin_mut().capture_graph(stream_raw as usize);
+        if graph.is_null() {
+            None
+        } else {
+            Some(CudaGraph(graph))
+        }
+    }
+
+    /// Re-captures the enqueue and updates `graph` in place, which is much
+    /// cheaper than instantiating a new graph. Only succeeds if the topology
+    /// is unchanged, e.g. when just tensor addresses differ.
+    pub fn update_graph(&mut self, graph: &mut CudaGraph, stream: &CuStream) -> bool {
+        let stream_raw = unsafe { stream.get_raw() };
+        self.0.pin_mut().update_graph(graph.0.pin_mut(), stream_raw as usize)
+    }
+
     pub fn set_persistent_cache_limit(&mut self, limit: usize) {
         self.0.pin_mut().set_persistent_cache_limit(limit)
     }
@@ -548,6 +1078,27 @@ impl ExecutionContext {
             .collect();
         self.0.pin_mut().set_aux_streams(streams.as_slice())
     }
+
+    /// Returns the underlying `nvinfer1::IExecutionContext*`, an escape hatch
+    /// for handing the context to other C++ code.
+    ///
+    /// # Safety
+    ///
+    /// The pointer is owned by this `ExecutionContext`: it is only valid while
+    /// the context lives and must not be deleted.
+    pub unsafe fn as_raw_ptr(&self) -> *mut c_void {
+        self.0.get_raw() as _
+    }
+
+    pub fn set_error_recorder(&mut self, recorder: &ErrorRecorder) {
+        self.0.pin_mut().set_error_recorder(&recorder.0)
+    }
+
+    /// Whether errors go to a recorder, set on the context or inherited from
+    /// its engine.
+    pub fn has_error_recorder(&self) -> bool {
+        self.0.has_error_recorder()
+    }
 }
 
 #[cfg(test)]
@@ -555,6 +1106,106 @@ mod tests {
     use super::*;
     use crate::logger::Severity;
 
+    #[test]
+    fn test_retry_on_oom() {
+        let mut calls = 0;
+        let res = retry_on_oom(3, Duration::from_millis(1), || {
+            calls += 1;
+            match calls {
+                1 => Err("Cuda Runtime (out of memory)".to_string()),
+                _ => Ok(calls),
+            }
+        });
+        assert_eq!(res, Ok(2));
+
+        // other failures are not retried
+        let mut calls = 0;
+        let res: Result<(), _> = retry_on_oom(3, Duration::from_millis(1), || {
+            calls += 1;
+            Err("Serialization assertion failed".to_string())
+        });
+        assert_eq!((res, calls), (Err("Serialization assertion failed".to_string()), 1));
+
+        let mut calls = 0;
+        let res: Result<(), _> = retry_on_oom(2, Duration::from_millis(1), || {
+            calls += 1;
+            Err(format!("CUDA_ERROR_OUT_OF_MEMORY ({})", calls))
+        });
+        assert_eq!((res, calls), (Err("CUDA_ERROR_OUT_OF_MEMORY (2)".to_string()), 2));
+    }
+
+    #[test]
+    fn test_plan_hash() {
+        let plan = b"plan bytes".to_vec();
+        assert_eq!(CudaEngine::plan_hash(&plan), CudaEngine::plan_hash(&plan.clone()));
+        assert_ne!(CudaEngine::plan_hash(&plan), CudaEngine::plan_hash(b"plan bytez"));
+        assert_ne!(CudaEngine::plan_hash(&plan), CudaEngine::plan_hash(&plan[1..]));
+        // zero padding does not collide with the unpadded plan
+        assert_ne!(CudaEngine::plan_hash(b"a"), CudaEngine::plan_hash(b"a\0"));
+        // pinned, as the hashes are compared across runs
+        assert_eq!(CudaEngine::plan_hash(b""), 0xaf63_bd4c_8601_b7df);
+        assert_eq!(CudaEngine::plan_hash(b"a"), 0x089b_e307_b544_f397);
+    }
+
+    #[test]
+    fn test_dtype_try_from() {
+        assert_eq!(DataType::try_from(0), Ok(DataType::FLOAT));
+        assert_eq!(DataType::try_from(6), Ok(DataType::FP8));
+        assert_eq!(DataType::try_from(7), Ok(DataType::BF16));
+        assert_eq!(DataType::try_from(8), Ok(DataType::INT64));
+        assert_eq!(DataType::try_from(9), Ok(DataType::INT4));
+        assert_eq!(DataType::try_from(42), Err(42));
+        assert_eq!(DataType::try_from(-1), Err(-1));
+    }
+
+    #[test]
+    fn test_dtype_predicates() {
+        assert!(DataType::FLOAT.is_float() && !DataType::FLOAT.is_integer());
+        assert!(DataType::INT32.is_integer() && !DataType::INT32.is_quantized());
+        assert!(DataType::INT8.is_integer() && DataType::INT8.is_quantized());
+        assert!(DataType::FP8.is_float() && DataType::FP8.is_quantized());
+        assert!(DataType::BF16.is_float() && !DataType::BF16.is_quantized());
+        assert!(!DataType::BOOL.is_float() && !DataType::BOOL.is_integer());
+        assert!(DataType::INT64.is_integer() && !DataType::INT64.is_quantized());
+    }
+
+    #[test]
+    fn test_dtype_elem_size() {
+        assert_eq!(DataType::INT32.get_elem_size(), 4);
+        assert_eq!(DataType::INT64.get_elem_size(), 8);
+        assert_eq!(DataType::HALF.get_byte_size(6), 12);
+        assert_eq!(DataType::INT4.get_byte_size(6), 3);
+        assert_eq!(DataType::INT4.get_byte_size(7), 4);
+        assert_eq!(DataType::INT4.get_num_elems(4), 8);
+        assert_eq!(DataType::FLOAT.get_num_elems(10), 2);
+    }
+
+    #[test]
+    fn test_tensor_io_mode_predicates() {
+        assert!(TensorIOMode::INPUT.is_input());
+        assert!(!TensorIOMode::INPUT.is_output() && !TensorIOMode::INPUT.is_none());
+
+        assert!(TensorIOMode::OUTPUT.is_output());
+        assert!(!TensorIOMode::OUTPUT.is_input() && !TensorIOMode::OUTPUT.is_none());
+
+        assert!(TensorIOMode::NONE.is_none());
+        assert!(!TensorIOMode::NONE.is_input() && !TensorIOMode::NONE.is_output());
+    }
+
+    #[test]
+    fn test_tensor_io_mode_display() {
+        assert_eq!(TensorIOMode::NONE.to_string(), "NONE");
+        assert_eq!(TensorIOMode::INPUT.to_string(), "INPUT");
+        assert_eq!(TensorIOMode::OUTPUT.to_string(), "OUTPUT");
+    }
+
+    #[test]
+    fn test_tensor_format_display() {
+        assert_eq!(TensorFormat::LINEAR.to_string(), "LINEAR");
+        assert_eq!(TensorFormat::CHW32.to_string(), "CHW32");
+        assert_eq!(TensorFormat::DLAHWC4.to_string(), "DLA_HWC4");
+    }
+
     #[test]
     fn test_runtime() {
         use std::{io::Read, path::Path};
@@ -569,10 +1220,20 @@ mod tests {
             file.read_to_end(&mut data).unwrap();
 
             let mut engine = runtime.deserialize(data.as_slice()).unwrap();
-            let _context = engine.create_execution_context().unwrap();
+            assert!(engine.device_memory_size() > 0);
+            for profile_index in 0..engine.get_num_optimization_profiles() {
+                let size = engine.device_memory_size_for_profile(profile_index);
+                assert!(size > 0 && size <= engine.device_memory_size());
+            }
+            let mut context = engine.create_execution_context().unwrap();
 
             let num_io_tensors = engine.get_num_io_tensors();
 
+            let summary = engine.io_summary();
+            runtime.logger().log(Severity::Info, &summary);
+            // a header, a rule and one line per tensor between two outer rules
+            assert_eq!(summary.lines().count(), num_io_tensors as usize + 4);
+
             let msg = format!("num_io_tensors: {}", num_io_tensors);
             runtime.logger().log(Severity::Info, msg.as_str());
 
@@ -580,11 +1241,120 @@ mod tests {
                 let name = engine.get_io_tensor_name(i);
                 let mode = engine.get_tensor_io_mode(name);
                 let shape = engine.get_tensor_shape(name);
-                let msg = format!("name: {}, mode: {:?}, shape: {:?}", name, mode, shape);
+                assert_eq!(engine.try_get_tensor_dtype(name), Ok(engine.get_tensor_dtype(name)));
+                let msg = format!("name: {}, mode: {}, shape: {:?}", name, mode, shape);
+                runtime.logger().log(Severity::Info, msg.as_str());
+
+                let format = engine.get_tensor_format(name);
+                let desc = engine.get_tensor_format_desc(name);
+                let msg = format!("name: {}, format: {}, desc: {}", name, format, desc);
                 runtime.logger().log(Severity::Info, msg.as_str());
             }
+
+            let addresses: HashMap<&str, usize> = (0..num_io_tensors)
+                .map(|i| (engine.get_io_tensor_name(i), 0x1000 * (i as usize + 1)))
+                .collect();
+            context.set_all_tensor_addresses(&addresses).unwrap();
+            assert_eq!(context.get_all_tensor_addresses(&engine), addresses);
+
+            let plan = engine.serialize().unwrap();
+            let reloaded = runtime.deserialize(plan.as_bytes()).unwrap();
+            assert_eq!(reloaded.get_num_io_tensors(), num_io_tensors);
         } else {
             runtime.logger().log(Severity::Info, "Engine file not found! Skip test!");
         }
     }
+
+    #[test]
+    fn test_runtime_with_logger() {
+        let engine_path = std::path::Path::new("../tmp/pp-ocr-v4-det-fp16.engine");
+        if !engine_path.exists() {
+            println!("Engine file not found! Skip test!");
+            return;
+        }
+        let data = std::fs::read(engine_path).unwrap();
+
+        let mut logger = Logger::with_min_severity(Severity::Info);
+        logger.set_capture(true);
+        let mut runtime = Runtime::with_logger(logger).unwrap();
+        runtime.deserialize(data.as_slice()).unwrap();
+
+        let captured = runtime.logger().take_captured();
+        assert!(captured.iter().any(|msg| msg.contains("Loaded engine size")));
+    }
+
+    #[test]
+    fn test_last_loaded_engine_size() {
+        let mut runtime = Runtime::new().unwrap();
+        assert_eq!(runtime.last_loaded_engine_size(), None);
+
+        let engine_path = std::path::Path::new("../tmp/pp-ocr-v4-det-fp16.engine");
+        if !engine_path.exists() {
+            println!("Engine file not found! Skip test!");
+            return;
+        }
+        let data = std::fs::read(engine_path).unwrap();
+
+        let engine = runtime.deserialize(data.as_slice()).unwrap();
+        assert_eq!(
+            runtime.last_loaded_engine_size(),
+            Some(data.len() + engine.device_memory_size())
+        );
+    }
+
+    #[test]
+    fn test_as_raw_ptr() {
+        let engine_path = std::path::Path::new("../tmp/pp-ocr-v4-det-fp16.engine");
+        if !engine_path.exists() {
+            println!("Engine file not found! Skip test!");
+            return;
+        }
+        let data = std::fs::read(engine_path).unwrap();
+
+        let mut runtime = Runtime::new().unwrap();
+        let mut engine = runtime.deserialize(data.as_slice()).unwrap();
+        assert!(!unsafe { engine.as_raw_ptr() }.is_null());
+        let context = engine.create_execution_context().unwrap();
+        assert!(!unsafe { context.as_raw_ptr() }.is_null());
+    }
+
+    #[test]
+    fn test_is_engine_compatible() {
+        use crate::builder::Builder;
+
+        // a plan built on this device always targets its compute capability
+        let mut builder = Builder::new().unwrap();
+        let mut network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[1, 4]).unwrap();
+        let output = network.add_identity(input).unwrap();
+        network.mark_output(output);
+        let mut config = builder.create_builder_config().unwrap();
+        let plan = builder.build_serialized_network(&mut network, &mut config).unwrap();
+
+        let runtime = Runtime::new().unwrap();
+        assert!(runtime.is_engine_compatible(plan.as_bytes()));
+        assert!(!runtime.is_engine_compatible(&[0u8; 64]));
+    }
+
+    #[test]
+    fn test_context_outlives_engine() {
+        use crate::builder::Builder;
+
+        let mut builder = Builder::new().unwrap();
+        let mut network = builder.create_network().unwrap();
+        let input = network.add_input("x", DataType::FLOAT, &[1, 4]).unwrap();
+        let output = network.add_identity(input).unwrap();
+        network.set_tensor_name(output, "y");
+        network.mark_output(output);
+        let mut config = builder.create_builder_config().unwrap();
+        let plan = builder.build_serialized_network(&mut network, &mut config).unwrap();
+
+        let mut runtime = Runtime::new().unwrap();
+        let mut engine = runtime.deserialize(plan.as_bytes()).unwrap();
+        let context = engine.create_execution_context().unwrap();
+        drop(engine);
+
+        assert!(context.all_input_dimensions_specified());
+        assert_eq!(context.get_tensor_shape("y"), vec![1, 4]);
+    }
 }
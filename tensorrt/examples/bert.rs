@@ -0,0 +1,105 @@
+use clap::Parser;
+use cuda_rs::{device::CuDevice, stream::CuStream};
+use tensorrt::{DataType, Shape, TRTEngine, TRTError, TRTResult, Tensor};
+use std::{collections::HashMap, path::Path};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[arg(short, long)]
+    engine: String,
+
+    #[arg(short, long, default_value_t = 2)]
+    batch_size: i32,
+
+    #[arg(short, long, default_value_t = 128)]
+    seq_len: i32,
+}
+
+// BERT vocabulary ids
+const CLS: i64 = 101;
+const SEP: i64 = 102;
+const PAD: i64 = 0;
+
+/// Uploads `values` with the integer type the engine expects for `name`.
+fn int_tensor(
+    engine: &TRTEngine,
+    name: &str,
+    values: &[i64],
+    shape: &Shape,
+    stream: &CuStream,
+) -> TRTResult<Tensor> {
+    match engine.tensor_info(name)?.dtype {
+        DataType::INT64 => Tensor::from_host_slice(values, shape, stream),
+        DataType::INT32 => {
+            let values = values.iter().map(|&v| v as i32).collect::<Vec<_>>();
+            Tensor::from_host_slice(&values, shape, stream)
+        }
+        _ => Err(TRTError::DTypeMismatch),
+    }
+}
+
+fn main() -> TRTResult<()> {
+    let args = Args::parse();
+    let Args { engine: engine_path, batch_size, seq_len } = args;
+    let engine_path = Path::new(&engine_path);
+
+    // dummy sentences of increasing length: [CLS] tokens... [SEP] [PAD]...
+    let mut input_ids = vec![];
+    let mut attention_mask = vec![];
+    for b in 0..batch_size {
+        let len = (8 * (b + 1)).min(seq_len);
+        for i in 0..seq_len {
+            let (id, mask) = match i {
+                0 => (CLS, 1),
+                i if i == len - 1 => (SEP, 1),
+                i if i < len => (1000 + i as i64, 1),
+                _ => (PAD, 0),
+            };
+            input_ids.push(id);
+            attention_mask.push(mask);
+        }
+    }
+    let token_type_ids = vec![0i64; input_ids.len()];
+
+    cuda_rs::init()?;
+
+    let device = CuDevice::new(0)?;
+    let ctx = device.retain_primary_context()?;
+    let _guard = ctx.guard()?;
+    let stream = CuStream::new()?;
+
+    let mut engine = TRTEngine::new(&engine_path, &stream)?;
+
+    engine.activate()?;
+
+    // batch * seq_len; output shapes are inferred from the inputs
+    let input_shape = Shape(vec![batch_size, seq_len]);
+    let max_shape_dict = HashMap::from([
+        ("input_ids", &input_shape),
+        ("attention_mask", &input_shape),
+        ("token_type_ids", &input_shape),
+    ]);
+    engine.allocate_io_tensors(&max_shape_dict, None)?;
+
+    let input_ids = int_tensor(&engine, "input_ids", &input_ids, &input_shape, &stream)?;
+    let attention_mask = int_tensor(&engine, "attention_mask", &attention_mask, &input_shape, &stream)?;
+    let token_type_ids = int_tensor(&engine, "token_type_ids", &token_type_ids, &input_shape, &stream)?;
+
+    let feed_dict = HashMap::from([
+        ("input_ids", &input_ids),
+        ("attention_mask", &attention_mask),
+        ("token_type_ids", &token_type_ids),
+    ]);
+    let outputs = engine.inference(&feed_dict, None)?;
+
+    stream.synchronize()?;
+
+    for (name, tensor) in outputs.iter() {
+        println!("{}: {} {:?}", name, tensor.shape(), tensor.dtype());
+    }
+
+    println!("Done");
+
+    Ok(())
+}
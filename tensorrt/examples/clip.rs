@@ -1,6 +1,6 @@
 use clap::Parser;
 use cuda_rs::{device::CuDevice, stream::CuStream};
-use tensorrt::{TRTEngine, TRTResult, Shape, DataType, Tensor};
+use tensorrt::{OutputMap, TRTEngine, TRTResult, Shape, DataType, Tensor};
 use tch::vision::imagenet::load_image_and_resize224;
 use std::{collections::HashMap, path::Path};
 
@@ -54,24 +54,10 @@ fn main() -> TRTResult<()> {
     let feed_dict: HashMap<&str, &Tensor> = HashMap::from([
         ("images", &input_tensor),
     ]);
-    let res = engine.inference(&feed_dict, None)?;
-
-    stream.synchronize()?;
-
-    let features = res.get("features").unwrap();
-    let features_ptr = unsafe { features.get_raw_ptr() };
-
-    let device = tch::Device::cuda_if_available();
-    let features = unsafe {
-        tch::Tensor::from_blob(
-            features_ptr as _,
-            vec![1, 768].as_slice(),
-            vec![768, 1].as_slice(),
-            tch::Kind::Float,
-            device,
-        )
-    };
-    let features = features.to_device(tch::Device::Cpu);
+    let res = OutputMap::from(engine.inference(&feed_dict, None)?);
+
+    let features = res.get_as::<f32>("features")?;
+    let features = tch::Tensor::from_slice(&features);
 
     let mean = features.mean(tch::Kind::Float);
 
@@ -54,7 +54,7 @@ fn main() -> TRTResult<()> {
     ]);
     engine.inference(&feed_dict, None)?;
 
-    stream.synchronize()?;
+    engine.synchronize()?;
 
     // TODO: post-processing
 
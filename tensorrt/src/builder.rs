@@ -0,0 +1,253 @@
+use crate::{
+    engine::ProfileShapes,
+    error::{TRTError, TRTResult},
+};
+use tensorrt_rs_sys::{
+    builder::{Builder, BuilderConfig, BuilderFlag, DeviceType, MemoryPoolType},
+    runtime::{OptProfileSelector, ProfilingVerbosity},
+};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "onnx")]
+use tensorrt_rs_sys::{builder::TimingCache, logger::Severity, parser::Parser};
+
+/// Settings for building an engine; unset ones keep TensorRT's defaults.
+#[derive(Clone, Debug, Default)]
+pub struct BuildOptions {
+    flags: Vec<BuilderFlag>,
+    workspace_size: Option<usize>,
+    optimization_level: Option<u8>,
+    profiling_verbosity: Option<ProfilingVerbosity>,
+    default_device_type: Option<DeviceType>,
+    // shapes of the dynamic inputs, all in one optimization profile
+    profile: Vec<(String, ProfileShapes)>,
+    cache_path: Option<PathBuf>,
+    timing_cache_path: Option<PathBuf>,
+}
+
+impl BuildOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn flag(mut self, flag: BuilderFlag) -> Self {
+        if !self.flags.contains(&flag) {
+            self.flags.push(flag);
+        }
+        self
+    }
+
+    pub fn fp16(self) -> Self {
+        self.flag(BuilderFlag::FP16)
+    }
+
+    pub fn int8(self) -> Self {
+        self.flag(BuilderFlag::INT8)
+    }
+
+    /// Caps the scratch memory layers may use, see
+    /// `BuilderConfig::set_memory_pool_limit`.
+    pub fn workspace_size(mut self, size: usize) -> Self {
+        self.workspace_size = Some(size);
+        self
+    }
+
+    pub fn optimization_level(mut self, level: u8) -> Self {
+        self.optimization_level = Some(level);
+        self
+    }
+
+    pub fn profiling_verbosity(mut self, verbosity: ProfilingVerbosity) -> Self {
+        self.profiling_verbosity = Some(verbosity);
+        self
+    }
+
+    pub fn default_device_type(mut self, device_type: DeviceType) -> Self {
+        self.default_device_type = Some(device_type);
+        self
+    }
+
+    /// Sets the shapes dynamic input `name` accepts. All inputs set this way
+    /// share a single optimization profile.
+    pub fn profile(mut self, name: &str, shapes: ProfileShapes) -> Self {
+        self.profile.retain(|(input, _)| input != name);
+        self.profile.push((name.to_string(), shapes));
+        self
+    }
+
+    /// Where `TRTEngine::from_onnx` caches the built plan.
+    pub fn cache_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.cache_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn get_cache_path(&self) -> Option<&Path> {
+        self.cache_path.as_deref()
+    }
+
+    /// Where builds keep their timing cache, which makes rebuilding the same
+    /// model much faster even when the plan itself has to be rebuilt.
+    pub fn timing_cache_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.timing_cache_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Creates a config on `builder` with these options applied.
+    pub fn create_config(&self, builder: &mut Builder) -> TRTResult<BuilderConfig> {
+        let mut config = match builder.create_builder_config() {
+            Some(config) => config,
+            None => return Err(TRTError::BuilderConfigCreationError),
+        };
+
+        for &flag in self.flags.iter() {
+            config.set_flag(flag);
+        }
+        if let Some(size) = self.workspace_size {
+            config.set_memory_pool_limit(MemoryPoolType::WORKSPACE, size);
+        }
+        if let Some(level) = self.optimization_level {
+            config.set_optimization_level(level).map_err(TRTError::InvalidOptimizationLevel)?;
+        }
+        if let Some(verbosity) = self.profiling_verbosity {
+            config.set_profiling_verbosity(verbosity);
+        }
+        if let Some(device_type) = self.default_device_type {
+            config.set_default_device_type(device_type);
+        }
+
+        if !self.profile.is_empty() {
+            let mut profile = match builder.create_optimization_profile() {
+                Some(profile) => profile,
+                None => return Err(TRTError::BuilderConfigCreationError),
+            };
+            for (name, shapes) in self.profile.iter() {
+                let selectors = [
+                    (OptProfileSelector::MIN, &shapes.min),
+                    (OptProfileSelector::OPT, &shapes.opt),
+                    (OptProfileSelector::MAX, &shapes.max),
+                ];
+                for (select, shape) in selectors {
+                    if !profile.set_dimensions(name, select, &shape.0) {
+                        return Err(TRTError::ProfileValidationError {
+                            profile_index: 0,
+                            tensor_name: name.clone(),
+                            reason: format!("invalid {:?} shape {}", select, shape),
+                        });
+                    }
+                }
+            }
+            if config.add_optimization_profile(profile).is_none() {
+                return Err(TRTError::ProfileValidationError {
+                    profile_index: 0,
+                    tensor_name: String::new(),
+                    reason: "the profile does not cover every dynamic input".to_string(),
+                });
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Parses the ONNX model at `onnx_path` and builds it into a serialized plan.
+#[cfg(feature = "onnx")]
+pub(crate) fn build_onnx(onnx_path: &Path, options: &BuildOptions) -> TRTResult<Vec<u8>> {
+    let mut builder = match Builder::new() {
+        Some(builder) => builder,
+        None => return Err(TRTError::BuilderCreationError),
+    };
+    let network = match builder.create_network() {
+        Some(network) => network,
+        None => return Err(TRTError::BuilderCreationError),
+    };
+    let mut parser = match Parser::new(network) {
+        Some(parser) => parser,
+        None => return Err(TRTError::BuilderCreationError),
+    };
+
+    let path = match onnx_path.to_str() {
+        Some(path) => path,
+        None => {
+            let reason = format!("non UTF-8 path {:?}", onnx_path);
+            return Err(TRTError::OnnxParseError(vec![reason]));
+        }
+    };
+    if !parser.parse_from_file(path, Severity::Warning) {
+        return Err(TRTError::OnnxParseError(parser.errors()));
+    }
+
+    let mut config = options.create_config(&mut builder)?;
+    let timing_cache = match options.timing_cache_path.as_ref() {
+        Some(path) => {
+            let cache = match TimingCache::load_from_file(&config, path)? {
+                Some(cache) if config.set_timing_cache(&cache, false) => cache,
+                // an invalid or mismatched cache is replaced by a fresh one
+                _ => {
+                    let cache = match config.create_timing_cache(&[]) {
+                        Some(cache) => cache,
+                        None => return Err(TRTError::BuilderConfigCreationError),
+                    };
+                    config.set_timing_cache(&cache, false);
+                    cache
+                }
+            };
+            Some((cache, path))
+        }
+        None => None,
+    };
+    let plan = match builder.build_serialized_network(parser.network(), &mut config) {
+        Some(plan) => plan,
+        None => return Err(TRTError::EngineBuildError),
+    };
+    if let Some((cache, path)) = timing_cache {
+        cache.save_to_file(path)?;
+    }
+
+    Ok(plan.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor::Shape;
+
+    #[test]
+    fn test_create_config() -> TRTResult<()> {
+        let mut builder = Builder::new().unwrap();
+
+        let options = BuildOptions::new()
+            .fp16()
+            .fp16()
+            .workspace_size(1 << 20)
+            .optimization_level(1)
+            .profiling_verbosity(ProfilingVerbosity::DETAILED);
+        assert_eq!(options.flags, vec![BuilderFlag::FP16]);
+
+        let config = options.create_config(&mut builder)?;
+        assert!(config.get_flag(BuilderFlag::FP16));
+        assert!(!config.get_flag(BuilderFlag::INT8));
+        assert_eq!(config.get_memory_pool_limit(MemoryPoolType::WORKSPACE), 1 << 20);
+        assert_eq!(config.get_optimization_level(), 1);
+        assert_eq!(config.get_profiling_verbosity(), ProfilingVerbosity::DETAILED);
+        assert_eq!(config.get_default_device_type(), DeviceType::GPU);
+
+        let shapes = ProfileShapes {
+            min: Shape(vec![4, 1]),
+            opt: Shape(vec![2, 4]),
+            max: Shape(vec![8, 4]),
+        };
+        let invalid = BuildOptions::new().profile("x", shapes);
+        match invalid.create_config(&mut builder) {
+            Err(TRTError::ProfileValidationError { tensor_name, .. }) => assert_eq!(tensor_name, "x"),
+            res => panic!("unexpected result: {:?}", res.map(|_| ())),
+        }
+
+        let level = BuilderConfig::MAX_OPTIMIZATION_LEVEL + 1;
+        let invalid = BuildOptions::new().optimization_level(level);
+        assert!(matches!(
+            invalid.create_config(&mut builder),
+            Err(TRTError::InvalidOptimizationLevel(6))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use crate::{
+    error::{TRTError, TRTResult},
+    tensor::{Shape, Tensor},
+};
+use cuda_rs::{context::CuContext, stream::CuStream};
+use image::imageops::FilterType;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tensorrt_rs_sys::calibrator::Int8Calibrator;
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["bmp", "jpeg", "jpg", "png", "webp"];
+
+/// Feeds the images in a directory to an INT8 build as calibration batches.
+///
+/// Each image is resized to the input's height and width, scaled to [0, 1],
+/// normalized per RGB channel and laid out as NCHW floats. The images are
+/// taken in file name order; a last batch that is not full is skipped.
+///
+/// An image that fails to load or upload ends the calibration, and with it
+/// the build; `errors` tells why.
+///
+/// The CUDA context current at `new` is made current again whenever the
+/// calibrator uses the GPU, as the build may run it on another thread.
+pub struct ImageDirCalibrator {
+    paths: Vec<PathBuf>,
+    next: usize,
+    shape: Shape,
+    mean: [f32; 3],
+    std: [f32; 3],
+    cu_context: CuContext,
+    stream: CuStream,
+    // the batch TensorRT reads, kept until the next one is requested
+    batch: Option<Tensor>,
+    errors: CalibrationErrors,
+}
+
+/// The errors that made an `ImageDirCalibrator` fail, still readable once
+/// the calibrator was handed to the builder. Clones share the errors.
+#[derive(Clone, Default)]
+pub struct CalibrationErrors(Arc<Mutex<Vec<String>>>);
+
+impl CalibrationErrors {
+    /// Returns the errors in order and clears them.
+    pub fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+
+    fn push(&self, error: String) {
+        self.0.lock().unwrap().push(error);
+    }
+}
+
+// SAFETY: the context, stream and batch are driver handles, which any thread
+// may use while their context is current; `get_batch` and `drop` push
+// `cu_context` before touching the others.
+unsafe impl Send for ImageDirCalibrator {}
+
+impl ImageDirCalibrator {
+    /// `shape` is the shape of the network's only input, `[N, 3, H, W]`.
+    pub fn new<P: AsRef<Path>>(dir: P, shape: &Shape, stream: &CuStream) -> TRTResult<Self> {
+        if shape.0.len() != 4 || shape.0[1] != 3 || shape.0.iter().any(|&dim| dim <= 0) {
+            return Err(TRTError::ShapeMismatch);
+        }
+
+        let mut paths = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let is_image = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+            if is_image {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        Ok(Self {
+            paths,
+            next: 0,
+            shape: shape.clone(),
+            mean: [0.0; 3],
+            std: [1.0; 3],
+            cu_context: CuContext::current()?,
+            stream: stream.clone(),
+            batch: None,
+            errors: CalibrationErrors::default(),
+        })
+    }
+
+    /// Normalizes the scaled pixels to `(pixel - mean) / std` per channel.
+    pub fn normalize(mut self, mean: [f32; 3], std: [f32; 3]) -> Self {
+        self.mean = mean;
+        self.std = std;
+        self
+    }
+
+    /// A handle to the errors that make `get_batch` fail; keep it to find out
+    /// why a calibrated build failed.
+    pub fn errors(&self) -> CalibrationErrors {
+        self.errors.clone()
+    }
+
+    /// The number of batches the images make up.
+    pub fn num_batches(&self) -> usize {
+        self.paths.len() / self.shape.0[0] as usize
+    }
+
+    fn load_batch(&self, paths: &[PathBuf]) -> Option<Vec<f32>> {
+        let (height, width) = (self.shape.0[2] as u32, self.shape.0[3] as u32);
+        let plane = (height * width) as usize;
+
+        let mut data = vec![0.0f32; paths.len() * 3 * plane];
+        for (image_data, path) in data.chunks_exact_mut(3 * plane).zip(paths) {
+            let image = match image::open(path) {
+                Ok(image) => image.to_rgb8(),
+                Err(err) => {
+                    self.errors.push(format!("{}: {}", path.display(), err));
+                    return None;
+                }
+            };
+            let image = image::imageops::resize(&image, width, height, FilterType::Triangle);
+            for (i, pixel) in image.pixels().enumerate() {
+                for c in 0..3 {
+                    let value = pixel[c] as f32 / 255.0;
+                    image_data[c * plane + i] = (value - self.mean[c]) / self.std[c];
+                }
+            }
+        }
+
+        Some(data)
+    }
+
+    // Uploads `data` and waits for it, as TensorRT reads the batch without
+    // synchronizing on the stream.
+    fn upload_batch(&self, data: &[f32]) -> TRTResult<Tensor> {
+        let batch = Tensor::from_host_slice(data, &self.shape, &self.stream)?;
+        self.stream.synchronize()?;
+
+        Ok(batch)
+    }
+}
+
+// SAFETY: the binding points at `batch`, a synchronized upload in the input's
+// shape that is kept until the next call
+unsafe impl Int8Calibrator for ImageDirCalibrator {
+    fn get_batch(&mut self, _names: &[String], bindings: &mut [usize]) -> bool {
+        if bindings.len() != 1 {
+            self.errors.push(format!("expected 1 input, the network has {}", bindings.len()));
+            return false;
+        }
+        let batch_size = self.shape.0[0] as usize;
+        if self.next + batch_size > self.paths.len() {
+            return false;
+        }
+        let _guard = match self.cu_context.clone().guard() {
+            Ok(guard) => guard,
+            Err(err) => {
+                self.errors.push(format!("making the CUDA context current: {}", err));
+                return false;
+            }
+        };
+
+        let paths = &self.paths[self.next..self.next + batch_size];
+        let data = match self.load_batch(paths) {
+            Some(data) => data,
+            None => return false,
+        };
+        match self.upload_batch(&data) {
+            Ok(batch) => {
+                bindings[0] = unsafe { batch.get_raw_ptr() };
+                self.batch = Some(batch);
+                self.next += batch_size;
+                true
+            }
+            Err(err) => {
+                self.errors.push(format!("uploading batch {}: {}", self.next / batch_size, err));
+                false
+            }
+        }
+    }
+}
+
+impl Drop for ImageDirCalibrator {
+    fn drop(&mut self) {
+        // the batch is freed on its stream, which needs the context
+        if let Ok(_guard) = self.cu_context.clone().guard() {
+            self.batch = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cuda_rs::device::CuDevice;
+
+    #[test]
+    fn test_image_dir_calibrator() -> TRTResult<()> {
+        cuda_rs::init()?;
+        let device = CuDevice::new(0)?;
+        let ctx = device.retain_primary_context()?;
+        let _guard = ctx.guard()?;
+        let stream = CuStream::new()?;
+
+        let dir = std::env::temp_dir().join("tensorrt-rs-calibration-images");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        for (i, color) in [[255u8, 0, 0], [0, 255, 0], [0, 0, 255]].into_iter().enumerate() {
+            let image = image::RgbImage::from_pixel(8, 6, image::Rgb(color));
+            image.save(dir.join(format!("{}.png", i)))?;
+        }
+        fs::write(dir.join("labels.txt"), "not an image")?;
+
+        let shape = Shape(vec![2, 3, 4, 4]);
+        let mut calibrator = ImageDirCalibrator::new(&dir, &shape, &stream)?
+            .normalize([0.5; 3], [0.5; 3]);
+        assert_eq!(calibrator.num_batches(), 1);
+
+        let names = vec!["x".to_string()];
+        let mut bindings = [0];
+        assert!(calibrator.get_batch(&names, &mut bindings));
+        assert_ne!(bindings[0], 0);
+        let batch = calibrator.batch.as_ref().unwrap().to_host_vec::<f32>(None)?;
+        // a red image, then a green one, normalized to [-1, 1]
+        assert_eq!(batch[0], 1.0);
+        assert_eq!(batch[16], -1.0);
+        assert_eq!(batch[48 + 16], 1.0);
+        // the third image does not make up a full batch
+        assert!(!calibrator.get_batch(&names, &mut bindings));
+        let errors = calibrator.errors();
+        assert!(errors.take().is_empty());
+
+        // an image that cannot be decoded fails the batch, and says why
+        fs::write(dir.join("3.png"), "not a png")?;
+        let mut calibrator = ImageDirCalibrator::new(&dir, &shape, &stream)?;
+        let errors = calibrator.errors();
+        assert!(calibrator.get_batch(&names, &mut bindings));
+        assert!(!calibrator.get_batch(&names, &mut bindings));
+        let reported = errors.take();
+        assert_eq!(reported.len(), 1);
+        assert!(reported[0].contains("3.png"));
+        assert!(errors.take().is_empty());
+
+        // the build may call the calibrator on a thread without a context
+        let mut calibrator = ImageDirCalibrator::new(&dir, &shape, &stream)?;
+        let errors = calibrator.errors();
+        let loaded = std::thread::spawn(move || {
+            let mut bindings = [0];
+            calibrator.get_batch(&["x".to_string()], &mut bindings)
+        });
+        assert!(loaded.join().unwrap());
+        assert!(errors.take().is_empty());
+
+        let mut calibrator = ImageDirCalibrator::new(&dir, &shape, &stream)?;
+        let errors = calibrator.errors();
+        assert!(!calibrator.get_batch(&names, &mut [0, 0]));
+        assert_eq!(errors.take().len(), 1);
+
+        assert!(matches!(
+            ImageDirCalibrator::new(&dir, &Shape(vec![1, 1, 4, 4]), &stream),
+            Err(TRTError::ShapeMismatch)
+        ));
+
+        fs::remove_dir_all(&dir)?;
+
+        Ok(())
+    }
+}
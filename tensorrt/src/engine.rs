@@ -1,65 +1,628 @@
+#[cfg(feature = "onnx")]
+use crate::builder::{build_onnx, BuildOptions};
 use crate::{
     error::{TRTError, TRTResult},
-    tensor::{Shape, Tensor},
+    runtime::{deserialize_standard, SharedRuntime},
+    tensor::{MemoryLocation, OutputMap, Shape, Tensor},
+};
+use cuda_rs::{
+    context::CuContext, error::CuError, event::CuEvent, memory::DeviceMemory, stream::CuStream,
 };
-use cuda_rs::stream::CuStream;
 use tensorrt_rs_sys::{
-    runtime::{Runtime, CudaEngine, ExecutionContext},
-    logger::Severity,
+    device::{self, OwnedStream},
+    runtime::{
+        Runtime, CudaEngine, CudaGraph, DataType, ExecutionContext, HardwareCompatibilityLevel,
+        OptProfileSelector, SerializationFlag, TensorFormat, TensorIOMode,
+    },
+    logger::{Logger, Severity},
+    plugin,
+    profiler::Profiler,
+    recorder::ErrorRecorder,
+    refitter::{Refitter, TensorLocation},
 };
-use std::{collections::HashMap, fs, path::Path};
+use std::{collections::HashMap, fs, path::Path, sync::OnceLock};
+
+/// A borrow of an engine's default stream. Unlike a `CuStream` it can't be
+/// cloned, since an engine built with `build_with_own_stream` destroys the
+/// stream when it drops.
+pub struct EngineStream<'a>(&'a CuStream);
+
+impl<'a> EngineStream<'a> {
+    pub fn synchronize(&self) -> TRTResult<()> {
+        Ok(self.0.synchronize()?)
+    }
+
+    /// Whether all work on the stream has completed.
+    pub fn query(&self) -> TRTResult<bool> {
+        Ok(self.0.query()?)
+    }
+
+    pub fn wait_on_event(&self, event: &CuEvent) -> TRTResult<()> {
+        Ok(self.0.wait_on_event(event)?)
+    }
+
+    /// The stream itself, e.g. to allocate tensors on it.
+    ///
+    /// # Safety
+    ///
+    /// Neither the stream nor anything holding a clone of it, like a tensor
+    /// allocated on it, may outlive the engine.
+    pub unsafe fn as_cu_stream(&self) -> &'a CuStream {
+        self.0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfileShapes {
+    pub min: Shape,
+    pub opt: Shape,
+    pub max: Shape,
+}
+
+/// How many aux streams the engine can run layers on in parallel, and how
+/// many were handed to its execution context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuxStreamInfo {
+    pub expected: i32,
+    pub configured: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TensorInfo {
+    pub name: String,
+    pub io_mode: TensorIOMode,
+    pub dtype: DataType,
+    pub shape: Shape,
+    pub format: TensorFormat,
+    // Index of the vectorized dimension, or -1 for scalar formats.
+    pub vectorized_dim: i32,
+}
+
+/// An input or output an `IoSpec` expects; -1 in `shape` accepts any extent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TensorSpec {
+    pub name: String,
+    pub dtype: DataType,
+    pub shape: Shape,
+}
+
+/// The IO tensors a model config expects an engine to have, checked with
+/// `TRTEngine::validate_against_spec`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IoSpec {
+    pub inputs: Vec<TensorSpec>,
+    pub outputs: Vec<TensorSpec>,
+}
+
+/// Steady-state latencies measured by `TRTEngine::benchmark`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BenchStats {
+    pub mean_ms: f32,
+    pub p50_ms: f32,
+    pub p99_ms: f32,
+    // inferences per second
+    pub throughput: f32,
+}
 
 pub struct TRTEngine {
     runtime: Option<Runtime>,
+    shared_runtime: Option<SharedRuntime>,
     engine: Option<CudaEngine>,
+    // `CudaEngine::plan_hash` of the plan the engine was deserialized from
+    plan_hash: u64,
     context: Option<ExecutionContext>,
     stream: CuStream,
-    tensors: HashMap<String, Tensor>,
+    cu_context: CuContext,
+    inputs: HashMap<String, Tensor>,
+    outputs: HashMap<String, Tensor>,
+    // IO tensors of the inactive profiles, keyed by profile index
+    parked_tensors: HashMap<i32, IOTensors>,
+    lazy_alloc: bool,
+    graph: Option<GraphState>,
+    // the stream of the last profile switch and an event recorded after it
+    profile_switch: Option<(CuStream, CuEvent)>,
+    // the stream the IO tensors were allocated on, until a mismatch is reported
+    alloc_stream: Option<CuStream>,
+    // set if the engine creates its aux streams itself
+    aux_stream_config: Option<StreamConfig>,
+    num_configured_aux_streams: usize,
+    // clones of the streams handed to `set_aux_streams`, kept while the
+    // context may use them
+    given_aux_streams: Vec<CuStream>,
+    // streams the engine created; declared last so they outlive everything above
+    aux_streams: Vec<OwnedStream>,
+    owned_stream: Option<OwnedStream>,
+    error_recorder: Option<ErrorRecorder>,
+    // forwards to `error_recorder`; the engine's own, so clearing it before a
+    // call leaves the errors of other users of `error_recorder` alone
+    engine_recorder: Option<ErrorRecorder>,
 }
 
-impl TRTEngine {
-    pub fn new<P: AsRef<Path>>(engine_path: &P, stream: &CuStream) -> TRTResult<Self> {
-        let mut runtime = match Runtime::new() {
+#[derive(Clone, Copy)]
+struct StreamConfig {
+    non_blocking: bool,
+    priority: i32,
+}
+
+impl StreamConfig {
+    fn create(&self) -> TRTResult<OwnedStream> {
+        match OwnedStream::with_priority(self.non_blocking, self.priority) {
+            Some(stream) => Ok(stream),
+            None => Err(TRTError::StreamCreationError),
+        }
+    }
+}
+
+struct IOTensors {
+    inputs: HashMap<String, Tensor>,
+    outputs: HashMap<String, Tensor>,
+}
+
+struct GraphState {
+    graph: CudaGraph,
+    // tensors replaced by `bind_tensor` that the graph still points at
+    retired: Vec<Tensor>,
+}
+
+/// Configures how a `TRTEngine` is created.
+#[derive(Default)]
+pub struct TRTEngineBuilder {
+    logger: Option<Logger>,
+    non_blocking: bool,
+    stream_priority: Option<i32>,
+    error_recorder: Option<ErrorRecorder>,
+}
+
+impl TRTEngineBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Routes the runtime's messages, including those emitted while the
+    /// engine is deserialized (version mismatches, missing plugins), through
+    /// `logger`.
+    pub fn logger(mut self, logger: Logger) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    /// Creates the streams the engine owns as non-blocking, so they don't
+    /// synchronize with the legacy default stream.
+    pub fn non_blocking_streams(mut self, non_blocking: bool) -> Self {
+        self.non_blocking = non_blocking;
+        self
+    }
+
+    /// Priority of the streams the engine creates itself: its aux streams,
+    /// and its main stream with `build_with_own_stream`. Lower numbers mean
+    /// higher priority; `device::get_stream_priority_range` gives the range
+    /// (e.g. 0 down to -5), and values outside it are clamped.
+    pub fn stream_priority(mut self, priority: i32) -> Self {
+        self.stream_priority = Some(priority);
+        self
+    }
+
+    /// Reports the errors of the runtime, the engine and its contexts to
+    /// `recorder` instead of the logger. Calls that fail after TensorRT
+    /// recorded why return `TRTError::Recorded` with those errors. The
+    /// recorder may be shared with other engines: each one tells its own
+    /// errors apart through a recorder that forwards to it.
+    pub fn error_recorder(mut self, recorder: ErrorRecorder) -> Self {
+        self.error_recorder = Some(recorder);
+        self
+    }
+
+    /// Like `build`, but the engine creates and owns its stream.
+    pub fn build_with_own_stream<P: AsRef<Path>>(self, engine_path: &P) -> TRTResult<TRTEngine> {
+        let stream = self.stream_config().create()?;
+
+        // SAFETY: the engine owns the stream, which it destroys after its
+        // last use of the handle
+        let mut engine = self.build(engine_path, &unsafe { stream.as_cu_stream() })?;
+        engine.owned_stream = Some(stream);
+
+        Ok(engine)
+    }
+
+    fn stream_config(&self) -> StreamConfig {
+        StreamConfig {
+            non_blocking: self.non_blocking,
+            priority: self.stream_priority.unwrap_or(0),
+        }
+    }
+
+    pub fn build<P: AsRef<Path>>(self, engine_path: &P, stream: &CuStream) -> TRTResult<TRTEngine> {
+        let data = fs::read(engine_path)?;
+
+        self.build_from_bytes(&data, stream)
+    }
+
+    /// Like `build`, but deserializes a plan that is already in memory.
+    pub fn build_from_bytes(self, data: &[u8], stream: &CuStream) -> TRTResult<TRTEngine> {
+        // TensorRT creates blocking, default priority aux streams by itself
+        let aux_stream_config = match self.non_blocking || self.stream_priority.is_some() {
+            true => Some(self.stream_config()),
+            false => None,
+        };
+        let runtime = match self.logger {
+            Some(logger) => Runtime::with_logger(logger),
+            None => Runtime::new(),
+        };
+        let mut runtime = match runtime {
             Some(runtime) => runtime,
             None => return Err(TRTError::RuntimeCreationError),
         };
 
-        let data = fs::read(engine_path)?;
+        let engine_recorder = self.error_recorder.as_ref().map(ErrorRecorder::forwarding);
+        if let Some(recorder) = &engine_recorder {
+            runtime.set_error_recorder(recorder);
+        }
+        let engine = deserialize_standard(&mut runtime, data, engine_recorder.as_ref())?;
 
-        let engine = match runtime.deserialize(data.as_slice()) {
-            Some(engine) => engine,
-            None => return Err(TRTError::EngineDeserializationError),
+        Ok(TRTEngine {
+            runtime: Some(runtime),
+            shared_runtime: None,
+            engine: Some(engine),
+            plan_hash: CudaEngine::plan_hash(data),
+            context: None,
+            stream: stream.clone(),
+            cu_context: CuContext::current()?,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parked_tensors: HashMap::new(),
+            lazy_alloc: false,
+            graph: None,
+            profile_switch: None,
+            alloc_stream: None,
+            aux_stream_config,
+            num_configured_aux_streams: 0,
+            given_aux_streams: vec![],
+            aux_streams: vec![],
+            owned_stream: None,
+            error_recorder: self.error_recorder,
+            engine_recorder,
+        })
+    }
+}
+
+impl TRTEngine {
+    pub fn new<P: AsRef<Path>>(engine_path: &P, stream: &CuStream) -> TRTResult<Self> {
+        TRTEngineBuilder::new().build(engine_path, stream)
+    }
+
+    pub fn builder() -> TRTEngineBuilder {
+        TRTEngineBuilder::new()
+    }
+
+    /// Like `new`, but the engine creates and owns its stream. With
+    /// `non_blocking`, that stream and the aux streams TensorRT runs parallel
+    /// layers on don't synchronize with the legacy default stream, so work
+    /// queued there can overlap with inference.
+    pub fn new_with_stream_flags<P: AsRef<Path>>(
+        engine_path: &P,
+        non_blocking: bool,
+    ) -> TRTResult<Self> {
+        TRTEngineBuilder::new()
+            .non_blocking_streams(non_blocking)
+            .build_with_own_stream(engine_path)
+    }
+
+    /// Like `new`, but first registers TensorRT's standard plugins, which
+    /// engines using e.g. `EfficientNMS_TRT` need to deserialize.
+    pub fn new_with_standard_plugins<P: AsRef<Path>>(
+        engine_path: &P,
+        stream: &CuStream,
+    ) -> TRTResult<Self> {
+        init_standard_plugins()?;
+
+        Self::new(engine_path, stream)
+    }
+
+    /// Builds an engine from the ONNX model at `onnx_path` with `options`.
+    ///
+    /// With `BuildOptions::cache_path`, a plan already at that path is loaded
+    /// instead, and a freshly built one is written there. The cached plan is
+    /// not checked against the model or the options, so remove it when
+    /// either changes.
+    #[cfg(feature = "onnx")]
+    pub fn from_onnx<P: AsRef<Path>>(
+        onnx_path: &P,
+        options: BuildOptions,
+        stream: &CuStream,
+    ) -> TRTResult<Self> {
+        let cache_path = options.get_cache_path();
+        let plan = match cache_path.filter(|path| path.exists()) {
+            Some(path) => fs::read(path)?,
+            None => {
+                let plan = build_onnx(onnx_path.as_ref(), &options)?;
+                if let Some(path) = cache_path {
+                    write_plan(path, &plan)?;
+                }
+                plan
+            }
         };
 
+        TRTEngineBuilder::new().build_from_bytes(&plan, stream)
+    }
+
+    /// Deserializes `data` through a runtime shared with other engines.
+    pub fn from_bytes_with_runtime(
+        data: &[u8],
+        runtime: &SharedRuntime,
+        stream: &CuStream,
+    ) -> TRTResult<Self> {
+        let engine = runtime.deserialize(data)?;
+
         Ok(Self {
-            runtime: Some(runtime),
+            runtime: None,
+            shared_runtime: Some(runtime.clone()),
             engine: Some(engine),
+            plan_hash: CudaEngine::plan_hash(data),
             context: None,
             stream: stream.clone(),
-            tensors: HashMap::new(),
+            cu_context: CuContext::current()?,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parked_tensors: HashMap::new(),
+            lazy_alloc: false,
+            graph: None,
+            profile_switch: None,
+            alloc_stream: None,
+            aux_stream_config: None,
+            num_configured_aux_streams: 0,
+            given_aux_streams: vec![],
+            aux_streams: vec![],
+            owned_stream: None,
+            error_recorder: None,
+            engine_recorder: None,
         })
     }
 
+    /// Hash of the plan the engine was loaded from, to compare against the
+    /// `CudaEngine::plan_hash` of an expected artifact.
+    pub fn plan_hash(&self) -> u64 {
+        self.plan_hash
+    }
+
+    /// The engine's plan, e.g. to store an engine built at runtime somewhere
+    /// other than a file.
+    pub fn serialize(&self) -> TRTResult<Vec<u8>> {
+        let engine = self.engine()?;
+        match engine.serialize() {
+            Some(plan) => Ok(plan.as_bytes().to_vec()),
+            None => Err(TRTError::EngineSerializationError),
+        }
+    }
+
+    /// Like `serialize`, but leaves out the parts named by `flags`, e.g. the
+    /// weights of a refittable engine that is refitted after loading. Fails
+    /// before TensorRT 10.
+    pub fn serialize_with_flags(&self, flags: &[SerializationFlag]) -> TRTResult<Vec<u8>> {
+        let engine = self.engine()?;
+        match engine.serialize_with_flags(flags) {
+            Some(plan) => Ok(plan.as_bytes().to_vec()),
+            None => Err(TRTError::EngineSerializationError),
+        }
+    }
+
+    /// Refits named weights of a refittable engine from tensors, e.g. weights
+    /// already on the GPU, without a round trip through host memory. The
+    /// refit runs on the engine's stream, after the inferences enqueued
+    /// before it. Needs TensorRT 10.
+    pub fn refit_from_tensors(&mut self, weights: &[(&str, &Tensor)]) -> TRTResult<()> {
+        let engine = self.engine.as_mut().ok_or(TRTError::EngineCreationError)?;
+        let mut refitter = match Refitter::new(engine) {
+            Some(refitter) => refitter,
+            None => return Err(TRTError::RefitterCreationError),
+        };
+
+        let mut reasons = vec![];
+        for &(name, tensor) in weights.iter() {
+            let location = match tensor.location() {
+                MemoryLocation::Device => TensorLocation::DEVICE,
+                MemoryLocation::HostPinned => TensorLocation::HOST,
+            };
+            // the tensors are borrowed until the stream is synchronized below
+            let set = unsafe {
+                refitter.set_named_weights_from_ptr(
+                    name,
+                    tensor.get_raw_ptr(),
+                    tensor.numel(),
+                    tensor.dtype(),
+                    location,
+                )
+            };
+            if !set {
+                reasons.push(format!("weights '{}' rejected", name));
+            }
+        }
+        reasons.extend(
+            refitter.get_missing_weights().iter().map(|name| format!("weights '{}' missing", name)),
+        );
+        if !reasons.is_empty() {
+            return Err(TRTError::RefitError(reasons));
+        }
+
+        // SAFETY: synchronized right away, before the tensors are released
+        let refitted = unsafe { refitter.refit_cuda_engine_async(&self.stream) };
+        self.stream.synchronize()?;
+        if refitted {
+            Ok(())
+        } else {
+            Err(TRTError::RefitError(vec!["refitCudaEngineAsync failed".to_string()]))
+        }
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: &P) -> TRTResult<()> {
+        let engine = self.engine()?;
+        let plan = match engine.serialize() {
+            Some(plan) => plan,
+            None => return Err(TRTError::EngineSerializationError),
+        };
+
+        write_plan(path.as_ref(), plan.as_bytes())
+    }
+
+    pub fn reload_engine<P: AsRef<Path>>(&mut self, new_path: &P) -> TRTResult<()> {
+        let data = fs::read(new_path)?;
+
+        self.reload_from_bytes(data.as_slice())
+    }
+
+    /// Replaces the engine, dropping the current context and IO tensors. The
+    /// engine is re-activated if it was active before; IO tensors need to be
+    /// allocated again. If `bytes` cannot be deserialized, the current engine
+    /// is left untouched.
+    pub fn reload_from_bytes(&mut self, bytes: &[u8]) -> TRTResult<()> {
+        let recorder = self.engine_recorder.as_ref();
+        let engine = match (self.runtime.as_mut(), self.shared_runtime.as_ref()) {
+            (Some(runtime), _) => deserialize_standard(runtime, bytes, recorder)?,
+            (None, Some(runtime)) => runtime.deserialize(bytes)?,
+            (None, None) => return Err(TRTError::RuntimeCreationError),
+        };
+        let was_active = self.context.is_some();
+
+        self.graph = None;
+        self.context = None;
+        self.inputs.clear();
+        self.outputs.clear();
+        self.parked_tensors.clear();
+        self.alloc_stream = None;
+        self.engine = Some(engine);
+        self.plan_hash = CudaEngine::plan_hash(bytes);
+
+        if was_active {
+            self.activate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the engine can run on `device_index`. Call it after `new`
+    /// and before `activate`.
+    ///
+    /// An engine deserialized successfully matches the device of the current
+    /// context, so without hardware compatibility the target device must share
+    /// its compute capability; `AMPEREPLUS` engines need compute capability 8.0+.
+    pub fn validate_engine_compatibility(&self, device_index: u32) -> TRTResult<()> {
+        let engine = self.engine()?;
+        let engine_compat = engine.get_hardware_compatibility_level();
+
+        let target = match device::get_compute_capability(device_index as _) {
+            Some(cc) => cc,
+            None => return Err(TRTError::CudaError(CuError::InvalidDevice)),
+        };
+        let compatible = match engine_compat {
+            HardwareCompatibilityLevel::NONE => {
+                let current = device::get_current_device()
+                    .and_then(device::get_compute_capability);
+                match current {
+                    Some(cc) => cc == target,
+                    None => return Err(TRTError::CudaError(CuError::InvalidContext)),
+                }
+            }
+            HardwareCompatibilityLevel::AMPEREPLUS => target.0 >= 8,
+        };
+
+        if compatible {
+            Ok(())
+        } else {
+            Err(TRTError::IncompatibleHardware { engine_compat, device: device_index })
+        }
+    }
+
     // TODO: reuse device memory
     pub fn activate(&mut self) -> TRTResult<()> {
+        self.create_context(true)
+    }
+
+    /// Like `activate`, but the context gets no scratch memory of its own;
+    /// assign at least `required_workspace_size` bytes with `set_device_memory`
+    /// before running inference, e.g. one buffer shared by several engines
+    /// that never run at the same time.
+    pub fn activate_without_device_memory(&mut self) -> TRTResult<()> {
+        self.create_context(false)
+    }
+
+    /// Hands the context `size` bytes of scratch memory at device address
+    /// `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// TensorRT writes to `ptr` without any checks: it has to point at `size`
+    /// bytes of device memory that stay allocated, and unused by anything
+    /// else while the context runs, until other memory is set or the engine
+    /// is dropped.
+    pub unsafe fn set_device_memory(&mut self, ptr: usize, size: usize) -> TRTResult<()> {
+        let required = self.required_workspace_size()?;
+        if size < required {
+            return Err(TRTError::InsufficientDeviceMemory(required, size));
+        }
+        if ptr == 0 {
+            return Err(TRTError::InvalidAddress);
+        }
+        let context: &mut ExecutionContext = match self.context.as_mut() {
+            Some(context) => context,
+            None => return Err(TRTError::ExecutionContextNotInitialized),
+        };
+
+        self.graph = None;
+        context.set_device_memory_v2(ptr, size);
+
+        Ok(())
+    }
+
+    fn create_context(&mut self, with_device_memory: bool) -> TRTResult<()> {
         let engine = match self.engine.as_mut() {
             Some(engine) => engine,
             None => return Err(TRTError::EngineCreationError),
         };
 
-        self.context = match engine.create_execution_context() {
-            Some(context) => Some(context),
-            None => return Err(TRTError::ExecutionContextCreationError),
+        clear_recorded(self.engine_recorder.as_ref());
+        let context = match with_device_memory {
+            true => engine.create_execution_context(),
+            false => engine.create_execution_context_without_device_memory(),
+        };
+        let mut context = match context {
+            Some(context) => context,
+            None => {
+                let err = TRTError::ExecutionContextCreationError;
+                return Err(recorded(self.engine_recorder.as_ref(), err));
+            }
         };
 
+        let mut aux_streams = vec![];
+        if let Some(config) = self.aux_stream_config {
+            for _ in 0..engine.get_num_aux_streams() {
+                aux_streams.push(config.create()?);
+            }
+            // SAFETY: the aux streams are kept, and dropped after the context
+            let streams: Vec<_> =
+                aux_streams.iter().map(|stream| unsafe { stream.as_cu_stream() }).collect();
+            context.set_aux_streams(&streams.iter().collect::<Vec<_>>());
+        }
+
+        // replace the context before the aux streams it may still be using
+        self.context = Some(context);
+        self.num_configured_aux_streams = aux_streams.len();
+        self.given_aux_streams.clear();
+        self.aux_streams = aux_streams;
+
         Ok(())
     }
 
+    /// Allocates the IO tensors for the shapes in `max_shape_dict`. Inputs
+    /// missing from it take the engine's shapes, which must then be fully
+    /// known; outputs missing from it take the shapes the context infers from
+    /// the inputs, e.g. for NLP engines whose outputs depend on the sequence
+    /// length.
     pub fn allocate_io_tensors(
         &mut self,
         max_shape_dict: &HashMap<&str, &Shape>,
         stream: Option<&CuStream>,
     ) -> TRTResult<()> {
+        self.graph = None;
+
         let engine = match self.engine.as_mut() {
             Some(engine) => engine,
             None => return Err(TRTError::EngineCreationError),
@@ -74,48 +637,257 @@ impl TRTEngine {
             None => &self.stream,
         };
 
-        let num_io_tensors = engine.get_num_io_tensors();
+        allocate_tensors(engine, context, max_shape_dict, stream, &mut self.inputs, &mut self.outputs)?;
+        self.alloc_stream = Some(stream.clone());
+
+        Ok(())
+    }
+
+    /// With lazy allocation, `inference` allocates the IO tensors on its first
+    /// call if `allocate_io_tensors` was not called, sizing inputs from the
+    /// feed dict and outputs from the shapes the engine infers.
+    pub fn set_lazy_alloc(&mut self, lazy_alloc: bool) {
+        self.lazy_alloc = lazy_alloc;
+    }
+
+    pub fn inference(
+        &mut self,
+        feed_dict: &HashMap<&str, &Tensor>,
+        stream: Option<&CuStream>,
+    ) -> TRTResult<&HashMap<String, Tensor>> {
+        self.check_cuda_context()?;
+        self.check_profile_stream(stream)?;
+        #[cfg(debug_assertions)]
+        self.check_alloc_stream(stream);
+
+        let context: &mut ExecutionContext = match self.context.as_mut() {
+            Some(context) => context,
+            None => return Err(TRTError::ExecutionContextNotInitialized),
+        };
+        let stream = match stream {
+            Some(stream) => stream,
+            None => &self.stream,
+        };
+
+        let engine = match self.engine.as_ref() {
+            Some(engine) => engine,
+            None => return Err(TRTError::EngineCreationError),
+        };
+
+        if self.lazy_alloc && self.inputs.is_empty() && self.outputs.is_empty() {
+            let shape_dict = feed_dict
+                .iter()
+                .map(|(name, tensor)| (*name, tensor.shape()))
+                .collect();
+            allocate_tensors(engine, context, &shape_dict, stream, &mut self.inputs, &mut self.outputs)?;
+            self.alloc_stream = Some(stream.clone());
+        }
+
+        clear_recorded(self.engine_recorder.as_ref());
+        enqueue(engine, context, &mut self.inputs, feed_dict, stream)
+            .map_err(|err| recorded(self.engine_recorder.as_ref(), err))?;
+
+        Ok(&self.outputs)
+    }
+
+    /// Runs an inference on the CUDA default (legacy) stream and waits for it,
+    /// for the simplest scripts.
+    ///
+    /// The legacy default stream synchronizes with every other blocking stream
+    /// on the device, so it serializes with all concurrent work; servers
+    /// should use `inference` with their own streams instead.
+    pub fn inference_default_stream(
+        &mut self,
+        feed_dict: &HashMap<&str, &Tensor>,
+    ) -> TRTResult<&HashMap<String, Tensor>> {
+        // borrowed, so dropping it does not destroy the default stream
+        let stream = unsafe { CuStream::from_raw(std::ptr::null_mut()) };
+        self.inference(feed_dict, Some(&stream))?;
+        stream.synchronize()?;
+
+        Ok(&self.outputs)
+    }
+
+    /// Enqueues an inference on the engine's stream and resolves once it has
+    /// completed, without blocking an async worker on `synchronize`: the wait
+    /// happens on tokio's blocking thread pool.
+    ///
+    /// The engine is not `Send`, so neither is the returned future: await it
+    /// on a current-thread runtime, or spawn it with `spawn_local` inside a
+    /// `tokio::task::LocalSet`.
+    #[cfg(feature = "tokio")]
+    pub async fn inference_async_tokio(
+        &mut self,
+        feed_dict: &HashMap<&str, &Tensor>,
+    ) -> TRTResult<&HashMap<String, Tensor>> {
+        self.inference(feed_dict, None)?;
+
+        let event = CuEvent::new()?;
+        event.record(&self.stream)?;
+        let waiter = EventWaiter { event, cu_context: self.cu_context.clone() };
+        match tokio::task::spawn_blocking(move || waiter.wait()).await {
+            Ok(res) => res?,
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+
+        Ok(&self.outputs)
+    }
+
+    /// Runs the inference on `compute_stream` and copies the outputs on
+    /// `output_stream` once it completes, so the next stage can consume them
+    /// without serializing behind the compute stream. The returned tensors
+    /// live on `output_stream`.
+    pub fn inference_with_output_stream(
+        &mut self,
+        feed_dict: &HashMap<&str, &Tensor>,
+        compute_stream: &CuStream,
+        output_stream: &CuStream,
+    ) -> TRTResult<HashMap<String, Tensor>> {
+        self.inference(feed_dict, Some(compute_stream))?;
+
+        let computed = CuEvent::new()?;
+        computed.record(compute_stream)?;
+        output_stream.wait_on_event(&computed)?;
+
+        let mut res = HashMap::new();
+        for (name, tensor) in self.outputs.iter() {
+            let mut output = Tensor::empty(tensor.shape(), tensor.dtype(), output_stream)?;
+            output.copy_from(tensor, Some(output_stream))?;
+            res.insert(name.clone(), output);
+        }
+
+        // the next inference must not overwrite the outputs before they are copied
+        let copied = CuEvent::new()?;
+        copied.record(output_stream)?;
+        compute_stream.wait_on_event(&copied)?;
+
+        Ok(res)
+    }
+
+    /// Runs `warmup` inferences on the engine's stream, then times each of
+    /// `iters` more with CUDA events. The events of all iterations are read
+    /// after a single synchronize, so the measured enqueues run back to back.
+    pub fn benchmark(
+        &mut self,
+        feed_dict: &HashMap<&str, &Tensor>,
+        warmup: usize,
+        iters: usize,
+    ) -> TRTResult<BenchStats> {
+        if iters == 0 {
+            return Err(TRTError::EmptyBenchmark);
+        }
+
+        for _ in 0..warmup {
+            self.inference(feed_dict, None)?;
+        }
+
+        let mut events = Vec::with_capacity(iters);
+        for _ in 0..iters {
+            let start = CuEvent::new()?;
+            let end = CuEvent::new()?;
+            start.record(&self.stream)?;
+            self.inference(feed_dict, None)?;
+            end.record(&self.stream)?;
+            events.push((start, end));
+        }
+        self.stream.synchronize()?;
+
+        let mut latencies = Vec::with_capacity(iters);
+        for (start, end) in events.iter() {
+            latencies.push(end.elapsed_time(start)?);
+        }
+        latencies.sort_by(f32::total_cmp);
+
+        let mean_ms = latencies.iter().sum::<f32>() / iters as f32;
+        Ok(BenchStats {
+            mean_ms,
+            p50_ms: percentile(&latencies, 50),
+            p99_ms: percentile(&latencies, 99),
+            throughput: if mean_ms > 0.0 { 1000.0 / mean_ms } else { 0.0 },
+        })
+    }
+
+    /// Whether `capture_graph` can work for this engine: false if an IO
+    /// tensor is a shape tensor, or an output's shape depends on the data,
+    /// in which case inference has to stay on plain enqueues.
+    ///
+    /// Until every input has a shape on the context, outputs with dynamic
+    /// shapes cannot be told apart from data-dependent ones and count as such.
+    pub fn is_graph_capturable(&self) -> bool {
+        let engine = match self.engine.as_ref() {
+            Some(engine) => engine,
+            None => return false,
+        };
+        let context = self
+            .context
+            .as_ref()
+            .filter(|context| context.all_input_dimensions_specified());
 
-        for i in 0..num_io_tensors {
+        (0..engine.get_num_io_tensors()).all(|i| {
             let name = engine.get_io_tensor_name(i);
-            let shape = engine.get_tensor_shape(name);
-            let shape = Shape(shape);
-            let shape = match max_shape_dict.get(name) {
-                Some(max_shape) => max_shape,
-                None => &shape,
-            };
-            if shape.0.iter().any(|&dim| dim < 0) {
-                return Err(TRTError::ShapeError(shape.0.clone()));
-            }
-            if engine.get_tensor_io_mode(name).is_input() {
-                if !context.set_input_shape(name, shape.0.as_slice()) {
-                    return Err(TRTError::ShapeError(shape.0.clone()));
-                }
+            if engine.is_shape_inference_io(name) {
+                return false;
             }
-
-            let dtype = engine.get_tensor_dtype(name);
-            let tensor = Tensor::empty(&shape, dtype, stream)?;
-            let ptr = unsafe { tensor.get_raw_ptr() };
-            self.tensors.insert(name.to_string(), tensor);
-            if !context.set_tensor_address(name, ptr as _) {
-                return Err(TRTError::InvalidAddress);
+            if engine.get_tensor_io_mode(name) != TensorIOMode::OUTPUT {
+                return true;
             }
+
+            let shape = match context {
+                Some(context) => context.get_tensor_shape(name),
+                None => engine.get_tensor_shape(name),
+            };
+            shape.iter().all(|&dim| dim >= 0)
+        })
+    }
+
+    /// Captures one inference into a CUDA graph that `inference_graph` replays
+    /// with a single launch, including the work on the engine's aux streams.
+    ///
+    /// The IO tensors must be allocated. The graph keeps their shapes and
+    /// addresses, so changing them (reallocating, binding, new input shapes or
+    /// another profile) drops it.
+    pub fn capture_graph(&mut self, stream: Option<&CuStream>) -> TRTResult<()> {
+        self.check_cuda_context()?;
+        self.check_profile_stream(stream)?;
+        let engine_name = self.engine_name();
+
+        let context: &mut ExecutionContext = match self.context.as_mut() {
+            Some(context) => context,
+            None => return Err(TRTError::ExecutionContextNotInitialized),
+        };
+        let stream = match stream {
+            Some(stream) => stream,
+            None => &self.stream,
+        };
+
+        // let TensorRT finish its deferred setup outside the capture
+        clear_recorded(self.engine_recorder.as_ref());
+        if !context.enqueue_v3(stream) {
+            let err = TRTError::EnqueueError(engine_name);
+            return Err(recorded(self.engine_recorder.as_ref(), err));
         }
 
-        // TODO: validate shapes, (batch size)
+        self.graph = match context.capture_graph(stream) {
+            Some(graph) => Some(GraphState { graph, retired: vec![] }),
+            None => return Err(TRTError::GraphCaptureError),
+        };
 
         Ok(())
     }
 
-    // TODO: use cuda graph
-    pub fn inference(
+    /// Like `inference`, but replays the graph recorded by `capture_graph`.
+    /// Inputs must keep the shapes they had when the graph was captured.
+    pub fn inference_graph(
         &mut self,
         feed_dict: &HashMap<&str, &Tensor>,
         stream: Option<&CuStream>,
     ) -> TRTResult<&HashMap<String, Tensor>> {
-        let context: &mut ExecutionContext = match self.context.as_mut() {
-            Some(context) => context,
-            None => return Err(TRTError::ExecutionContextNotInitialized),
+        self.check_cuda_context()?;
+
+        let graph = match self.graph.as_mut() {
+            Some(state) if state.retired.is_empty() => &mut state.graph,
+            Some(_) => return Err(TRTError::GraphAddressesOutdated),
+            None => return Err(TRTError::GraphNotCaptured),
         };
         let stream = match stream {
             Some(stream) => stream,
@@ -123,46 +895,2690 @@ impl TRTEngine {
         };
 
         for (name, input_tensor) in feed_dict {
-            let tensor = match self.tensors.get_mut(name.to_owned()) {
+            let tensor = match self.inputs.get_mut(*name) {
                 Some(tensor) => tensor,
                 None => continue,
             };
-            let new_shape = input_tensor.shape();
-            if tensor.shape() != new_shape {
-                unsafe { tensor.reset_shape(new_shape)? };
-                if !context.set_input_shape(name, new_shape.0.as_slice()) {
-                    return Err(TRTError::ShapeError(new_shape.0.clone()));
-                }
+            if tensor.shape() != input_tensor.shape() {
+                return Err(TRTError::InvalidShape(input_tensor.shape().0.clone()));
             }
             tensor.copy_from(input_tensor, Some(stream))?;
         }
 
-        // TODO: validate shapes, (batch size)
-
-        if !context.enqueue_v3(stream) {
-            return Err(TRTError::EnqueueError);
+        if !graph.launch(stream) {
+            return Err(TRTError::GraphLaunchError);
         }
 
-        Ok(&self.tensors)
+        Ok(&self.outputs)
     }
 
-    pub fn log(&mut self, level: Severity, msg: &str) {
-        self.runtime.as_mut().unwrap().logger().log(level, msg);
-    }
-}
+    /// Points the captured graph at the currently bound tensors, e.g. after
+    /// `bind_tensor` swapped in another buffer for double-buffering, which is
+    /// much cheaper than capturing it again. Until then `inference_graph`
+    /// refuses to replay.
+    ///
+    /// Only address changes can be applied this way. Shape changes (binding a
+    /// tensor of another shape, `set_input_shapes`, switching profiles or
+    /// reallocating) drop the graph and need a new `capture_graph`.
+    pub fn update_graph_addresses(&mut self) -> TRTResult<()> {
+        self.check_cuda_context()?;
 
-impl Drop for TRTEngine {
-    fn drop(&mut self) {
-        if let Some(context) = self.context.take() {
+        let context: &mut ExecutionContext = match self.context.as_mut() {
+            Some(context) => context,
+            None => return Err(TRTError::ExecutionContextNotInitialized),
+        };
+        let state = match self.graph.as_mut() {
+            Some(state) => state,
+            None => return Err(TRTError::GraphNotCaptured),
+        };
+
+        for (name, tensor) in self.inputs.iter().chain(self.outputs.iter()) {
+            if !context.set_tensor_address(name, unsafe { tensor.get_raw_ptr() }) {
+                return Err(TRTError::InvalidAddress);
+            }
+        }
+
+        if !context.update_graph(&mut state.graph, &self.stream) {
+            return Err(TRTError::GraphUpdateError);
+        }
+        state.retired.clear();
+
+        Ok(())
+    }
+
+    pub fn output(&self, name: &str) -> Option<&Tensor> {
+        self.outputs.get(name)
+    }
+
+    pub fn outputs(&self) -> impl Iterator<Item = (&str, &Tensor)> {
+        self.outputs.iter().map(|(name, tensor)| (name.as_str(), tensor))
+    }
+
+    pub fn output_map(&self) -> OutputMap<'_> {
+        OutputMap::from(&self.outputs)
+    }
+
+    /// The engine's default stream, borrowed as it may be one the engine
+    /// created itself and destroys when it drops.
+    pub fn stream(&self) -> EngineStream<'_> {
+        EngineStream(&self.stream)
+    }
+
+    /// Replaces the default stream, which the IO tensors also move to. Work
+    /// on `stream` is ordered after the work already enqueued on the old
+    /// stream, so the tensors are not used or freed while it still runs.
+    pub fn set_stream(&mut self, stream: CuStream) -> TRTResult<()> {
+        let done = CuEvent::new()?;
+        done.record(&self.stream)?;
+        stream.wait_on_event(&done)?;
+
+        for tensor in self.inputs.values_mut().chain(self.outputs.values_mut()) {
+            tensor.set_stream(&stream);
+        }
+        self.stream = stream;
+
+        Ok(())
+    }
+
+    /// Returns device memory that is no longer in use to the driver, e.g.
+    /// between bursts on a multi-tenant server. The IO tensors are allocated
+    /// from the device's stream-ordered pool, which keeps freed blocks cached
+    /// for reuse; this waits for the engine's stream so its pending frees have
+    /// landed, then trims that pool. The IO tensors themselves are kept.
+    pub fn trim_memory(&mut self) -> TRTResult<()> {
+        self.check_cuda_context()?;
+        self.stream.synchronize()?;
+
+        let device = match device::get_current_device() {
+            Some(device) => device,
+            None => return Err(TRTError::CudaError(CuError::InvalidContext)),
+        };
+        if !device::trim_default_mem_pool(device, 0) {
+            return Err(TRTError::MemoryTrimError);
+        }
+
+        Ok(())
+    }
+
+    /// Waits for all work on the engine's stream, e.g. after `inference`
+    /// without a stream, so callers need not keep their own handle to it.
+    pub fn synchronize(&self) -> TRTResult<()> {
+        self.stream.synchronize()?;
+
+        Ok(())
+    }
+
+    /// Sets the shapes of the given inputs on the context, e.g. before their
+    /// data is ready, checking each against the active profile. Allocated
+    /// input tensors are reshaped in place, e.g. for a smaller last batch,
+    /// and once every input has a shape the output tensors take the
+    /// inferred shapes.
+    pub fn set_input_shapes(&mut self, shapes: &HashMap<&str, &Shape>) -> TRTResult<()> {
+        self.graph = None;
+
+        let engine = match self.engine.as_ref() {
+            Some(engine) => engine,
+            None => return Err(TRTError::EngineCreationError),
+        };
+        let context: &mut ExecutionContext = match self.context.as_mut() {
+            Some(context) => context,
+            None => return Err(TRTError::ExecutionContextNotInitialized),
+        };
+
+        for (name, shape) in shapes {
+            if !engine.get_tensor_io_mode(name).is_input() {
+                return Err(TRTError::UnknownTensor(name.to_string()));
+            }
+            match self.inputs.get(*name) {
+                Some(tensor) if shape.size() > tensor.capacity() => {
+                    return Err(TRTError::InvalidShape(shape.0.clone()))
+                }
+                _ => {}
+            }
+        }
+
+        for (name, shape) in shapes {
+            if !context.set_input_shape(name, shape.0.as_slice()) {
+                return Err(shape_error(engine, context, name, shape));
+            }
+            if let Some(tensor) = self.inputs.get_mut(*name) {
+                // SAFETY: checked against the allocation above
+                unsafe { tensor.reset_shape(shape)? };
+            }
+        }
+
+        if !context.all_input_dimensions_specified() {
+            return Ok(());
+        }
+        clear_recorded(self.engine_recorder.as_ref());
+        if !context.infer_shapes() {
+            return Err(recorded(self.engine_recorder.as_ref(), TRTError::ResetShapesError));
+        }
+
+        for (name, tensor) in self.outputs.iter_mut() {
+            let shape = Shape(context.get_tensor_shape(name));
+            if shape.size() > tensor.capacity() {
+                return Err(TRTError::InvalidShape(shape.0));
+            }
+            unsafe { tensor.reset_shape(&shape)? };
+        }
+
+        Ok(())
+    }
+
+    /// Whether every input has a shape on the context, so outputs can be
+    /// inferred and inference can run.
+    pub fn all_input_dimensions_specified(&self) -> TRTResult<bool> {
+        match self.context.as_ref() {
+            Some(context) => Ok(context.all_input_dimensions_specified()),
+            None => Err(TRTError::ExecutionContextNotInitialized),
+        }
+    }
+
+    /// The engine's name, as set when it was built; empty without an engine.
+    pub fn engine_name(&self) -> String {
+        match self.engine.as_ref() {
+            Some(engine) => engine.get_name().to_string(),
+            None => String::new(),
+        }
+    }
+
+    pub fn io_count(&self) -> TRTResult<i32> {
+        let engine = self.engine()?;
+
+        Ok(engine.get_num_io_tensors())
+    }
+
+    pub fn input_count(&self) -> TRTResult<i32> {
+        Ok(self.input_names()?.len() as i32)
+    }
+
+    pub fn output_count(&self) -> TRTResult<i32> {
+        Ok(self.output_names()?.len() as i32)
+    }
+
+    pub fn input_names(&self) -> TRTResult<Vec<&str>> {
+        self.io_names(TensorIOMode::INPUT)
+    }
+
+    pub fn output_names(&self) -> TRTResult<Vec<&str>> {
+        self.io_names(TensorIOMode::OUTPUT)
+    }
+
+    pub fn has_input(&self, name: &str) -> TRTResult<bool> {
+        let engine = self.engine()?;
+
+        Ok(engine.get_tensor_io_mode(name).is_input())
+    }
+
+    pub fn has_output(&self, name: &str) -> TRTResult<bool> {
+        let engine = self.engine()?;
+
+        Ok(engine.get_tensor_io_mode(name).is_output())
+    }
+
+    pub fn tensor_info(&self, name: &str) -> TRTResult<TensorInfo> {
+        let engine = self.engine()?;
+
+        let io_mode = engine.get_tensor_io_mode(name);
+        if io_mode == TensorIOMode::NONE {
+            return Err(TRTError::UnknownTensor(name.to_string()));
+        }
+
+        Ok(TensorInfo {
+            name: name.to_string(),
+            io_mode,
+            dtype: engine.get_tensor_dtype(name),
+            shape: Shape(engine.get_tensor_shape(name)),
+            format: engine.get_tensor_format(name),
+            vectorized_dim: engine.get_tensor_vectorized_dim(name),
+        })
+    }
+
+    pub fn tensor_infos(&self) -> TRTResult<Vec<TensorInfo>> {
+        let engine = self.engine()?;
+
+        (0..engine.get_num_io_tensors())
+            .map(|i| self.tensor_info(engine.get_io_tensor_name(i)))
+            .collect()
+    }
+
+    /// Checks the engine's IO tensors against `spec`, e.g. one loaded from a
+    /// serving config, and reports every difference at once: missing or
+    /// unexpected tensors, swapped directions, dtypes and shapes.
+    pub fn validate_against_spec(&self, spec: &IoSpec) -> TRTResult<()> {
+        let infos = self.tensor_infos()?;
+
+        let mut diffs = vec![];
+        let expected = spec.inputs.iter().map(|tensor| (tensor, TensorIOMode::INPUT))
+            .chain(spec.outputs.iter().map(|tensor| (tensor, TensorIOMode::OUTPUT)));
+        for (tensor, io_mode) in expected {
+            let info = match infos.iter().find(|info| info.name == tensor.name) {
+                Some(info) => info,
+                None => {
+                    diffs.push(format!("missing {} '{}'", io_mode, tensor.name));
+                    continue;
+                }
+            };
+            if info.io_mode != io_mode {
+                diffs.push(format!("'{}' is an {}, expected an {}", info.name, info.io_mode, io_mode));
+            }
+            if info.dtype != tensor.dtype {
+                diffs.push(format!(
+                    "'{}' has dtype {:?}, expected {:?}",
+                    info.name, info.dtype, tensor.dtype,
+                ));
+            }
+            let shape_matches = info.shape.0.len() == tensor.shape.0.len()
+                && info.shape.0.iter().zip(tensor.shape.0.iter())
+                    .all(|(&got, &expected)| expected == -1 || got == expected);
+            if !shape_matches {
+                diffs.push(format!(
+                    "'{}' has shape {}, expected {}",
+                    info.name, info.shape, tensor.shape,
+                ));
+            }
+        }
+
+        for info in infos.iter() {
+            let listed = spec.inputs.iter().chain(spec.outputs.iter())
+                .any(|tensor| tensor.name == info.name);
+            if !listed {
+                diffs.push(format!("unexpected {} '{}'", info.io_mode, info.name));
+            }
+        }
+
+        if diffs.is_empty() {
+            Ok(())
+        } else {
+            Err(TRTError::SpecMismatch(diffs))
+        }
+    }
+
+    /// Binds an externally produced buffer in place of the allocated tensor.
+    /// `format` is the layout the buffer was written in; debug builds log a
+    /// warning when it differs from the format the engine expects.
+    pub fn bind_tensor(&mut self, name: &str, tensor: Tensor, format: TensorFormat) -> TRTResult<()> {
+        let info = self.tensor_info(name)?;
+        if cfg!(debug_assertions) {
+            if let Some(msg) = format_mismatch(&info, format) {
+                self.log(Severity::Warning, &msg);
+            }
+        }
+
+        let engine = match self.engine.as_ref() {
+            Some(engine) => engine,
+            None => return Err(TRTError::EngineCreationError),
+        };
+        let context: &mut ExecutionContext = match self.context.as_mut() {
+            Some(context) => context,
+            None => return Err(TRTError::ExecutionContextNotInitialized),
+        };
+        // a tensor of another type would have its bytes misread
+        if tensor.dtype() != info.dtype {
+            return Err(TRTError::DTypeMismatch);
+        }
+        if info.io_mode.is_input() && !context.set_input_shape(name, tensor.shape().0.as_slice()) {
+            return Err(shape_error(engine, context, name, tensor.shape()));
+        }
+        // TensorRT writes outputs in the shape the context infers, padded for
+        // vectorized formats; it is only known once every input shape is set
+        let shape = Shape(context.get_tensor_shape(name));
+        if shape.0.iter().all(|&dim| dim >= 0) {
+            let required = io_byte_size(engine, name, &shape);
+            if tensor.get_memory().size < required {
+                return Err(TRTError::BufferSizeMismatch(required, tensor.get_memory().size));
+            }
+        }
+        if !context.set_tensor_address(name, unsafe { tensor.get_raw_ptr() }) {
+            return Err(TRTError::InvalidAddress);
+        }
+        debug_assert_eq!(context.get_tensor_address(name), unsafe { tensor.get_raw_ptr() });
+
+        let tensors = match info.io_mode.is_input() {
+            true => &mut self.inputs,
+            false => &mut self.outputs,
+        };
+        let replaced = tensors.insert(name.to_string(), tensor);
+
+        // a captured graph survives an address change until
+        // `update_graph_addresses`, but not a change of shape
+        let same_shape = replaced
+            .as_ref()
+            .is_some_and(|old| old.shape() == tensors[name].shape());
+        if !same_shape {
+            self.graph = None;
+        }
+        if let (Some(state), Some(old)) = (self.graph.as_mut(), replaced) {
+            state.retired.push(old);
+        }
+
+        Ok(())
+    }
+
+    /// Points IO tensor `name` at `tensor`'s memory without copying or
+    /// allocating, e.g. to alternate between the two buffers of a double
+    /// buffered pipeline. The shape and dtype must match the current binding.
+    ///
+    /// The buffer previously bound under `name` is released. A feed dict
+    /// entry for a rebound input that holds the same memory is not copied.
+    ///
+    /// # Safety
+    ///
+    /// The engine only borrows `tensor`'s memory: it has to stay allocated
+    /// until `name` is bound to another tensor, or the engine is dropped,
+    /// and no inference may still be running on it.
+    pub unsafe fn rebind(&mut self, name: &str, tensor: &Tensor) -> TRTResult<()> {
+        let info = self.tensor_info(name)?;
+        let current = match self.inputs.get(name).or_else(|| self.outputs.get(name)) {
+            Some(current) => current,
+            None => return Err(TRTError::UnknownTensor(name.to_string())),
+        };
+        if tensor.dtype() != current.dtype() {
+            return Err(TRTError::DTypeMismatch);
+        }
+        if tensor.shape() != current.shape() {
+            return Err(TRTError::ShapeMismatch);
+        }
+
+        let ptr = unsafe { tensor.get_raw_ptr() };
+        let (shape, dtype, stream) = (tensor.shape(), tensor.dtype(), &tensor.get_memory().stream);
+        // SAFETY: the caller keeps `tensor` alive while the engine uses it
+        let view = unsafe { Tensor::from_borrowed_ptr(ptr, shape, dtype, stream) };
+        self.bind_tensor(name, view, info.format)
+    }
+
+    /// Device memory needed by an execution context of this engine, whichever
+    /// optimization profile it selects.
+    pub fn required_workspace_size(&self) -> TRTResult<usize> {
+        let engine = self.engine()?;
+
+        Ok(required_device_memory(engine))
+    }
+
+    pub fn num_optimization_profiles(&self) -> TRTResult<i32> {
+        let engine = self.engine()?;
+
+        Ok(engine.get_num_optimization_profiles())
+    }
+
+    /// The min, opt and max shapes of each input under `profile_index`.
+    pub fn profile_shapes(&self, profile_index: i32) -> TRTResult<HashMap<String, ProfileShapes>> {
+        let engine = self.engine()?;
+        check_profile_index(engine, profile_index)?;
+
+        let mut shapes = HashMap::new();
+        for i in 0..engine.get_num_io_tensors() {
+            let name = engine.get_io_tensor_name(i);
+            if !engine.get_tensor_io_mode(name).is_input() {
+                continue;
+            }
+            let profile_shape = |select| {
+                Shape(engine.get_profile_shape(name, profile_index, select))
+            };
+            shapes.insert(name.to_string(), ProfileShapes {
+                min: profile_shape(OptProfileSelector::MIN),
+                opt: profile_shape(OptProfileSelector::OPT),
+                max: profile_shape(OptProfileSelector::MAX),
+            });
+        }
+
+        Ok(shapes)
+    }
+
+    /// Checks that `profile_index` exists, that its input shapes satisfy
+    /// min <= opt <= max, and that the input shapes currently set on the
+    /// context lie within the profile's bounds.
+    pub fn validate_profile(&self, profile_index: i32) -> TRTResult<()> {
+        let engine = self.engine()?;
+        check_profile_consistency(engine, profile_index)?;
+
+        if let Some(context) = self.context.as_ref() {
+            for i in 0..engine.get_num_io_tensors() {
+                let name = engine.get_io_tensor_name(i);
+                if engine.get_tensor_io_mode(name).is_input() {
+                    let shape = context.get_tensor_shape(name);
+                    check_profile_shape(engine, profile_index, name, &shape)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks `feed_dict` against the engine before running it: every input
+    /// must be present with the engine's dtype and a shape within the bounds
+    /// of the active profile. Returns the first violation.
+    pub fn validate_feed(&self, feed_dict: &HashMap<&str, &Tensor>) -> TRTResult<()> {
+        let engine = self.engine()?;
+        let profile_index = match self.context.as_ref() {
+            Some(context) => context.get_optimization_profile(),
+            None => 0,
+        };
+
+        for name in feed_dict.keys() {
+            if !engine.get_tensor_io_mode(name).is_input() {
+                return Err(TRTError::UnknownTensor(name.to_string()));
+            }
+        }
+
+        for name in self.input_names()? {
+            let tensor = match feed_dict.get(name) {
+                Some(tensor) => tensor,
+                None => return Err(TRTError::MissingInput(name.to_string())),
+            };
+            if tensor.dtype() != engine.get_tensor_dtype(name) {
+                return Err(TRTError::DTypeMismatch);
+            }
+            check_profile_shape(engine, profile_index, name, &tensor.shape().0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `profile_index` is consistent and makes it the context's
+    /// active profile. The shapes of the tensors bound again are checked
+    /// against it once it is active.
+    ///
+    /// The switch is ordered on `stream`: until it has completed, inferences
+    /// on other streams fail with `ProfileStreamMismatch`, so synchronize
+    /// `stream` first or keep enqueueing on it.
+    ///
+    /// Each profile keeps its own IO tensors: those of the profile switched
+    /// away from are set aside, and the ones `profile_index` had when it was
+    /// last active are bound again. A profile switched to for the first time
+    /// has none until `allocate_io_tensors` (or lazy allocation) runs. If
+    /// they cannot be bound again, the previous profile and its tensors are
+    /// restored before the error is returned.
+    pub fn switch_profile(&mut self, profile_index: i32, stream: Option<&CuStream>) -> TRTResult<()> {
+        // the context still holds the shapes of the previous profile, which
+        // need not fit the new one
+        check_profile_consistency(self.engine()?, profile_index)?;
+
+        let engine = match self.engine.as_ref() {
+            Some(engine) => engine,
+            None => return Err(TRTError::EngineCreationError),
+        };
+        let context: &mut ExecutionContext = match self.context.as_mut() {
+            Some(context) => context,
+            None => return Err(TRTError::ExecutionContextNotInitialized),
+        };
+        let stream = match stream {
+            Some(stream) => stream,
+            None => &self.stream,
+        };
+
+        self.graph = None;
+        let switched = CuEvent::new()?;
+        let previous = context.get_optimization_profile();
+        if !context.set_optimization_profile_async(profile_index, stream) {
+            return Err(TRTError::ProfileValidationError {
+                profile_index,
+                tensor_name: String::new(),
+                reason: "the execution context rejected the profile".to_string(),
+            });
+        }
+        if previous == profile_index {
+            switched.record(stream)?;
+            self.profile_switch = Some((stream.clone(), switched));
+            return Ok(());
+        }
+
+        let parked = IOTensors {
+            inputs: std::mem::take(&mut self.inputs),
+            outputs: std::mem::take(&mut self.outputs),
+        };
+        if !parked.inputs.is_empty() || !parked.outputs.is_empty() {
+            self.parked_tensors.insert(previous, parked);
+        }
+
+        if let Some(restored) = self.parked_tensors.remove(&profile_index) {
+            if let Err(err) = bind_io_tensors(engine, context, profile_index, &restored) {
+                // go back to the previous profile and its tensors, which were
+                // bound fine before
+                self.parked_tensors.insert(profile_index, restored);
+                if context.set_optimization_profile_async(previous, stream)
+                    && switched.record(stream).is_ok()
+                {
+                    self.profile_switch = Some((stream.clone(), switched));
+                }
+                if let Some(tensors) = self.parked_tensors.remove(&previous) {
+                    let _ = bind_io_tensors(engine, context, previous, &tensors);
+                    self.inputs = tensors.inputs;
+                    self.outputs = tensors.outputs;
+                }
+
+                return Err(err);
+            }
+            self.inputs = restored.inputs;
+            self.outputs = restored.outputs;
+        }
+
+        // recorded last so that a failure leaves the tensors matching the
+        // active profile
+        switched.record(stream)?;
+        self.profile_switch = Some((stream.clone(), switched));
+
+        Ok(())
+    }
+
+    /// Hands `streams` to the execution context for the layers TensorRT runs
+    /// in parallel; the engine keeps clones of them while the context may use
+    /// them. Warns through the engine's logger if fewer streams are given
+    /// than the engine expects: TensorRT then runs the remaining parallel
+    /// layers on the main stream.
+    pub fn set_aux_streams(&mut self, streams: &[&CuStream]) -> TRTResult<()> {
+        let expected = self.engine()?.get_num_aux_streams();
+        let context: &mut ExecutionContext = match self.context.as_mut() {
+            Some(context) => context,
+            None => return Err(TRTError::ExecutionContextNotInitialized),
+        };
+
+        self.graph = None;
+        context.set_aux_streams(streams);
+        self.num_configured_aux_streams = streams.len();
+        self.given_aux_streams = streams.iter().map(|&stream| stream.clone()).collect();
+
+        if !streams.is_empty() && (streams.len() as i32) < expected {
+            self.log_warning(&format!(
+                "{} of {} aux streams configured, the rest fall back to the main stream",
+                streams.len(), expected,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Reports the time each layer takes in every inference to `profiler`.
+    /// Each inference then waits for the engine to finish, and graphs can't
+    /// be captured. The profiler belongs to the current context, so
+    /// activating the engine again drops it.
+    pub fn set_profiler<P: Profiler + 'static>(&mut self, profiler: P) -> TRTResult<()> {
+        let context: &mut ExecutionContext = match self.context.as_mut() {
+            Some(context) => context,
+            None => return Err(TRTError::ExecutionContextNotInitialized),
+        };
+
+        self.graph = None;
+        context.set_profiler(profiler);
+
+        Ok(())
+    }
+
+    /// Reports the aux streams the engine expects and how many were set.
+    pub fn aux_stream_info(&self) -> TRTResult<AuxStreamInfo> {
+        Ok(AuxStreamInfo {
+            expected: self.engine()?.get_num_aux_streams(),
+            configured: self.num_configured_aux_streams,
+        })
+    }
+
+    pub fn current_profile(&self) -> TRTResult<i32> {
+        let context = self.context()?;
+
+        Ok(context.get_optimization_profile())
+    }
+
+    /// The device address bound to `name`, or `None` if nothing is bound or
+    /// the engine is not activated.
+    pub fn tensor_address(&self, name: &str) -> Option<usize> {
+        let context = self.context.as_ref()?;
+
+        match context.get_tensor_address(name) {
+            0 => None,
+            address => Some(address),
+        }
+    }
+
+    pub fn tensor_address_map(&self) -> TRTResult<HashMap<String, usize>> {
+        let engine = self.engine()?;
+        let context = self.context()?;
+
+        let addresses = context
+            .get_all_tensor_addresses(engine)
+            .into_iter()
+            .map(|(name, address)| (name.to_string(), address))
+            .collect();
+
+        Ok(addresses)
+    }
+
+    /// The recorder set with `TRTEngineBuilder::error_recorder`, which
+    /// receives every error, including those returned as `TRTError::Recorded`.
+    pub fn error_recorder(&self) -> Option<&ErrorRecorder> {
+        self.error_recorder.as_ref()
+    }
+
+    /// The logger of an owned runtime; `None` for a shared runtime, which
+    /// `log` still goes through.
+    pub fn logger(&mut self) -> Option<&mut Logger> {
+        self.runtime.as_mut().map(|runtime| runtime.logger())
+    }
+
+    /// Logs through the runtime's logger; a no-op once the runtime is gone.
+    pub fn log(&mut self, level: Severity, msg: &str) {
+        self.try_log(level, msg);
+    }
+
+    /// Like `log`, but returns false if there is no runtime to log through.
+    pub fn try_log(&mut self, level: Severity, msg: &str) -> bool {
+        if let Some(logger) = self.logger() {
+            logger.log(level, msg);
+            return true;
+        }
+
+        match self.shared_runtime.as_ref() {
+            Some(runtime) => {
+                runtime.log(level, msg);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Logs a table of the IO tensors at info level; a no-op without an engine.
+    pub fn print_io_spec(&mut self) {
+        let summary = match self.engine.as_ref() {
+            Some(engine) => engine.io_summary(),
+            None => return,
+        };
+        self.log_info(&format!("IO tensors:\n{}", summary));
+    }
+
+    pub fn log_info(&mut self, msg: &str) {
+        self.log(Severity::Info, msg);
+    }
+
+    pub fn log_warning(&mut self, msg: &str) {
+        self.log(Severity::Warning, msg);
+    }
+
+    pub fn log_error(&mut self, msg: &str) {
+        self.log(Severity::Error, msg);
+    }
+
+    fn check_profile_stream(&mut self, stream: Option<&CuStream>) -> TRTResult<()> {
+        let (switch_stream, switched) = match self.profile_switch.as_ref() {
+            Some(switch) => switch,
+            None => return Ok(()),
+        };
+        let stream = stream.unwrap_or(&self.stream);
+        if unsafe { switch_stream.get_raw() == stream.get_raw() } {
+            return Ok(());
+        }
+        if !switched.query()? {
+            return Err(TRTError::ProfileStreamMismatch);
+        }
+        self.profile_switch = None;
+
+        Ok(())
+    }
+
+    // Enqueueing on another stream than the IO tensors were allocated on
+    // races with their stream-ordered allocation unless the caller
+    // synchronizes the two, so debug builds report it once per allocation.
+    #[cfg(debug_assertions)]
+    fn check_alloc_stream(&mut self, stream: Option<&CuStream>) {
+        let alloc_stream = match self.alloc_stream.as_ref() {
+            Some(alloc_stream) => alloc_stream,
+            None => return,
+        };
+        let stream = stream.unwrap_or(&self.stream);
+        if unsafe { alloc_stream.get_raw() != stream.get_raw() } {
+            self.alloc_stream = None;
+            self.log_warning(
+                "Inference enqueued on a different stream than the IO tensors were allocated on",
+            );
+        }
+    }
+
+    fn check_cuda_context(&self) -> TRTResult<()> {
+        let current = CuContext::current()?;
+        if unsafe { current.get_raw() != self.cu_context.get_raw() } {
+            return Err(TRTError::WrongCudaContext);
+        }
+
+        Ok(())
+    }
+
+    fn io_names(&self, mode: TensorIOMode) -> TRTResult<Vec<&str>> {
+        let engine = self.engine()?;
+
+        let names = (0..engine.get_num_io_tensors())
+            .map(|i| engine.get_io_tensor_name(i))
+            .filter(|name| engine.get_tensor_io_mode(name) == mode)
+            .collect();
+
+        Ok(names)
+    }
+
+    fn engine(&self) -> TRTResult<&CudaEngine> {
+        self.engine.as_ref().ok_or(TRTError::EngineCreationError)
+    }
+
+    fn context(&self) -> TRTResult<&ExecutionContext> {
+        self.context.as_ref().ok_or(TRTError::ExecutionContextNotInitialized)
+    }
+}
+
+#[cfg(feature = "tokio")]
+struct EventWaiter {
+    event: CuEvent,
+    cu_context: CuContext,
+}
+
+// SAFETY: CUDA events and contexts are not tied to the host thread that
+// created them.
+#[cfg(feature = "tokio")]
+unsafe impl Send for EventWaiter {}
+
+#[cfg(feature = "tokio")]
+impl EventWaiter {
+    fn wait(self) -> TRTResult<()> {
+        let _guard = self.cu_context.guard()?;
+        let event = self.event;
+        event.synchronize()?;
+
+        Ok(())
+    }
+}
+
+/// Registers the standard plugins once per process. They log through the
+/// logger they were registered with, so it is never freed.
+fn init_standard_plugins() -> TRTResult<()> {
+    static INITIALIZED: OnceLock<bool> = OnceLock::new();
+
+    let initialized = *INITIALIZED.get_or_init(|| {
+        let logger = Box::leak(Box::new(Logger::new()));
+        plugin::init_standard_plugins(logger, "")
+    });
+
+    if initialized {
+        Ok(())
+    } else {
+        Err(TRTError::PluginInitError)
+    }
+}
+
+fn check_profile_index(engine: &CudaEngine, profile_index: i32) -> TRTResult<()> {
+    let num_profiles = engine.get_num_optimization_profiles();
+    if profile_index < 0 || profile_index >= num_profiles {
+        return Err(TRTError::ProfileValidationError {
+            profile_index,
+            tensor_name: String::new(),
+            reason: format!("the engine has {} profiles", num_profiles),
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks `shape` against the profile's min and max shapes for input `name`.
+/// Unspecified (negative) dimensions are skipped.
+/// Sets the shapes and addresses of `tensors` on `context`, whose active
+/// profile is `profile_index`.
+fn bind_io_tensors(
+    engine: &CudaEngine,
+    context: &mut ExecutionContext,
+    profile_index: i32,
+    tensors: &IOTensors,
+) -> TRTResult<()> {
+    for (name, tensor) in &tensors.inputs {
+        check_profile_shape(engine, profile_index, name, &tensor.shape().0)?;
+        if !context.set_input_shape(name, tensor.shape().0.as_slice()) {
+            return Err(shape_error(engine, context, name, tensor.shape()));
+        }
+    }
+    for (name, tensor) in tensors.inputs.iter().chain(tensors.outputs.iter()) {
+        if !context.set_tensor_address(name, unsafe { tensor.get_raw_ptr() }) {
+            return Err(TRTError::InvalidAddress);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `profile_index` exists and that its input shapes satisfy
+/// min <= opt <= max.
+fn check_profile_consistency(engine: &CudaEngine, profile_index: i32) -> TRTResult<()> {
+    check_profile_index(engine, profile_index)?;
+
+    // only inputs have profile shapes
+    for i in 0..engine.get_num_io_tensors() {
+        let name = engine.get_io_tensor_name(i);
+        if !engine.get_tensor_io_mode(name).is_input() {
+            continue;
+        }
+
+        let min = engine.get_profile_shape(name, profile_index, OptProfileSelector::MIN);
+        let opt = engine.get_profile_shape(name, profile_index, OptProfileSelector::OPT);
+        let max = engine.get_profile_shape(name, profile_index, OptProfileSelector::MAX);
+        let consistent = min.len() == opt.len()
+            && opt.len() == max.len()
+            && (0..min.len()).all(|j| min[j] <= opt[j] && opt[j] <= max[j]);
+        if !consistent {
+            return Err(TRTError::ProfileValidationError {
+                profile_index,
+                tensor_name: name.to_string(),
+                reason: format!("inconsistent shapes min {:?}, opt {:?}, max {:?}", min, opt, max),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn check_profile_shape(
+    engine: &CudaEngine,
+    profile_index: i32,
+    name: &str,
+    shape: &[i32],
+) -> TRTResult<()> {
+    let min = engine.get_profile_shape(name, profile_index, OptProfileSelector::MIN);
+    let max = engine.get_profile_shape(name, profile_index, OptProfileSelector::MAX);
+    let error = |reason| TRTError::ProfileValidationError {
+        profile_index,
+        tensor_name: name.to_string(),
+        reason,
+    };
+
+    if shape.len() != min.len() || shape.len() != max.len() {
+        return Err(error(format!("rank {} does not match profile rank {}", shape.len(), min.len())));
+    }
+    for (i, &dim) in shape.iter().enumerate() {
+        if dim >= 0 && (dim < min[i] || dim > max[i]) {
+            return Err(error(format!("dimension {} is {}, outside [{}, {}]", i, dim, min[i], max[i])));
+        }
+    }
+
+    Ok(())
+}
+
+/// The scratch memory an execution context needs to run under whichever of
+/// the engine's optimization profiles is selected, i.e. the largest of them.
+pub(crate) fn required_device_memory(engine: &CudaEngine) -> usize {
+    (0..engine.get_num_optimization_profiles())
+        .map(|profile_index| engine.device_memory_size_for_profile(profile_index))
+        .fold(engine.device_memory_size(), usize::max)
+}
+
+/// The error for an input shape the execution context rejected, carrying the
+/// bounds of the active profile.
+fn shape_error(engine: &CudaEngine, context: &ExecutionContext, name: &str, got: &Shape) -> TRTError {
+    let profile_index = context.get_optimization_profile();
+
+    TRTError::ShapeError {
+        got: got.clone(),
+        name: name.to_string(),
+        min: Shape(engine.get_profile_shape(name, profile_index, OptProfileSelector::MIN)),
+        max: Shape(engine.get_profile_shape(name, profile_index, OptProfileSelector::MAX)),
+    }
+}
+
+fn format_mismatch(info: &TensorInfo, format: TensorFormat) -> Option<String> {
+    if info.format == format {
+        return None;
+    }
+
+    Some(format!(
+        "Tensor '{}' is bound as {} but the engine expects {}",
+        info.name, format, info.format,
+    ))
+}
+
+// Writes next to `path` and renames, so readers never see a partial plan.
+fn write_plan(path: &Path, plan: &[u8]) -> TRTResult<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    fs::write(&tmp_path, plan)?;
+    fs::rename(&tmp_path, path)?;
+
+    Ok(())
+}
+
+// Nearest-rank percentile of ascending, non-empty `sorted`.
+fn percentile(sorted: &[f32], pct: usize) -> f32 {
+    let rank = (sorted.len() * pct).div_ceil(100);
+    sorted[rank.max(1) - 1]
+}
+
+/// Bytes needed for `shape` in a vectorized format, where the vectorized
+/// dimension is padded up to a multiple of `components_per_element`.
+fn padded_byte_size(
+    shape: &Shape,
+    vectorized_dim: i32,
+    components_per_element: i32,
+    bytes_per_component: i32,
+) -> usize {
+    let components = components_per_element.max(1) as usize;
+
+    let numel = shape.0
+        .iter()
+        .enumerate()
+        .map(|(i, &dim)| {
+            let dim = dim as usize;
+            if i as i32 == vectorized_dim {
+                dim.div_ceil(components) * components
+            } else {
+                dim
+            }
+        })
+        .product::<usize>();
+
+    numel * bytes_per_component as usize
+}
+
+/// Bytes IO tensor `name` occupies with `shape` in the engine's format.
+fn io_byte_size(engine: &CudaEngine, name: &str, shape: &Shape) -> usize {
+    match engine.get_tensor_vectorized_dim(name) {
+        vectorized_dim if vectorized_dim < 0 => {
+            engine.get_tensor_dtype(name).get_byte_size(shape.size())
+        }
+        vectorized_dim => padded_byte_size(
+            shape,
+            vectorized_dim,
+            engine.get_tensor_components_per_element(name),
+            engine.get_tensor_bytes_per_component(name),
+        ),
+    }
+}
+
+pub(crate) fn allocate_tensors(
+    engine: &CudaEngine,
+    context: &mut ExecutionContext,
+    max_shape_dict: &HashMap<&str, &Shape>,
+    stream: &CuStream,
+    inputs: &mut HashMap<String, Tensor>,
+    outputs: &mut HashMap<String, Tensor>,
+) -> TRTResult<()> {
+    let num_io_tensors = engine.get_num_io_tensors();
+
+    // inputs first, so outputs missing from `max_shape_dict` can take the
+    // shapes the context infers from them
+    let mut names = (0..num_io_tensors)
+        .map(|i| engine.get_io_tensor_name(i))
+        .collect::<Vec<_>>();
+    names.sort_by_key(|name| !engine.get_tensor_io_mode(name).is_input());
+
+    for name in names {
+        let shape = if engine.get_tensor_io_mode(name).is_input() {
+            engine.get_tensor_shape(name)
+        } else {
+            context.get_tensor_shape(name)
+        };
+        let shape = Shape(shape);
+        let shape = match max_shape_dict.get(name) {
+            Some(max_shape) => max_shape,
+            None => &shape,
+        };
+        if shape.0.iter().any(|&dim| dim < 0) {
+            return Err(TRTError::InvalidShape(shape.0.clone()));
+        }
+        if engine.get_tensor_io_mode(name).is_input() {
+            check_profile_shape(engine, context.get_optimization_profile(), name, &shape.0)?;
+            if !context.set_input_shape(name, shape.0.as_slice()) {
+                return Err(shape_error(engine, context, name, shape));
+            }
+        }
+
+        let dtype = engine.get_tensor_dtype(name);
+        let tensor = if engine.get_tensor_vectorized_dim(name) < 0 {
+            Tensor::empty(&shape, dtype, stream)?
+        } else {
+            let size = io_byte_size(engine, name, shape);
+            Tensor::from_memory(DeviceMemory::new(size, stream)?, shape, dtype)
+        };
+        debug_assert_eq!(tensor.dtype(), engine.get_tensor_dtype(name));
+        let ptr = unsafe { tensor.get_raw_ptr() };
+        if engine.get_tensor_io_mode(name).is_input() {
+            inputs.insert(name.to_string(), tensor);
+        } else {
+            outputs.insert(name.to_string(), tensor);
+        }
+        if !context.set_tensor_address(name, ptr as _) {
+            return Err(TRTError::InvalidAddress);
+        }
+        debug_assert_eq!(context.get_tensor_address(name), ptr);
+    }
+
+    // TODO: validate shapes, (batch size)
+
+    Ok(())
+}
+
+// Drops what the engine's own recorder holds from earlier calls, so that
+// `recorded` only sees the errors of the next one.
+fn clear_recorded(recorder: Option<&ErrorRecorder>) {
+    if let Some(recorder) = recorder {
+        recorder.clear();
+    }
+}
+
+// With a recorder set, TensorRT reports why a call failed to it instead of
+// the logger; its errors replace `err` if that is only the generic error of
+// a failed TensorRT call. Errors found on the Rust side are kept.
+fn recorded(recorder: Option<&ErrorRecorder>, err: TRTError) -> TRTError {
+    let generic = matches!(
+        err,
+        TRTError::ExecutionContextCreationError
+            | TRTError::EnqueueError(_)
+            | TRTError::ResetShapesError
+    );
+    match recorder.map(ErrorRecorder::take_errors) {
+        Some(errors) if generic && !errors.is_empty() => TRTError::Recorded(errors),
+        _ => err,
+    }
+}
+
+pub(crate) fn enqueue(
+    engine: &CudaEngine,
+    context: &mut ExecutionContext,
+    inputs: &mut HashMap<String, Tensor>,
+    feed_dict: &HashMap<&str, &Tensor>,
+    stream: &CuStream,
+) -> TRTResult<()> {
+    for (name, input_tensor) in feed_dict {
+        let tensor = match inputs.get_mut(name.to_owned()) {
+            Some(tensor) => tensor,
+            None => continue,
+        };
+        let new_shape = input_tensor.shape();
+        if tensor.shape() != new_shape {
+            unsafe { tensor.reset_shape(new_shape)? };
+            if !context.set_input_shape(name, new_shape.0.as_slice()) {
+                return Err(shape_error(engine, context, name, new_shape));
+            }
+        }
+        // a rebound input already reads from the fed tensor
+        if unsafe { tensor.get_raw_ptr() == input_tensor.get_raw_ptr() } {
+            continue;
+        }
+        tensor.copy_from(input_tensor, Some(stream))?;
+    }
+
+    // TODO: validate shapes, (batch size)
+
+    if !context.enqueue_v3(stream) {
+        return Err(TRTError::EnqueueError(engine.get_name().to_string()));
+    }
+
+    Ok(())
+}
+
+impl Drop for TRTEngine {
+    // Tear down in dependency order: the context and the tensors bound to it,
+    // then the engine, and the runtime (which owns the logger) last.
+    fn drop(&mut self) {
+        self.graph = None;
+
+        if let Some(context) = self.context.take() {
             std::mem::drop(context);
         }
 
-        if let Some(engine) = self.engine.take() {
-            std::mem::drop(engine);
+        self.inputs.clear();
+        self.outputs.clear();
+        self.parked_tensors.clear();
+
+        if let Some(engine) = self.engine.take() {
+            std::mem::drop(engine);
+        }
+
+        if let Some(runtime) = self.runtime.take() {
+            std::mem::drop(runtime);
+        }
+
+        if let Some(runtime) = self.shared_runtime.take() {
+            std::mem::drop(runtime);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cuda_rs::device::CuDevice;
+    use std::{ffi::c_void, sync::mpsc};
+    use tensorrt_rs_sys::{
+        builder::{Builder, BuilderConfig, BuilderFlag, NetworkDefinition},
+        runtime::HostMemory,
+    };
+
+    const ENGINE_PATH: &str = "../tmp/pp-ocr-v4-det-fp16.engine";
+
+    // Skips the test where the engine file, and so the GPU it was built
+    // for, is missing.
+    fn with_stream<F>(f: F) -> TRTResult<()>
+    where
+        F: FnOnce(&CuStream) -> TRTResult<()>,
+    {
+        if !Path::new(ENGINE_PATH).exists() {
+            println!("Engine file not found! Skip test!");
+            return Ok(());
+        }
+
+        cuda_rs::init()?;
+
+        let device = CuDevice::new(0)?;
+        let ctx = device.retain_primary_context()?;
+        let _guard = ctx.guard()?;
+        let stream = CuStream::new()?;
+
+        f(&stream)
+    }
+
+    fn with_engine<F>(f: F) -> TRTResult<()>
+    where
+        F: FnOnce(&mut TRTEngine, &CuStream) -> TRTResult<()>,
+    {
+        with_stream(|stream| {
+            let mut engine = TRTEngine::new(&ENGINE_PATH, stream)?;
+
+            f(&mut engine, stream)
+        })
+    }
+
+    // Builds the network `define` fills in, or returns `None` if `define`
+    // gives up, e.g. on a layer this TensorRT lacks.
+    fn build_plan<D>(define: D) -> Option<HostMemory>
+    where
+        D: FnOnce(&NetworkDefinition, &mut BuilderConfig) -> Option<()>,
+    {
+        let mut builder = Builder::new().unwrap();
+        let mut config = builder.create_builder_config().unwrap();
+        let mut network = builder.create_network().unwrap();
+        define(&network, &mut config)?;
+
+        Some(builder.build_serialized_network(&mut network, &mut config).unwrap())
+    }
+
+    fn with_plan<D, F>(define: D, f: F) -> TRTResult<()>
+    where
+        D: FnOnce(&NetworkDefinition, &mut BuilderConfig),
+        F: FnOnce(&[u8], &CuStream) -> TRTResult<()>,
+    {
+        with_stream(|stream| {
+            let plan = build_plan(|network, config| {
+                define(network, config);
+                Some(())
+            })
+            .unwrap();
+
+            f(plan.as_bytes(), stream)
+        })
+    }
+
+    fn activate_and_allocate(engine: &mut TRTEngine) -> TRTResult<()> {
+        let input_shape = Shape(vec![1, 3, 352, 640]);
+        let output_shape = Shape(vec![1, 1, 352, 640]);
+        let max_shape_dict = HashMap::from([
+            ("x", &input_shape),
+            ("sigmoid_0.tmp_0", &output_shape),
+        ]);
+
+        engine.activate()?;
+        engine.allocate_io_tensors(&max_shape_dict, None)
+    }
+
+    extern "C" {
+        fn cuLaunchHostFunc(
+            stream: *mut c_void,
+            func: extern "C" fn(*mut c_void),
+            data: *mut c_void,
+        ) -> i32;
+    }
+
+    // Holds `stream` until the returned sender sends or drops, e.g. once a
+    // test panics, so work queued behind it stays pending meanwhile.
+    fn block_stream(stream: &CuStream) -> mpsc::Sender<()> {
+        extern "C" fn wait(data: *mut c_void) {
+            let release = unsafe { Box::from_raw(data as *mut mpsc::Receiver<()>) };
+            let _ = release.recv();
+        }
+
+        let (sender, release) = mpsc::channel();
+        let data = Box::into_raw(Box::new(release)) as *mut c_void;
+        let res = unsafe { cuLaunchHostFunc(stream.get_raw() as _, wait, data) };
+        assert_eq!(res, 0, "cuLaunchHostFunc failed");
+
+        sender
+    }
+
+    #[test]
+    fn test_padded_byte_size() {
+        // kHALF in kCHW2: 2 bytes per component, channels padded to even
+        let shape = Shape(vec![1, 3, 4, 4]);
+        assert_eq!(padded_byte_size(&shape, 1, 2, 2), 4 * 4 * 4 * 2);
+        // kINT8 in kCHW32
+        assert_eq!(padded_byte_size(&shape, 1, 32, 1), 32 * 4 * 4);
+        // already aligned
+        let shape = Shape(vec![1, 64, 2, 2]);
+        assert_eq!(padded_byte_size(&shape, 1, 32, 1), 64 * 2 * 2);
+    }
+
+    #[test]
+    fn test_vectorized_allocation() -> TRTResult<()> {
+        // a HALF input in kCHW2, whose 3 channels are padded to 4
+        let define = |network: &NetworkDefinition, config: &mut BuilderConfig| {
+            config.set_flag(BuilderFlag::FP16);
+            let input = network.add_input("x", DataType::HALF, &[1, 3, 8, 8]).unwrap();
+            network.set_tensor_allowed_formats(input, &[TensorFormat::CHW2]);
+            let output = network.add_identity(input).unwrap();
+            network.set_tensor_name(output, "y");
+            network.set_tensor_allowed_formats(output, &[TensorFormat::LINEAR]);
+            network.mark_output(output);
+        };
+
+        with_plan(define, |plan, stream| {
+            let mut engine = TRTEngine::builder().build_from_bytes(plan, stream)?;
+            engine.activate()?;
+            engine.allocate_io_tensors(&HashMap::new(), None)?;
+
+            let x = engine.tensor_info("x")?;
+            assert_eq!(x.format, TensorFormat::CHW2);
+            assert_eq!(x.vectorized_dim, 1);
+            let tensor = &engine.inputs["x"];
+            assert_eq!(tensor.byte_size(), 3 * 8 * 8 * 2);
+            assert_eq!(tensor.get_memory().size, 4 * 8 * 8 * 2);
+
+            let y = engine.tensor_info("y")?;
+            assert_eq!(y.vectorized_dim, -1);
+            let tensor = &engine.outputs["y"];
+            assert_eq!(tensor.get_memory().size, tensor.byte_size());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_profile_shapes() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            assert_eq!(engine.num_optimization_profiles()?, 1);
+
+            let shapes = engine.profile_shapes(0)?;
+            assert_eq!(shapes.len(), engine.input_names()?.len());
+            for (name, ProfileShapes { min, opt, max }) in shapes.iter() {
+                assert!(!min.0.is_empty(), "{}", name);
+                assert_eq!(min.0.len(), opt.0.len(), "{}", name);
+                assert_eq!(opt.0.len(), max.0.len(), "{}", name);
+                for i in 0..min.0.len() {
+                    assert!(min.0[i] <= opt.0[i] && opt.0[i] <= max.0[i], "{}", name);
+                }
+            }
+
+            assert!(matches!(
+                engine.profile_shapes(1),
+                Err(TRTError::ProfileValidationError { profile_index: 1, .. })
+            ));
+
+            engine.activate()?;
+            assert_eq!(engine.current_profile()?, 0);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_validate_profile() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            engine.validate_profile(0)?;
+            assert!(matches!(
+                engine.validate_profile(-1),
+                Err(TRTError::ProfileValidationError { profile_index: -1, .. })
+            ));
+            let num_profiles = engine.num_optimization_profiles()?;
+            assert!(engine.validate_profile(num_profiles).is_err());
+
+            activate_and_allocate(engine)?;
+            engine.validate_profile(0)?;
+            engine.switch_profile(0, None)?;
+            assert!(engine.switch_profile(-1, None).is_err());
+
+            // out of range shapes are rejected before reaching TensorRT
+            let max = &engine.profile_shapes(0)?["x"].max;
+            let too_large = Shape(max.0.iter().map(|dim| dim + 1).collect());
+            let max_shape_dict = HashMap::from([("x", &too_large)]);
+            assert!(matches!(
+                engine.allocate_io_tensors(&max_shape_dict, None),
+                Err(TRTError::ProfileValidationError { .. })
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_validate_engine_compatibility() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            engine.validate_engine_compatibility(0)?;
+            assert!(engine.validate_engine_compatibility(u32::MAX).is_err());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_save_and_reload() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            fn io_spec(engine: &TRTEngine) -> TRTResult<Vec<(String, Vec<i32>, DataType)>> {
+                let engine = engine.engine()?;
+                Ok((0..engine.get_num_io_tensors())
+                    .map(|i| engine.get_io_tensor_name(i))
+                    .map(|name| {
+                        let shape = engine.get_tensor_shape(name);
+                        (name.to_string(), shape, engine.get_tensor_dtype(name))
+                    })
+                    .collect())
+            }
+
+            engine.activate()?;
+            let spec = io_spec(engine)?;
+
+            let path = std::env::temp_dir().join("tensorrt-rs-test-save.engine");
+            engine.save_to_file(&path)?;
+            engine.reload_engine(&path)?;
+            assert_eq!(io_spec(engine)?, spec);
+            assert!(engine.context.is_some());
+
+            let bytes = fs::read(&path)?;
+            engine.reload_from_bytes(bytes.as_slice())?;
+            assert_eq!(io_spec(engine)?, spec);
+
+            let plan = engine.serialize()?;
+            engine.reload_from_bytes(&plan)?;
+            assert_eq!(io_spec(engine)?, spec);
+
+            // a broken plan keeps the current engine and context
+            assert!(engine.reload_from_bytes(&plan[..plan.len() / 2]).is_err());
+            assert_eq!(io_spec(engine)?, spec);
+            assert!(engine.context.is_some());
+
+            fs::remove_file(&path)?;
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_wrong_cuda_context() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            activate_and_allocate(engine)?;
+
+            let other = CuContext::new(&CuDevice::new(0)?)?;
+            let _guard = other.guard()?;
+            assert!(matches!(
+                engine.inference(&HashMap::new(), None),
+                Err(TRTError::WrongCudaContext)
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_tensor_address_map() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            activate_and_allocate(engine)?;
+
+            let addresses = engine.tensor_address_map()?;
+            assert_eq!(addresses.len(), engine.inputs.len() + engine.outputs.len());
+            for (name, tensor) in engine.inputs.iter().chain(engine.outputs.iter()) {
+                assert_eq!(addresses[name], unsafe { tensor.get_raw_ptr() });
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_tensor_address() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            assert_eq!(engine.tensor_address("x"), None);
+
+            activate_and_allocate(engine)?;
+            for (name, tensor) in engine.inputs.iter().chain(engine.outputs.iter()) {
+                assert_eq!(engine.tensor_address(name), Some(unsafe { tensor.get_raw_ptr() }));
+            }
+            assert_eq!(engine.tensor_address("missing"), None);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_set_input_shapes() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            let max = &engine.profile_shapes(0)?["x"].max;
+            if max.0[0] < 4 {
+                println!("Engine does not support batch 4! Skip test!");
+                return Ok(());
+            }
+
+            let input_shape = Shape(vec![4, 3, 352, 640]);
+            let output_shape = Shape(vec![4, 1, 352, 640]);
+            let max_shape_dict = HashMap::from([
+                ("x", &input_shape),
+                ("sigmoid_0.tmp_0", &output_shape),
+            ]);
+            engine.activate()?;
+            engine.allocate_io_tensors(&max_shape_dict, None)?;
+            let addresses = engine.tensor_address_map()?;
+
+            let input = Tensor::empty(&input_shape, DataType::FLOAT, stream)?;
+            let outputs = engine.inference(&HashMap::from([("x", &input)]), None)?;
+            assert_eq!(outputs["sigmoid_0.tmp_0"].shape(), &output_shape);
+
+            let small_shape = Shape(vec![2, 3, 352, 640]);
+            engine.set_input_shapes(&HashMap::from([("x", &small_shape)]))?;
+            assert_eq!(engine.outputs["sigmoid_0.tmp_0"].shape(), &Shape(vec![2, 1, 352, 640]));
+
+            let input = Tensor::empty(&small_shape, DataType::FLOAT, stream)?;
+            let outputs = engine.inference(&HashMap::from([("x", &input)]), None)?;
+            assert_eq!(outputs["sigmoid_0.tmp_0"].shape(), &Shape(vec![2, 1, 352, 640]));
+            stream.synchronize()?;
+
+            // same allocations throughout
+            assert_eq!(engine.tensor_address_map()?, addresses);
+
+            let large_shape = Shape(vec![8, 3, 352, 640]);
+            assert!(matches!(
+                engine.set_input_shapes(&HashMap::from([("x", &large_shape)])),
+                Err(TRTError::InvalidShape(_))
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_set_input_shapes_before_allocation() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            let shapes = engine.profile_shapes(0)?["x"].clone();
+            if shapes.min == shapes.max {
+                println!("Engine has static input shapes! Skip test!");
+                return Ok(());
+            }
+
+            engine.activate()?;
+            assert!(!engine.all_input_dimensions_specified()?);
+
+            engine.set_input_shapes(&HashMap::from([("x", &shapes.opt)]))?;
+            assert!(engine.all_input_dimensions_specified()?);
+
+            let too_large = Shape(shapes.max.0.iter().map(|dim| dim + 1).collect());
+            match engine.set_input_shapes(&HashMap::from([("x", &too_large)])) {
+                Err(TRTError::ShapeError { name, .. }) => assert_eq!(name, "x"),
+                res => panic!("unexpected result: {:?}", res),
+            }
+            assert!(matches!(
+                engine.set_input_shapes(&HashMap::from([("sigmoid_0.tmp_0", &shapes.opt)])),
+                Err(TRTError::UnknownTensor(_))
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_allocate_inferred_output_shapes() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            engine.activate()?;
+
+            let input_shape = Shape(vec![1, 3, 352, 640]);
+            engine.allocate_io_tensors(&HashMap::from([("x", &input_shape)]), None)?;
+            assert_eq!(engine.outputs["sigmoid_0.tmp_0"].shape(), &Shape(vec![1, 1, 352, 640]));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_bind_tensor_format_mismatch() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+
+            let info = engine.tensor_info("x")?;
+            assert!(info.io_mode.is_input());
+            assert_eq!(engine.tensor_infos()?.len(), 2);
+            assert!(matches!(engine.tensor_info("y"), Err(TRTError::UnknownTensor(_))));
+
+            let mismatched = if info.format == TensorFormat::HWC8 {
+                TensorFormat::LINEAR
+            } else {
+                TensorFormat::HWC8
+            };
+            assert!(format_mismatch(&info, info.format).is_none());
+            let msg = format_mismatch(&info, mismatched).unwrap();
+            assert!(msg.contains(&info.format.to_string()));
+
+            let warnings = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+            let captured = warnings.clone();
+            engine.logger().unwrap().set_callback(move |severity, msg: &str| {
+                if severity == Severity::Warning {
+                    captured.lock().unwrap().push(msg.to_string());
+                }
+            });
+
+            let input_shape = Shape(vec![1, 3, 352, 640]);
+            let input = Tensor::empty(&input_shape, info.dtype, stream)?;
+            let ptr = unsafe { input.get_raw_ptr() };
+            engine.bind_tensor("x", input, mismatched)?;
+            assert_eq!(engine.tensor_address_map()?["x"], ptr);
+
+            // the mismatch is only checked in debug builds
+            let warnings = warnings.lock().unwrap();
+            if cfg!(debug_assertions) {
+                assert!(warnings.contains(&msg));
+            } else {
+                assert!(warnings.is_empty());
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_bind_undersized_output() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+
+            let name = "sigmoid_0.tmp_0";
+            let info = engine.tensor_info(name)?;
+            let smaller = Tensor::empty(&Shape(vec![1, 1, 320, 640]), info.dtype, stream)?;
+            let required = info.dtype.get_byte_size(352 * 640);
+            assert!(matches!(
+                engine.bind_tensor(name, smaller, info.format),
+                Err(TRTError::BufferSizeMismatch(expected, _)) if expected == required
+            ));
+
+            let output = Tensor::empty(&Shape(vec![1, 1, 352, 640]), info.dtype, stream)?;
+            engine.bind_tensor(name, output, info.format)?;
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_log_without_runtime() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            assert!(engine.logger().is_some());
+            assert!(engine.try_log(Severity::Info, "with runtime"));
+            engine.log_info("info");
+            engine.log_warning("warning");
+            engine.log_error("error");
+
+            let runtime = engine.runtime.take();
+            assert!(engine.logger().is_none());
+            assert!(!engine.try_log(Severity::Info, "without runtime"));
+            engine.log_error("no-op");
+            engine.runtime = runtime;
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_required_workspace_size() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            let required = engine.required_workspace_size()?;
+            assert!(required > 0);
+
+            let engine = engine.engine()?;
+            let sizes = (0..engine.get_num_optimization_profiles())
+                .map(|profile_index| engine.device_memory_size_for_profile(profile_index))
+                .collect::<Vec<_>>();
+            assert!(sizes.iter().all(|&size| size > 0 && size <= required));
+            if sizes.len() >= 2 {
+                assert_eq!(sizes.iter().max(), Some(&required));
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_set_stream() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+
+            let input = Tensor::empty(&Shape(vec![1, 3, 352, 640]), DataType::FLOAT, stream)?;
+            stream.synchronize()?;
+
+            let new_stream = CuStream::new()?;
+            engine.set_stream(new_stream.clone())?;
+            let raw = unsafe { engine.stream().as_cu_stream().get_raw() };
+            assert_eq!(raw, unsafe { new_stream.get_raw() });
+            for tensor in engine.inputs.values().chain(engine.outputs.values()) {
+                assert_eq!(
+                    unsafe { tensor.get_memory().stream.get_raw() },
+                    unsafe { new_stream.get_raw() },
+                );
+            }
+
+            engine.inference(&HashMap::from([("x", &input)]), None)?;
+            engine.synchronize()?;
+            assert!(new_stream.query()?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_shared_runtime() -> TRTResult<()> {
+        with_engine(|_, stream| {
+            let data = fs::read(ENGINE_PATH)?;
+            let runtime = SharedRuntime::new()?;
+
+            let mut engines = vec![
+                TRTEngine::from_bytes_with_runtime(&data, &runtime, stream)?,
+                TRTEngine::from_bytes_with_runtime(&data, &runtime, stream)?,
+            ];
+            let input = Tensor::empty(&Shape(vec![1, 3, 352, 640]), DataType::FLOAT, stream)?;
+            for engine in engines.iter_mut() {
+                activate_and_allocate(engine)?;
+                assert!(engine.logger().is_none());
+                assert!(engine.try_log(Severity::Info, "shared runtime"));
+
+                let outputs = engine.inference(&HashMap::from([("x", &input)]), None)?;
+                assert_eq!(outputs["sigmoid_0.tmp_0"].shape(), &Shape(vec![1, 1, 352, 640]));
+            }
+            stream.synchronize()?;
+
+            // the runtime outlives the engines it deserialized
+            engines.clear();
+            runtime.deserialize(&data)?;
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_io_counts() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            let inputs = engine.input_names()?;
+            let outputs = engine.output_names()?;
+            assert_eq!(engine.input_count()?, inputs.len() as i32);
+            assert_eq!(engine.output_count()?, outputs.len() as i32);
+            assert_eq!(engine.io_count()?, (inputs.len() + outputs.len()) as i32);
+
+            assert!(engine.has_input("x")?);
+            assert!(!engine.has_output("x")?);
+            assert!(engine.has_output("sigmoid_0.tmp_0")?);
+            assert!(!engine.has_input("missing")? && !engine.has_output("missing")?);
+
+            engine.engine = None;
+            assert!(matches!(engine.io_count(), Err(TRTError::EngineCreationError)));
+            assert!(matches!(engine.has_input("x"), Err(TRTError::EngineCreationError)));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lazy_alloc() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            let input_shape = Shape(vec![1, 3, 352, 640]);
+            let data = (0..input_shape.size()).map(|i| (i % 255) as f32 / 255.0).collect::<Vec<_>>();
+            let input = Tensor::from_host_slice(&data, &input_shape, stream)?;
+            let feed_dict = HashMap::from([("x", &input)]);
+
+            let read_output = |engine: &TRTEngine| -> TRTResult<Vec<u8>> {
+                let output = &engine.outputs["sigmoid_0.tmp_0"];
+                let mut bytes = vec![0u8; output.byte_size()];
+                output.to_host_bytes_into(&mut bytes, Some(stream))?;
+                Ok(bytes)
+            };
+
+            activate_and_allocate(engine)?;
+            engine.inference(&feed_dict, None)?;
+            let expected = read_output(engine)?;
+
+            let mut lazy = TRTEngine::new(&ENGINE_PATH, stream)?;
+            lazy.set_lazy_alloc(true);
+            lazy.activate()?;
+            let outputs = lazy.inference(&feed_dict, None)?;
+            assert_eq!(outputs["sigmoid_0.tmp_0"].shape(), &Shape(vec![1, 1, 352, 640]));
+            assert_eq!(read_output(&lazy)?, expected);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_outputs() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            activate_and_allocate(engine)?;
+
+            let outputs = engine.outputs().collect::<HashMap<_, _>>();
+            assert_eq!(outputs.len(), engine.output_count()? as usize);
+            assert!(outputs.contains_key("sigmoid_0.tmp_0"));
+            assert!(!outputs.contains_key("x"));
+
+            assert!(engine.output("sigmoid_0.tmp_0").is_some());
+            assert!(engine.output("x").is_none());
+            assert!(engine.output("missing").is_none());
+            assert_eq!(
+                engine.output_map().get_shape("sigmoid_0.tmp_0")?,
+                &Shape(vec![1, 1, 352, 640])
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_inference_returns_outputs_only() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+
+            let input = Tensor::empty(&Shape(vec![1, 3, 352, 640]), DataType::FLOAT, stream)?;
+            let feed_dict = HashMap::from([("x", &input)]);
+            let names = engine
+                .inference(&feed_dict, None)?
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>();
+            assert!(!names.is_empty());
+            assert!(!names.contains(&"x".to_string()));
+            for name in names {
+                assert!(engine.has_output(&name)?);
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_inference_with_output_stream() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            let input_shape = Shape(vec![1, 3, 352, 640]);
+            let data = (0..input_shape.size()).map(|i| (i % 255) as f32 / 255.0).collect::<Vec<_>>();
+            let input = Tensor::from_host_slice(&data, &input_shape, stream)?;
+            let feed_dict = HashMap::from([("x", &input)]);
+
+            activate_and_allocate(engine)?;
+            let expected = engine.inference(&feed_dict, None)?["sigmoid_0.tmp_0"]
+                .to_host_vec::<f32>(Some(stream))?;
+
+            let compute_stream = CuStream::new()?;
+            let output_stream = CuStream::new()?;
+            let outputs =
+                engine.inference_with_output_stream(&feed_dict, &compute_stream, &output_stream)?;
+            let output = outputs["sigmoid_0.tmp_0"].to_host_vec::<f32>(Some(&output_stream))?;
+            assert_eq!(output, expected);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_builder_with_logger() -> TRTResult<()> {
+        with_engine(|_, stream| {
+            let mut logger = Logger::with_min_severity(Severity::Info);
+            logger.set_capture(true);
+            let mut engine = TRTEngine::builder().logger(logger).build(&ENGINE_PATH, stream)?;
+
+            let captured = engine.logger().unwrap().take_captured();
+            assert!(captured.iter().any(|msg| msg.contains("Loaded engine size")));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_builder_with_error_recorder() -> TRTResult<()> {
+        with_engine(|_, stream| {
+            let recorder = ErrorRecorder::new();
+            let res = TRTEngine::builder()
+                .error_recorder(recorder.clone())
+                .build_from_bytes(b"not an engine", stream);
+            let num_errors = match res {
+                Err(TRTError::Recorded(errors)) => errors.len(),
+                _ => panic!("expected the recorded deserialization errors"),
+            };
+            // the shared recorder gets them too
+            assert_eq!(recorder.num_errors(), num_errors);
+            recorder.clear();
+
+            let mut engine = TRTEngine::builder()
+                .error_recorder(recorder.clone())
+                .build(&ENGINE_PATH, stream)?;
+            activate_and_allocate(&mut engine)?;
+            assert!(engine.error_recorder().is_some_and(|recorder| recorder.num_errors() == 0));
+
+            // another engine's calls do not clear what the shared recorder holds
+            let mut other = TRTEngine::builder()
+                .error_recorder(recorder.clone())
+                .build(&ENGINE_PATH, stream)?;
+            assert!(other.reload_from_bytes(b"not an engine").is_err());
+            let num_errors = recorder.num_errors();
+            assert!(num_errors > 0);
+            activate_and_allocate(&mut engine)?;
+            assert_eq!(recorder.num_errors(), num_errors);
+            recorder.clear();
+
+            // errors left from another call do not replace a Rust-side error,
+            // and are cleared before the next TensorRT call
+            let mut runtime = Runtime::new().unwrap();
+            runtime.set_error_recorder(&recorder);
+            assert!(runtime.deserialize(b"not an engine").is_none());
+            let err = recorded(Some(&recorder), TRTError::MissingInput("x".to_string()));
+            assert!(matches!(err, TRTError::MissingInput(_)));
+            assert!(runtime.deserialize(b"not an engine").is_none());
+            clear_recorded(Some(&recorder));
+            let err = recorded(Some(&recorder), TRTError::ResetShapesError);
+            assert!(matches!(err, TRTError::ResetShapesError));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_set_profiler() -> TRTResult<()> {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        struct LayerCount(Arc<AtomicUsize>);
+
+        impl Profiler for LayerCount {
+            fn report_layer_time(&mut self, _layer_name: &str, _ms: f32) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        with_engine(|engine, stream| {
+            let count = Arc::new(AtomicUsize::new(0));
+            assert!(matches!(
+                engine.set_profiler(LayerCount(count.clone())),
+                Err(TRTError::ExecutionContextNotInitialized)
+            ));
+
+            activate_and_allocate(engine)?;
+            engine.set_profiler(LayerCount(count.clone()))?;
+
+            let input_shape = Shape(vec![1, 3, 352, 640]);
+            let input = Tensor::empty(&input_shape, DataType::FLOAT, stream)?;
+            let feed_dict = HashMap::from([("x", &input)]);
+            engine.inference(&feed_dict, None)?;
+            let layers = count.load(Ordering::Relaxed);
+            assert!(layers > 0);
+
+            engine.inference(&feed_dict, None)?;
+            assert_eq!(count.load(Ordering::Relaxed), 2 * layers);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_capture_graph() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            // the capture has to follow the fork to and join from aux streams
+            if engine.engine()?.get_num_aux_streams() == 0 {
+                println!("Engine uses no aux streams! Skip test!");
+                return Ok(());
+            }
+
+            let input_shape = Shape(vec![1, 3, 352, 640]);
+            let data = (0..input_shape.size())
+                .map(|i| (i % 255) as f32 / 255.0)
+                .collect::<Vec<_>>();
+            let input = Tensor::from_host_slice(&data, &input_shape, stream)?;
+            let feed_dict = HashMap::from([("x", &input)]);
+
+            activate_and_allocate(engine)?;
+            assert!(matches!(
+                engine.inference_graph(&feed_dict, None),
+                Err(TRTError::GraphNotCaptured),
+            ));
+
+            let expected = engine.inference(&feed_dict, None)?["sigmoid_0.tmp_0"]
+                .to_host_vec::<f32>(Some(stream))?;
+
+            engine.capture_graph(None)?;
+            for _ in 0..2 {
+                let output = engine.inference_graph(&feed_dict, None)?["sigmoid_0.tmp_0"]
+                    .to_host_vec::<f32>(Some(stream))?;
+                assert_eq!(output, expected);
+            }
+
+            let smaller = Tensor::empty(&Shape(vec![1, 3, 320, 640]), DataType::FLOAT, stream)?;
+            assert!(matches!(
+                engine.inference_graph(&HashMap::from([("x", &smaller)]), None),
+                Err(TRTError::InvalidShape(_)),
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_validate_feed() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+
+            let input = Tensor::empty(&Shape(vec![1, 3, 352, 640]), DataType::FLOAT, stream)?;
+            engine.validate_feed(&HashMap::from([("x", &input)]))?;
+
+            assert!(matches!(
+                engine.validate_feed(&HashMap::new()),
+                Err(TRTError::MissingInput(name)) if name == "x",
+            ));
+
+            let wrong_dtype = Tensor::empty(&Shape(vec![1, 3, 352, 640]), DataType::INT32, stream)?;
+            assert!(matches!(
+                engine.validate_feed(&HashMap::from([("x", &wrong_dtype)])),
+                Err(TRTError::DTypeMismatch),
+            ));
+
+            let mut shape = engine.profile_shapes(0)?["x"].max.clone();
+            *shape.0.last_mut().unwrap() += 1;
+            let too_large = Tensor::empty(&shape, DataType::FLOAT, stream)?;
+            assert!(matches!(
+                engine.validate_feed(&HashMap::from([("x", &too_large)])),
+                Err(TRTError::ProfileValidationError { tensor_name, .. }) if tensor_name == "x",
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_engine_name() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            assert_eq!(engine.engine_name(), engine.engine()?.get_name());
+
+            // no input shapes or addresses are set, so enqueueing fails
+            engine.activate()?;
+            let err = engine.capture_graph(None).unwrap_err();
+            assert!(matches!(&err, TRTError::EnqueueError(name) if *name == engine.engine_name()));
+            assert!(err.to_string().contains(&format!("'{}'", engine.engine_name())));
+
+            engine.engine = None;
+            assert_eq!(engine.engine_name(), "");
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_update_graph_addresses() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            let input_shape = Shape(vec![1, 3, 352, 640]);
+            let upload = |offset: usize| -> TRTResult<Tensor> {
+                let data = (0..input_shape.size())
+                    .map(|i| ((i + offset) % 255) as f32 / 255.0)
+                    .collect::<Vec<_>>();
+                Tensor::from_host_slice(&data, &input_shape, stream)
+            };
+            let (a, b) = (upload(0)?, upload(7)?);
+
+            activate_and_allocate(engine)?;
+            let run = |engine: &mut TRTEngine, input: &Tensor| -> TRTResult<Vec<f32>> {
+                engine.inference(&HashMap::from([("x", input)]), None)?["sigmoid_0.tmp_0"]
+                    .to_host_vec::<f32>(Some(stream))
+            };
+            let expected_a = run(engine, &a)?;
+            let expected_b = run(engine, &b)?;
+            assert_ne!(expected_a, expected_b);
+
+            let replay = |engine: &mut TRTEngine| -> TRTResult<Vec<f32>> {
+                engine.inference_graph(&HashMap::new(), None)?["sigmoid_0.tmp_0"]
+                    .to_host_vec::<f32>(Some(stream))
+            };
+
+            engine.bind_tensor("x", a, TensorFormat::LINEAR)?;
+            engine.capture_graph(None)?;
+            assert_eq!(replay(engine)?, expected_a);
+
+            engine.bind_tensor("x", b, TensorFormat::LINEAR)?;
+            assert!(matches!(replay(engine), Err(TRTError::GraphAddressesOutdated)));
+            engine.update_graph_addresses()?;
+            assert_eq!(replay(engine)?, expected_b);
+
+            Ok(())
+        })
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_inference_async_tokio() -> TRTResult<()> {
+        if !Path::new(ENGINE_PATH).exists() {
+            println!("Engine file not found! Skip test!");
+            return Ok(());
         }
 
-        if let Some(runtime) = self.runtime.take() {
-            std::mem::drop(runtime);
+        // the future borrows the engine, which is not Send, so it cannot go
+        // through tokio::spawn
+        let local = tokio::task::LocalSet::new();
+        local.run_until(async {
+            tokio::task::spawn_local(async {
+                cuda_rs::init()?;
+
+                let device = CuDevice::new(0)?;
+                let ctx = device.retain_primary_context()?;
+                let _guard = ctx.guard()?;
+                let stream = CuStream::new()?;
+                let mut engine = TRTEngine::new(&ENGINE_PATH, &stream)?;
+                activate_and_allocate(&mut engine)?;
+
+                let input_shape = Shape(vec![1, 3, 352, 640]);
+                let data = (0..input_shape.size())
+                    .map(|i| (i % 255) as f32 / 255.0)
+                    .collect::<Vec<_>>();
+                let input = Tensor::from_host_slice(&data, &input_shape, &stream)?;
+                let feed_dict = HashMap::from([("x", &input)]);
+
+                let expected = engine.inference(&feed_dict, None)?["sigmoid_0.tmp_0"]
+                    .to_host_vec::<f32>(Some(&stream))?;
+                let outputs = engine.inference_async_tokio(&feed_dict).await?;
+                let output = outputs["sigmoid_0.tmp_0"].to_host_vec::<f32>(Some(&stream))?;
+                assert_eq!(output, expected);
+
+                Ok::<_, TRTError>(())
+            })
+            .await
+            .unwrap()
+        })
+        .await
+    }
+
+    #[test]
+    fn test_shape_error_bounds() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+
+            let bounds = engine.profile_shapes(0)?.remove("x").unwrap();
+            let mut shape = bounds.max.clone();
+            *shape.0.last_mut().unwrap() += 1;
+            let too_large = Tensor::empty(&shape, DataType::FLOAT, stream)?;
+
+            let err = engine.bind_tensor("x", too_large, TensorFormat::LINEAR).unwrap_err();
+            match &err {
+                TRTError::ShapeError { got, name, min, max } => {
+                    assert_eq!(got, &shape);
+                    assert_eq!(name, "x");
+                    assert_eq!(min, &bounds.min);
+                    assert_eq!(max, &bounds.max);
+                }
+                err => panic!("unexpected error: {}", err),
+            }
+            let msg = err.to_string();
+            assert!(msg.contains(&shape.to_string()) && msg.contains(&bounds.max.to_string()));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_inference_default_stream() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            let input_shape = Shape(vec![1, 3, 352, 640]);
+            let data = (0..input_shape.size()).map(|i| (i % 255) as f32 / 255.0).collect::<Vec<_>>();
+            let input = Tensor::from_host_slice(&data, &input_shape, stream)?;
+            let feed_dict = HashMap::from([("x", &input)]);
+
+            activate_and_allocate(engine)?;
+            let expected = engine.inference(&feed_dict, None)?["sigmoid_0.tmp_0"]
+                .to_host_vec::<f32>(Some(stream))?;
+
+            let output = engine.inference_default_stream(&feed_dict)?["sigmoid_0.tmp_0"]
+                .to_host_vec::<f32>(Some(stream))?;
+            assert_eq!(output, expected);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_new_with_stream_flags() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            let input_shape = Shape(vec![1, 3, 352, 640]);
+            let data = (0..input_shape.size()).map(|i| (i % 255) as f32 / 255.0).collect::<Vec<_>>();
+            let input = Tensor::from_host_slice(&data, &input_shape, stream)?;
+            let feed_dict = HashMap::from([("x", &input)]);
+
+            activate_and_allocate(engine)?;
+            let expected = engine.inference(&feed_dict, None)?["sigmoid_0.tmp_0"]
+                .to_host_vec::<f32>(Some(stream))?;
+
+            let mut engine = TRTEngine::new_with_stream_flags(&ENGINE_PATH, true)?;
+            activate_and_allocate(&mut engine)?;
+            let output = engine.inference(&feed_dict, None)?["sigmoid_0.tmp_0"]
+                .to_host_vec::<f32>(None)?;
+            assert_eq!(output, expected);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_trim_memory() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+
+            let input = Tensor::empty(&Shape(vec![1, 3, 352, 640]), DataType::FLOAT, stream)?;
+            engine.inference(&HashMap::from([("x", &input)]), None)?;
+
+            // grow the pool with a burst of short-lived buffers
+            for _ in 0..4 {
+                Tensor::empty(&Shape(vec![16, 3, 352, 640]), DataType::FLOAT, stream)?;
+            }
+            engine.trim_memory()?;
+
+            let smaller = Tensor::empty(&Shape(vec![1, 3, 320, 640]), DataType::FLOAT, stream)?;
+            let outputs = engine.inference(&HashMap::from([("x", &smaller)]), None)?;
+            assert!(outputs.contains_key("sigmoid_0.tmp_0"));
+            engine.synchronize()?;
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_mixed_precision_output_dtypes() -> TRTResult<()> {
+        // one FLOAT and one HALF output
+        let define = |network: &NetworkDefinition, _: &mut BuilderConfig| {
+            for (name, dtype) in [("a", DataType::FLOAT), ("b", DataType::HALF)] {
+                let input = network.add_input(name, dtype, &[1, 8]).unwrap();
+                let output = network.add_identity(input).unwrap();
+                network.set_tensor_name(output, &format!("{}_out", name));
+                network.mark_output(output);
+            }
+        };
+
+        with_plan(define, |plan, stream| {
+            let runtime = SharedRuntime::new()?;
+            let mut engine = TRTEngine::from_bytes_with_runtime(plan, &runtime, stream)?;
+            engine.activate()?;
+            engine.allocate_io_tensors(&HashMap::new(), None)?;
+
+            let a = Tensor::empty(&Shape(vec![1, 8]), DataType::FLOAT, stream)?;
+            let b = Tensor::empty(&Shape(vec![1, 8]), DataType::HALF, stream)?;
+            let outputs = engine.inference(&HashMap::from([("a", &a), ("b", &b)]), None)?;
+            assert_eq!(outputs["a_out"].dtype(), DataType::FLOAT);
+            assert_eq!(outputs["b_out"].dtype(), DataType::HALF);
+            engine.synchronize()?;
+
+            let wrong = Tensor::empty(&Shape(vec![1, 8]), DataType::FLOAT, stream)?;
+            assert!(matches!(
+                engine.bind_tensor("b_out", wrong, TensorFormat::LINEAR),
+                Err(TRTError::DTypeMismatch)
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_aux_stream_info() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+
+            let info = engine.aux_stream_info()?;
+            assert_eq!(info.expected, engine.engine()?.get_num_aux_streams());
+            assert_eq!(info.configured, 0);
+
+            if info.expected > 1 {
+                engine.logger().unwrap().set_capture(true);
+                engine.set_aux_streams(&[stream])?;
+                assert_eq!(engine.aux_stream_info()?.configured, 1);
+                let captured = engine.logger().unwrap().take_captured();
+                assert!(captured.iter().any(|msg| msg.contains("aux streams configured")));
+            }
+
+            let streams = (0..info.expected)
+                .map(|_| CuStream::new())
+                .collect::<Result<Vec<_>, _>>()?;
+            engine.set_aux_streams(&streams.iter().collect::<Vec<_>>())?;
+            assert_eq!(
+                engine.aux_stream_info()?,
+                AuxStreamInfo { expected: info.expected, configured: info.expected as usize }
+            );
+            // the engine keeps the streams alive
+            drop(streams);
+
+            let input = Tensor::empty(&Shape(vec![1, 3, 352, 640]), DataType::FLOAT, stream)?;
+            engine.inference(&HashMap::from([("x", &input)]), None)?;
+            engine.synchronize()?;
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_switch_profile_rebinds_tensors() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            if engine.num_optimization_profiles()? < 2 {
+                println!("Engine has a single profile! Skip test!");
+                return Ok(());
+            }
+            engine.activate()?;
+
+            let mut addresses = vec![];
+            for profile_index in 0..2 {
+                engine.switch_profile(profile_index, None)?;
+                let max_shapes = engine.profile_shapes(profile_index)?;
+                let max_shape_dict = max_shapes
+                    .iter()
+                    .map(|(name, shapes)| (name.as_str(), &shapes.max))
+                    .collect();
+                engine.allocate_io_tensors(&max_shape_dict, None)?;
+                addresses.push(engine.tensor_address_map()?);
+            }
+            assert_ne!(addresses[0]["x"], addresses[1]["x"]);
+
+            for profile_index in [0, 1, 0] {
+                engine.switch_profile(profile_index, None)?;
+                assert_eq!(engine.tensor_address_map()?, addresses[profile_index as usize]);
+
+                let shape = &engine.profile_shapes(profile_index)?["x"].opt;
+                let input = Tensor::empty(shape, DataType::FLOAT, stream)?;
+                engine.inference(&HashMap::from([("x", &input)]), None)?;
+                engine.synchronize()?;
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_activate_without_device_memory() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            engine.activate_without_device_memory()?;
+
+            let size = engine.required_workspace_size()?;
+            let scratch = DeviceMemory::new(size.max(1), stream)?;
+            let ptr = unsafe { scratch.get_raw() } as usize;
+            // nothing can be too small for an engine that needs no scratch
+            if size > 0 {
+                assert!(matches!(
+                    unsafe { engine.set_device_memory(ptr, size - 1) },
+                    Err(TRTError::InsufficientDeviceMemory(required, _)) if required == size
+                ));
+            }
+            unsafe { engine.set_device_memory(ptr, size)? };
+
+            let input_shape = Shape(vec![1, 3, 352, 640]);
+            let output_shape = Shape(vec![1, 1, 352, 640]);
+            engine.allocate_io_tensors(
+                &HashMap::from([("x", &input_shape), ("sigmoid_0.tmp_0", &output_shape)]),
+                None,
+            )?;
+            let input = Tensor::empty(&input_shape, DataType::FLOAT, stream)?;
+            engine.inference(&HashMap::from([("x", &input)]), None)?;
+            engine.synchronize()?;
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_print_io_spec() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            let summary = engine.engine()?.io_summary();
+            for name in ["x", "sigmoid_0.tmp_0"] {
+                assert!(summary.contains(name));
+            }
+
+            engine.logger().unwrap().set_capture(true);
+            engine.print_io_spec();
+            let captured = engine.logger().unwrap().take_captured();
+            assert!(captured.iter().any(|msg| msg.contains(&summary)));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_stream_priority() -> TRTResult<()> {
+        with_engine(|_, stream| {
+            let (_, greatest) = device::get_stream_priority_range().unwrap();
+
+            let mut engine = TRTEngine::builder()
+                .stream_priority(greatest)
+                .build_with_own_stream(&ENGINE_PATH)?;
+            activate_and_allocate(&mut engine)?;
+
+            let input = Tensor::empty(&Shape(vec![1, 3, 352, 640]), DataType::FLOAT, stream)?;
+            let outputs = engine.inference(&HashMap::from([("x", &input)]), None)?;
+            assert!(outputs.contains_key("sigmoid_0.tmp_0"));
+            engine.synchronize()?;
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_profile_stream_mismatch() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+            let input = Tensor::empty(&Shape(vec![1, 3, 352, 640]), DataType::FLOAT, stream)?;
+            let feed_dict = HashMap::from([("x", &input)]);
+
+            // hold the engine's stream so the switch queued behind it is pending
+            let other = CuStream::new()?;
+            let release = block_stream(stream);
+            engine.switch_profile(0, None)?;
+            assert!(matches!(
+                engine.inference(&feed_dict, Some(&other)),
+                Err(TRTError::ProfileStreamMismatch)
+            ));
+            engine.inference(&feed_dict, None)?;
+
+            release.send(()).unwrap();
+            engine.synchronize()?;
+            engine.inference(&feed_dict, Some(&other))?;
+            other.synchronize()?;
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_alloc_stream_mismatch() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+            let input = Tensor::empty(&Shape(vec![1, 3, 352, 640]), DataType::FLOAT, stream)?;
+            let feed_dict = HashMap::from([("x", &input)]);
+
+            engine.logger().unwrap().set_capture(true);
+            engine.inference(&feed_dict, Some(stream))?;
+            let captured = engine.logger().unwrap().take_captured();
+            assert!(!captured.iter().any(|msg| msg.contains("different stream")));
+
+            let other = CuStream::new()?;
+            stream.synchronize()?;
+            engine.inference(&feed_dict, Some(&other))?;
+            other.synchronize()?;
+            let captured = engine.logger().unwrap().take_captured();
+            assert_eq!(
+                captured.iter().any(|msg| msg.contains("different stream")),
+                cfg!(debug_assertions)
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_rebind_alternating_inputs() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+
+            let shape = Shape(vec![1, 3, 352, 640]);
+            let buffers = [0.0f32, 1.0]
+                .iter()
+                .map(|&value| Tensor::from_host_slice(&vec![value; shape.size()], &shape, stream))
+                .collect::<TRTResult<Vec<_>>>()?;
+
+            let mut expected = vec![];
+            for buffer in buffers.iter() {
+                let outputs = engine.inference(&HashMap::from([("x", buffer)]), None)?;
+                expected.push(outputs["sigmoid_0.tmp_0"].to_host_vec::<f32>(Some(stream))?);
+            }
+            let addresses = engine.tensor_address_map()?;
+
+            for i in [0, 1, 0, 1] {
+                // the buffers outlive every inference below
+                unsafe { engine.rebind("x", &buffers[i])? };
+                assert_eq!(engine.tensor_address("x"), Some(unsafe { buffers[i].get_raw_ptr() }));
+                let outputs = engine.inference(&HashMap::new(), None)?;
+                assert_eq!(outputs["sigmoid_0.tmp_0"].to_host_vec::<f32>(Some(stream))?, expected[i]);
+            }
+            // the outputs were not reallocated
+            let output_address = engine.tensor_address("sigmoid_0.tmp_0");
+            assert_eq!(output_address, addresses.get("sigmoid_0.tmp_0").copied());
+
+            let half = Tensor::empty(&shape, DataType::HALF, stream)?;
+            let res = unsafe { engine.rebind("x", &half) };
+            assert!(matches!(res, Err(TRTError::DTypeMismatch)));
+            let small = Tensor::empty(&Shape(vec![1, 3, 32, 32]), DataType::FLOAT, stream)?;
+            let res = unsafe { engine.rebind("x", &small) };
+            assert!(matches!(res, Err(TRTError::ShapeMismatch)));
+            stream.synchronize()?;
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_synchronize_own_stream() -> TRTResult<()> {
+        with_engine(|_, stream| {
+            let mut engine = TRTEngine::builder().build_with_own_stream(&ENGINE_PATH)?;
+            activate_and_allocate(&mut engine)?;
+
+            let input = Tensor::empty(&Shape(vec![1, 3, 352, 640]), DataType::FLOAT, stream)?;
+            stream.synchronize()?;
+            engine.inference(&HashMap::from([("x", &input)]), None)?;
+            engine.synchronize()?;
+            assert!(engine.stream().query()?);
+
+            let output = engine.output_map().get_as::<f32>("sigmoid_0.tmp_0")?;
+            assert_eq!(output.len(), 352 * 640);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_new_with_standard_plugins() -> TRTResult<()> {
+        with_engine(|_, stream| {
+            let engine = TRTEngine::new_with_standard_plugins(&ENGINE_PATH, stream)?;
+            assert!(engine.io_count()? > 0);
+            assert!(plugin::list_creators().iter().any(|name| name == "EfficientNMS_TRT"));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_benchmark() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+
+            let input = Tensor::empty(&Shape(vec![1, 3, 352, 640]), DataType::FLOAT, stream)?;
+            let stats = engine.benchmark(&HashMap::from([("x", &input)]), 3, 20)?;
+            assert!(stats.mean_ms > 0.0);
+            assert!(stats.p50_ms > 0.0);
+            assert!(stats.p99_ms >= stats.p50_ms);
+            assert!(stats.throughput > 0.0);
+
+            assert!(matches!(
+                engine.benchmark(&HashMap::from([("x", &input)]), 0, 0),
+                Err(TRTError::EmptyBenchmark),
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_is_graph_capturable() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            activate_and_allocate(engine)?;
+            assert!(engine.is_graph_capturable());
+
+            // the shape of the non-zero indices depends on the input data
+            let plan = build_plan(|network, _| {
+                let input = network.add_input("x", DataType::FLOAT, &[1, 8]).unwrap();
+                let output = network.add_non_zero(input)?;
+                network.set_tensor_name(output, "indices");
+                network.mark_output(output);
+                Some(())
+            });
+            let plan = match plan {
+                Some(plan) => plan,
+                None => {
+                    println!("INonZeroLayer is not supported! Skip test!");
+                    return Ok(());
+                }
+            };
+
+            let runtime = SharedRuntime::new()?;
+            let mut engine = TRTEngine::from_bytes_with_runtime(plan.as_bytes(), &runtime, stream)?;
+            engine.activate()?;
+            assert!(!engine.is_graph_capturable());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_plan_hash() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            let data = fs::read(ENGINE_PATH)?;
+            assert_eq!(engine.plan_hash(), CudaEngine::plan_hash(&data));
+
+            engine.reload_from_bytes(&data)?;
+            assert_eq!(engine.plan_hash(), CudaEngine::plan_hash(&data));
+            assert_ne!(engine.plan_hash(), CudaEngine::plan_hash(&data[1..]));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_validate_against_spec() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            let mut spec = IoSpec {
+                inputs: vec![TensorSpec {
+                    name: "x".to_string(),
+                    dtype: DataType::FLOAT,
+                    shape: Shape(vec![-1, 3, -1, -1]),
+                }],
+                outputs: vec![TensorSpec {
+                    name: "sigmoid_0.tmp_0".to_string(),
+                    dtype: DataType::FLOAT,
+                    shape: Shape(vec![-1, 1, -1, -1]),
+                }],
+            };
+            engine.validate_against_spec(&spec)?;
+
+            spec.inputs[0].dtype = DataType::HALF;
+            match engine.validate_against_spec(&spec) {
+                Err(err @ TRTError::SpecMismatch(_)) => assert_eq!(
+                    err.to_string(),
+                    "TensorRT engine does not match the IO spec: 'x' has dtype FLOAT, expected HALF",
+                ),
+                res => panic!("unexpected result: {:?}", res),
+            }
+
+            Ok(())
+        })
+    }
+
+    #[cfg(feature = "onnx")]
+    #[test]
+    fn test_from_onnx() -> TRTResult<()> {
+        let onnx_path = Path::new("../tmp/pp-ocr-v4-det.onnx");
+        if !onnx_path.exists() {
+            println!("ONNX model not found! Skip test!");
+            return Ok(());
         }
+
+        with_engine(|_, stream| {
+            let cache_path = std::env::temp_dir().join("tensorrt-rs-from-onnx.engine");
+            let timing_cache_path = std::env::temp_dir().join("tensorrt-rs-from-onnx.timing");
+            let _ = fs::remove_file(&cache_path);
+            let _ = fs::remove_file(&timing_cache_path);
+
+            let options = BuildOptions::new()
+                .fp16()
+                .profile("x", ProfileShapes {
+                    min: Shape(vec![1, 3, 32, 32]),
+                    opt: Shape(vec![1, 3, 352, 640]),
+                    max: Shape(vec![1, 3, 960, 960]),
+                })
+                .cache_path(&cache_path)
+                .timing_cache_path(&timing_cache_path);
+            let built = TRTEngine::from_onnx(&onnx_path, options.clone(), stream)?;
+            assert!(cache_path.exists());
+            assert!(timing_cache_path.exists());
+            assert_eq!(built.plan_hash(), CudaEngine::plan_hash(&fs::read(&cache_path)?));
+
+            // the second call loads the cached plan
+            let cached = TRTEngine::from_onnx(&onnx_path, options, stream)?;
+            assert_eq!(cached.plan_hash(), built.plan_hash());
+            assert_eq!(cached.profile_shapes(0)?["x"].max, Shape(vec![1, 3, 960, 960]));
+
+            fs::remove_file(&cache_path)?;
+            fs::remove_file(&timing_cache_path)?;
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_serialize_with_flags() -> TRTResult<()> {
+        with_engine(|engine, _| {
+            let plan = match engine.serialize_with_flags(&[]) {
+                Ok(plan) => plan,
+                Err(TRTError::EngineSerializationError) => {
+                    println!("serializeWithConfig is not supported! Skip test!");
+                    return Ok(());
+                }
+                Err(err) => return Err(err),
+            };
+            assert_eq!(plan.len(), engine.serialize()?.len());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_refit_from_tensors() -> TRTResult<()> {
+        with_engine(|engine, stream| {
+            let weights = Tensor::from_host_slice(&[1.0f32; 4], &Shape(vec![4]), stream)?;
+            match engine.refit_from_tensors(&[("missing", &weights)]) {
+                Err(TRTError::RefitError(reasons)) => assert!(!reasons.is_empty()),
+                Err(TRTError::RefitterCreationError) => {}
+                res => panic!("unexpected result: {:?}", res),
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_refit_from_device_tensors() -> TRTResult<()> {
+        let define = |network: &NetworkDefinition, config: &mut BuilderConfig| {
+            config.set_flag(BuilderFlag::REFIT);
+            let input = network.add_input("x", DataType::FLOAT, &[1, 2, 8, 8]).unwrap();
+            let kernel = [1.0f32; 4 * 2 * 3 * 3];
+            let output = network
+                .add_convolution("conv", input, 4, &[3, 3], &kernel, &[0.0f32; 4])
+                .unwrap();
+            network.set_tensor_name(output, "y");
+            network.mark_output(output);
+        };
+
+        with_plan(define, |plan, stream| {
+            let mut engine = TRTEngine::builder().build_from_bytes(plan, stream)?;
+            // refitting from pointers came with TensorRT 10, like serializeWithConfig
+            if engine.serialize_with_flags(&[]).is_err() {
+                println!("Refitting from device memory is not supported! Skip test!");
+                return Ok(());
+            }
+
+            let kernel_shape = Shape(vec![4, 2, 3, 3]);
+            let kernel = Tensor::from_host_slice(&[0.5f32; 4 * 2 * 3 * 3], &kernel_shape, stream)?;
+            let bias = Tensor::from_host_slice(&[1.0f32; 4], &Shape(vec![4]), stream)?;
+            assert_eq!(kernel.location(), MemoryLocation::Device);
+            engine.refit_from_tensors(&[("conv.weight", &kernel), ("conv.bias", &bias)])?;
+
+            engine.activate()?;
+            engine.allocate_io_tensors(&HashMap::new(), None)?;
+            let x_shape = Shape(vec![1, 2, 8, 8]);
+            let x = Tensor::from_host_slice(&[1.0f32; 2 * 8 * 8], &x_shape, stream)?;
+            let outputs = engine.inference(&HashMap::from([("x", &x)]), None)?;
+            let y = outputs["y"].to_host_vec::<f32>(Some(stream))?;
+            // 2 channels of 3x3 halves, plus the bias
+            assert_eq!(y.len(), 4 * 6 * 6);
+            assert!(y.iter().all(|&y| y == 10.0));
+
+            Ok(())
+        })
     }
 }
@@ -1,3 +1,8 @@
+use crate::tensor::Shape;
+use tensorrt_rs_sys::{
+    recorder::RecordedError,
+    runtime::{EngineCapability, HardwareCompatibilityLevel},
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,28 +11,110 @@ pub enum TRTError {
     IOError(#[from] std::io::Error),
     #[error("Cuda error: {0}")]
     CudaError(#[from] cuda_rs::error::CuError),
+    #[cfg(feature = "image")]
+    #[error("Image error: {0}")]
+    ImageError(#[from] image::ImageError),
     #[error("TensorRT runtime creation error")]
     RuntimeCreationError,
+    #[error("TensorRT standard plugin initialization error")]
+    PluginInitError,
+    #[error("TensorRT plugin library not found: {0}")]
+    PluginNotFound(String),
+    #[error("TensorRT plugin library load error: {0}")]
+    PluginLoadFailed(String),
     #[error("TensorRT engine deserialization error")]
     EngineDeserializationError,
+    #[error("TensorRT {capability:?} engine cannot be loaded by the {have} runtime, use the TensorRT safety or DLA runtime instead")]
+    IncompatibleRuntime {
+        capability: EngineCapability,
+        have: &'static str,
+    },
+    #[error("TensorRT builder creation error")]
+    BuilderCreationError,
+    #[error("TensorRT builder config creation error")]
+    BuilderConfigCreationError,
+    #[error("TensorRT invalid builder optimization level: {0}")]
+    InvalidOptimizationLevel(u8),
+    #[error("TensorRT ONNX parse error: {}", .0.join("; "))]
+    OnnxParseError(Vec<String>),
+    #[error("TensorRT engine build error")]
+    EngineBuildError,
     #[error("TensorRT engine creation error")]
     EngineCreationError,
+    #[error("TensorRT engine serialization error")]
+    EngineSerializationError,
+    #[error("TensorRT device memory trim error")]
+    MemoryTrimError,
+    #[error("TensorRT CUDA stream creation error")]
+    StreamCreationError,
     #[error("TensorRT execution context not initialized")]
     ExecutionContextNotInitialized,
     #[error("TensorRT execution context creation error")]
     ExecutionContextCreationError,
     #[error("TensorRT invalid shape: {0:?}")]
-    ShapeError(Vec<i32>),
+    InvalidShape(Vec<i32>),
+    #[error("TensorRT shape {got} for input '{name}' is outside the profile bounds {min} to {max}")]
+    ShapeError {
+        got: Shape,
+        name: String,
+        min: Shape,
+        max: Shape,
+    },
+    #[error("TensorRT engine does not match the IO spec: {}", .0.join("; "))]
+    SpecMismatch(Vec<String>),
+    #[error("TensorRT unknown tensor: {0}")]
+    UnknownTensor(String),
+    #[error("TensorRT missing input: {0}")]
+    MissingInput(String),
+    #[error("TensorRT profile {profile_index} validation error for tensor '{tensor_name}': {reason}")]
+    ProfileValidationError {
+        profile_index: i32,
+        tensor_name: String,
+        reason: String,
+    },
+    #[error("TensorRT optimization profile switch on another stream has not completed")]
+    ProfileStreamMismatch,
     #[error("TensorRT invalid address")]
     InvalidAddress,
-    #[error("TensorRT enqueue error")]
-    EnqueueError,
+    #[error("TensorRT invalid event")]
+    InvalidEvent,
+    #[error("TensorRT enqueue error for engine '{0}'")]
+    EnqueueError(String),
+    #[error("TensorRT CUDA graph capture error")]
+    GraphCaptureError,
+    #[error("TensorRT CUDA graph not captured")]
+    GraphNotCaptured,
+    #[error("TensorRT CUDA graph launch error")]
+    GraphLaunchError,
+    #[error("TensorRT CUDA graph update error")]
+    GraphUpdateError,
+    #[error("TensorRT CUDA graph points at replaced tensors, update its addresses first")]
+    GraphAddressesOutdated,
     #[error("TensorRT reset shapes error")]
     ResetShapesError,
     #[error("TensorRT shape mismatch")]
     ShapeMismatch,
     #[error("TensorRT dtype mismatch")]
     DTypeMismatch,
+    #[error("TensorRT device memory too small: need {0} bytes, got {1}")]
+    InsufficientDeviceMemory(usize, usize),
+    #[error("TensorRT buffer size mismatch: expected {0} bytes, got {1}")]
+    BufferSizeMismatch(usize, usize),
+    #[error("TensorRT engine with hardware compatibility {engine_compat:?} is incompatible with device {device}")]
+    IncompatibleHardware {
+        engine_compat: HardwareCompatibilityLevel,
+        device: u32,
+    },
+    #[error("TensorRT benchmark needs at least one measured iteration")]
+    EmptyBenchmark,
+    #[error("TensorRT engine used under a different CUDA context than it was created in")]
+    WrongCudaContext,
+    #[error("TensorRT refitter creation error")]
+    RefitterCreationError,
+    #[error("TensorRT refit error: {}", .0.join("; "))]
+    RefitError(Vec<String>),
+    #[error("TensorRT error: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    Recorded(Vec<RecordedError>),
 }
 
 pub type TRTResult<T> = Result<T, TRTError>;
@@ -1,9 +1,27 @@
+pub mod builder;
+#[cfg(feature = "image")]
+pub mod calibration;
 pub mod engine;
 pub mod error;
+pub mod plugin;
+pub mod pool;
+pub mod profiler;
+pub mod runtime;
 pub mod tensor;
 
-pub use engine::TRTEngine;
+pub use builder::BuildOptions;
+#[cfg(feature = "image")]
+pub use calibration::{CalibrationErrors, ImageDirCalibrator};
+pub use engine::{
+    AuxStreamInfo, BenchStats, EngineStream, IoSpec, ProfileShapes, TensorInfo, TensorSpec,
+    TRTEngine, TRTEngineBuilder,
+};
 pub use error::{TRTError, TRTResult};
-pub use tensor::{Shape, Tensor};
+pub use plugin::PluginLibrary;
+pub use pool::{PendingInference, TRTEnginePool};
+pub use profiler::{LayerStats, ProfilerReport};
+pub use runtime::SharedRuntime;
+pub use tensor::{MemoryLocation, OutputMap, Shape, Tensor, TensorElement};
 
-pub use tensorrt_rs_sys::runtime::DataType;
+pub use tensorrt_rs_sys::builder::{BuilderFlag, DeviceType};
+pub use tensorrt_rs_sys::runtime::{DataType, ProfilingVerbosity, SerializationFlag, TensorFormat};
@@ -0,0 +1,77 @@
+use crate::error::{TRTError, TRTResult};
+use tensorrt_rs_sys::plugin::{self, PluginLibraryHandle};
+use std::path::Path;
+
+/// A plugin library registered with TensorRT's plugin registry, whose
+/// plugins are deregistered again on drop.
+pub struct PluginLibrary {
+    handle: PluginLibraryHandle,
+}
+
+impl PluginLibrary {
+    /// Loads the plugin library at `plugin_path`, which may also be a bare
+    /// soname searched for on the library path, like `dlopen` does.
+    pub fn load<P: AsRef<Path>>(plugin_path: P) -> TRTResult<Self> {
+        let path = plugin_path.as_ref();
+        let path_str = match path.to_str() {
+            Some(path_str) => path_str,
+            None => return Err(TRTError::PluginLoadFailed(format!("invalid path: {}", path.display()))),
+        };
+
+        match plugin::load_library(path_str) {
+            Ok(handle) => Ok(Self { handle }),
+            // glibc's `dlerror` for ENOENT, with or without a search path.
+            Err(reason) if reason.contains("No such file or directory") => {
+                Err(TRTError::PluginNotFound(reason))
+            }
+            Err(reason) => {
+                Err(TRTError::PluginLoadFailed(format!("{}: {}", path.display(), reason)))
+            }
+        }
+    }
+
+    pub fn handle(&self) -> PluginLibraryHandle {
+        self.handle
+    }
+}
+
+impl Drop for PluginLibrary {
+    fn drop(&mut self) {
+        plugin::unload_library(self.handle);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_missing_library() {
+        let res = PluginLibrary::load("/nonexistent/libmissing_plugin.so");
+        match res {
+            Err(TRTError::PluginNotFound(msg)) => assert!(msg.contains("libmissing_plugin.so")),
+            _ => panic!("missing library was not reported as not found"),
+        }
+    }
+
+    #[test]
+    fn test_load_missing_soname() {
+        let res = PluginLibrary::load("libtensorrt_rs_missing_plugin.so");
+        assert!(matches!(res, Err(TRTError::PluginNotFound(_))));
+    }
+
+    #[test]
+    fn test_load_malformed_library() -> TRTResult<()> {
+        let path = std::env::temp_dir().join("tensorrt-rs-malformed-plugin.so");
+        std::fs::write(&path, b"not a shared library")?;
+
+        let res = PluginLibrary::load(&path);
+        std::fs::remove_file(&path)?;
+        match res {
+            Err(TRTError::PluginLoadFailed(msg)) => assert!(!msg.is_empty()),
+            _ => panic!("malformed library was loaded"),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,475 @@
+use crate::{
+    engine::{allocate_tensors, enqueue, required_device_memory},
+    error::{TRTError, TRTResult},
+    runtime::deserialize_standard,
+    tensor::{Shape, Tensor},
+};
+use cuda_rs::{context::CuContext, event::CuEvent, memory::DeviceMemory, stream::CuStream};
+use tensorrt_rs_sys::runtime::{CudaEngine, ExecutionContext, ProfilingVerbosity, Runtime};
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::{mpsc, Mutex},
+};
+
+struct Worker {
+    context: ExecutionContext,
+    // the context's scratch memory, reused by every inference on it
+    _scratch: Option<DeviceMemory>,
+    inputs: HashMap<String, Tensor>,
+    outputs: HashMap<String, Tensor>,
+    stream: CuStream,
+    // the NVTX verbosity last set on the context, if any
+    nvtx_verbosity: Option<ProfilingVerbosity>,
+}
+
+// SAFETY: a worker is only ever used by the thread that checked it out of the
+// pool, and the CUDA handles it holds are not tied to a host thread.
+unsafe impl Send for Worker {}
+
+/// A fixed set of execution contexts over a single engine.
+///
+/// Each context owns its IO tensors and stream, so `infer` can be called from
+/// several threads at once; callers block until a context is idle. The
+/// contexts are named `ctx-0`, `ctx-1`, ... to tell them apart in Nsight.
+pub struct TRTEnginePool {
+    idle: Mutex<mpsc::Receiver<Worker>>,
+    release: mpsc::Sender<Worker>,
+    cu_context: CuContext,
+    engine: CudaEngine,
+    scratch_memory_size: usize,
+    nvtx_verbosity: Mutex<Option<ProfilingVerbosity>>,
+    _runtime: Runtime,
+}
+
+// SAFETY: the runtime is only touched while constructing the pool, the engine
+// is only read afterwards, and workers are handed out exclusively through the
+// channel.
+unsafe impl Send for TRTEnginePool {}
+unsafe impl Sync for TRTEnginePool {}
+
+impl TRTEnginePool {
+    pub fn new<P: AsRef<Path>>(
+        engine_path: &P,
+        num_contexts: usize,
+        max_shape_dict: &HashMap<&str, &Shape>,
+    ) -> TRTResult<Self> {
+        let mut runtime = match Runtime::new() {
+            Some(runtime) => runtime,
+            None => return Err(TRTError::RuntimeCreationError),
+        };
+
+        let data = fs::read(engine_path)?;
+
+        let mut engine = deserialize_standard(&mut runtime, data.as_slice(), None)?;
+
+        let scratch_size = required_device_memory(&engine);
+        let mut scratch_memory_size = 0;
+
+        let (release, idle) = mpsc::channel();
+        for index in 0..num_contexts {
+            let mut context = match engine.create_execution_context_without_device_memory() {
+                Some(context) => context,
+                None => return Err(TRTError::ExecutionContextCreationError),
+            };
+            context.set_name(&format!("ctx-{}", index));
+            let stream = CuStream::new()?;
+            let scratch = match scratch_size {
+                0 => None,
+                size => {
+                    let scratch = DeviceMemory::new(size, &stream)?;
+                    context.set_device_memory_v2(unsafe { scratch.get_raw() } as usize, size);
+                    scratch_memory_size += scratch.size;
+                    Some(scratch)
+                }
+            };
+            let mut inputs = HashMap::new();
+            let mut outputs = HashMap::new();
+            allocate_tensors(&engine, &mut context, max_shape_dict, &stream, &mut inputs, &mut outputs)?;
+            stream.synchronize()?;
+
+            let worker = Worker {
+                context,
+                _scratch: scratch,
+                inputs,
+                outputs,
+                stream,
+                nvtx_verbosity: None,
+            };
+            release.send(worker).unwrap();
+        }
+
+        Ok(Self {
+            idle: Mutex::new(idle),
+            release,
+            cu_context: CuContext::current()?,
+            engine,
+            scratch_memory_size,
+            nvtx_verbosity: Mutex::new(None),
+            _runtime: runtime,
+        })
+    }
+
+    /// Bytes of scratch memory allocated for the contexts: one buffer each,
+    /// sized for the engine's most demanding profile, made up front and reused
+    /// across inferences. 0 if the engine needs no scratch memory.
+    pub fn scratch_memory_size(&self) -> usize {
+        self.scratch_memory_size
+    }
+
+    /// Sets the NVTX verbosity of every context: idle ones right away, those
+    /// that are checked out with their next inference.
+    pub fn set_nvtx_verbosity(&self, verbosity: ProfilingVerbosity) {
+        *self.nvtx_verbosity.lock().unwrap() = Some(verbosity);
+
+        // a caller waiting for a context holds the lock, so none is idle
+        let idle = match self.idle.try_lock() {
+            Ok(idle) => idle,
+            Err(_) => return,
+        };
+        for mut worker in idle.try_iter().collect::<Vec<_>>() {
+            worker.context.set_nvtx_verbosity(verbosity);
+            worker.nvtx_verbosity = Some(verbosity);
+            self.release.send(worker).unwrap();
+        }
+    }
+
+    /// Runs one inference on the next idle context and returns copies of the
+    /// outputs. Input tensors must be ready, i.e. any work producing them has
+    /// been synchronized.
+    pub fn infer(&self, feed_dict: &HashMap<&str, &Tensor>) -> TRTResult<HashMap<String, Tensor>> {
+        let _guard = self.cu_context.clone().guard()?;
+
+        let mut worker = self.idle.lock().unwrap().recv().unwrap();
+        let res = self.launch(&mut worker, feed_dict);
+        let synced = worker.stream.synchronize();
+        self.release.send(worker).unwrap();
+
+        let outputs = res?;
+        synced?;
+
+        Ok(outputs)
+    }
+
+    /// Starts an inference and returns as soon as it is enqueued.
+    ///
+    /// `input_consumed` is recorded once TensorRT no longer reads the inputs.
+    /// Inputs are staged into the context's own buffers on the same stream
+    /// before the enqueue, so after `input_consumed` completes the tensors in
+    /// `feed_dict` may be overwritten even though the outputs are still being
+    /// computed. Outputs become valid once `PendingInference::output_ready`
+    /// completes, and `PendingInference::wait` returns them. The context stays
+    /// checked out until the pending inference is waited on or dropped.
+    pub fn infer_staged(
+        &self,
+        feed_dict: &HashMap<&str, &Tensor>,
+        input_consumed: &CuEvent,
+    ) -> TRTResult<PendingInference<'_>> {
+        let _guard = self.cu_context.clone().guard()?;
+
+        let mut worker = self.idle.lock().unwrap().recv().unwrap();
+        if !worker.context.set_input_consumed_event(input_consumed) {
+            self.checkin(worker);
+            return Err(TRTError::InvalidEvent);
+        }
+
+        let res = self.launch(&mut worker, feed_dict).and_then(|outputs| {
+            let output_ready = CuEvent::new()?;
+            output_ready.record(&worker.stream)?;
+            Ok((outputs, output_ready))
+        });
+        match res {
+            Ok((outputs, output_ready)) => Ok(PendingInference {
+                pool: self,
+                worker: Some(worker),
+                outputs,
+                output_ready,
+            }),
+            Err(err) => {
+                let _ = worker.stream.synchronize();
+                self.checkin(worker);
+                Err(err)
+            }
+        }
+    }
+
+    fn launch(
+        &self,
+        worker: &mut Worker,
+        feed_dict: &HashMap<&str, &Tensor>,
+    ) -> TRTResult<HashMap<String, Tensor>> {
+        let Worker { context, inputs, outputs, stream, nvtx_verbosity, .. } = worker;
+
+        let verbosity = *self.nvtx_verbosity.lock().unwrap();
+        if let Some(verbosity) = verbosity.filter(|&verbosity| *nvtx_verbosity != Some(verbosity)) {
+            context.set_nvtx_verbosity(verbosity);
+            *nvtx_verbosity = Some(verbosity);
+        }
+
+        enqueue(&self.engine, context, inputs, feed_dict, stream)?;
+
+        let mut res = HashMap::new();
+        for (name, tensor) in outputs.iter() {
+            let mut output = Tensor::empty(tensor.shape(), tensor.dtype(), stream)?;
+            output.copy_from(tensor, Some(stream))?;
+            res.insert(name.clone(), output);
+        }
+
+        Ok(res)
+    }
+
+    fn checkin(&self, mut worker: Worker) {
+        let no_event = unsafe { CuEvent::from_raw(std::ptr::null_mut()) };
+        worker.context.set_input_consumed_event(&no_event);
+        self.release.send(worker).unwrap();
+    }
+}
+
+/// An inference started by `TRTEnginePool::infer_staged`.
+pub struct PendingInference<'a> {
+    pool: &'a TRTEnginePool,
+    worker: Option<Worker>,
+    outputs: HashMap<String, Tensor>,
+    output_ready: CuEvent,
+}
+
+impl<'a> PendingInference<'a> {
+    pub fn output_ready(&self) -> &CuEvent {
+        &self.output_ready
+    }
+
+    /// Waits for the outputs and returns the context to the pool.
+    pub fn wait(mut self) -> TRTResult<HashMap<String, Tensor>> {
+        self.output_ready.synchronize()?;
+
+        Ok(std::mem::take(&mut self.outputs))
+    }
+}
+
+impl<'a> Drop for PendingInference<'a> {
+    fn drop(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.stream.synchronize();
+            self.pool.checkin(worker);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cuda_rs::device::CuDevice;
+    use tensorrt_rs_sys::runtime::DataType;
+
+    #[test]
+    fn test_pool_concurrent_infer() -> TRTResult<()> {
+        let engine_path = Path::new("../tmp/pp-ocr-v4-det-fp16.engine");
+        if !engine_path.exists() {
+            println!("Engine file not found! Skip test!");
+            return Ok(());
+        }
+
+        cuda_rs::init()?;
+
+        let device = CuDevice::new(0)?;
+        let ctx = device.retain_primary_context()?;
+        let _guard = ctx.guard()?;
+
+        let input_shape = Shape(vec![1, 3, 352, 640]);
+        let output_shape = Shape(vec![1, 1, 352, 640]);
+        let max_shape_dict = HashMap::from([
+            ("x", &input_shape),
+            ("sigmoid_0.tmp_0", &output_shape),
+        ]);
+        let pool = TRTEnginePool::new(&engine_path, 2, &max_shape_dict)?;
+
+        std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    s.spawn(|| -> TRTResult<()> {
+                        let ctx = CuDevice::new(0)?.retain_primary_context()?;
+                        let _guard = ctx.guard()?;
+                        let stream = CuStream::new()?;
+
+                        let input = Tensor::empty(&input_shape, DataType::FLOAT, &stream)?;
+                        stream.synchronize()?;
+                        let feed_dict = HashMap::from([("x", &input)]);
+                        for _ in 0..3 {
+                            let outputs = pool.infer(&feed_dict)?;
+                            assert_eq!(outputs["sigmoid_0.tmp_0"].shape(), &output_shape);
+                        }
+
+                        Ok(())
+                    })
+                })
+                .collect();
+
+            for handle in handles {
+                handle.join().unwrap()?;
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_pool_infer_staged() -> TRTResult<()> {
+        let engine_path = Path::new("../tmp/pp-ocr-v4-det-fp16.engine");
+        if !engine_path.exists() {
+            println!("Engine file not found! Skip test!");
+            return Ok(());
+        }
+
+        cuda_rs::init()?;
+
+        let device = CuDevice::new(0)?;
+        let ctx = device.retain_primary_context()?;
+        let _guard = ctx.guard()?;
+        let stream = CuStream::new()?;
+
+        let input_shape = Shape(vec![1, 3, 352, 640]);
+        let output_shape = Shape(vec![1, 1, 352, 640]);
+        let max_shape_dict = HashMap::from([
+            ("x", &input_shape),
+            ("sigmoid_0.tmp_0", &output_shape),
+        ]);
+        let pool = TRTEnginePool::new(&engine_path, 1, &max_shape_dict)?;
+
+        let input = Tensor::empty(&input_shape, DataType::FLOAT, &stream)?;
+        stream.synchronize()?;
+        let feed_dict = HashMap::from([("x", &input)]);
+
+        let input_consumed = CuEvent::new()?;
+        let pending = pool.infer_staged(&feed_dict, &input_consumed)?;
+        input_consumed.synchronize()?;
+        let output_ready = pending.output_ready().clone();
+        let outputs = pending.wait()?;
+
+        assert!(output_ready.elapsed_time(&input_consumed)? >= 0.0);
+        assert_eq!(outputs["sigmoid_0.tmp_0"].shape(), &output_shape);
+
+        // the context is back in the pool
+        pool.infer(&feed_dict)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pool_scratch_per_context() -> TRTResult<()> {
+        let engine_path = Path::new("../tmp/pp-ocr-v4-det-fp16.engine");
+        if !engine_path.exists() {
+            println!("Engine file not found! Skip test!");
+            return Ok(());
+        }
+
+        cuda_rs::init()?;
+
+        let device = CuDevice::new(0)?;
+        let ctx = device.retain_primary_context()?;
+        let _guard = ctx.guard()?;
+        let stream = CuStream::new()?;
+
+        let input_shape = Shape(vec![1, 3, 352, 640]);
+        let output_shape = Shape(vec![1, 1, 352, 640]);
+        let max_shape_dict = HashMap::from([
+            ("x", &input_shape),
+            ("sigmoid_0.tmp_0", &output_shape),
+        ]);
+        let pool = TRTEnginePool::new(&engine_path, 2, &max_shape_dict)?;
+        let per_context = (0..pool.engine.get_num_optimization_profiles())
+            .map(|profile_index| pool.engine.device_memory_size_for_profile(profile_index))
+            .max()
+            .unwrap_or(0)
+            .max(pool.engine.device_memory_size());
+        assert_eq!(pool.scratch_memory_size(), 2 * per_context);
+
+        // each context has a buffer of its own, and inferences reuse it
+        let scratch_buffers = |pool: &TRTEnginePool| {
+            let workers = pool.idle.lock().unwrap().try_iter().collect::<Vec<_>>();
+            let buffers = workers
+                .iter()
+                .filter_map(|worker| worker._scratch.as_ref())
+                .map(|scratch| (unsafe { scratch.get_raw() } as usize, scratch.size))
+                .collect::<Vec<_>>();
+            for worker in workers {
+                pool.release.send(worker).unwrap();
+            }
+            buffers
+        };
+        let mut buffers = scratch_buffers(&pool);
+        buffers.sort();
+        if per_context > 0 {
+            assert_eq!(buffers.len(), 2);
+            assert_ne!(buffers[0].0, buffers[1].0);
+            assert!(buffers.iter().all(|&(_, size)| size == per_context));
+        } else {
+            assert!(buffers.is_empty());
+        }
+
+        let input = Tensor::empty(&input_shape, DataType::FLOAT, &stream)?;
+        stream.synchronize()?;
+        let feed_dict = HashMap::from([("x", &input)]);
+        for _ in 0..4 {
+            let outputs = pool.infer(&feed_dict)?;
+            assert_eq!(outputs["sigmoid_0.tmp_0"].shape(), &output_shape);
+        }
+        let mut after = scratch_buffers(&pool);
+        after.sort();
+        assert_eq!(after, buffers);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pool_context_names() -> TRTResult<()> {
+        let engine_path = Path::new("../tmp/pp-ocr-v4-det-fp16.engine");
+        if !engine_path.exists() {
+            println!("Engine file not found! Skip test!");
+            return Ok(());
+        }
+
+        cuda_rs::init()?;
+
+        let device = CuDevice::new(0)?;
+        let ctx = device.retain_primary_context()?;
+        let _guard = ctx.guard()?;
+        let stream = CuStream::new()?;
+
+        let input_shape = Shape(vec![1, 3, 352, 640]);
+        let output_shape = Shape(vec![1, 1, 352, 640]);
+        let max_shape_dict = HashMap::from([
+            ("x", &input_shape),
+            ("sigmoid_0.tmp_0", &output_shape),
+        ]);
+        let pool = TRTEnginePool::new(&engine_path, 3, &max_shape_dict)?;
+
+        // checked out while the verbosity changes, so it catches up on its
+        // next inference
+        let busy = pool.idle.lock().unwrap().recv().unwrap();
+        pool.set_nvtx_verbosity(ProfilingVerbosity::DETAILED);
+        assert_eq!(busy.nvtx_verbosity, None);
+        pool.release.send(busy).unwrap();
+
+        let input = Tensor::empty(&input_shape, DataType::FLOAT, &stream)?;
+        stream.synchronize()?;
+        for _ in 0..3 {
+            pool.infer(&HashMap::from([("x", &input)]))?;
+        }
+
+        let workers = (0..3)
+            .map(|_| pool.idle.lock().unwrap().recv().unwrap())
+            .collect::<Vec<_>>();
+        let mut names = workers
+            .iter()
+            .map(|worker| worker.context.get_name().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["ctx-0", "ctx-1", "ctx-2"]);
+        assert!(workers
+            .iter()
+            .all(|worker| worker.nvtx_verbosity == Some(ProfilingVerbosity::DETAILED)));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,298 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs, io,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+use tensorrt_rs_sys::profiler::Profiler;
+
+/// Timings of one layer over the runs of a `ProfilerReport`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayerStats {
+    pub name: String,
+    // number of runs the layer was reported in
+    pub runs: usize,
+    pub mean_ms: f32,
+    pub min_ms: f32,
+    pub max_ms: f32,
+    pub total_ms: f32,
+}
+
+/// Collects the layer times of repeated inferences, e.g. to compare engines
+/// across TensorRT upgrades, and exports them as a chrome://tracing (or
+/// Perfetto) trace or as CSV.
+///
+/// Clones share the timings, so hand one to `TRTEngine::set_profiler` and
+/// keep the other to read them. Call `end_run` after each inference: the
+/// layers reported since the previous call make up one run, even if a layer
+/// is reported several times, e.g. inside a loop.
+#[derive(Clone, Default)]
+pub struct ProfilerReport(Arc<Mutex<Timings>>);
+
+#[derive(Default)]
+struct Timings {
+    // layer names in the order they were first reported
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+    // (layer index, ms) in execution order, per run
+    runs: Vec<Vec<(usize, f32)>>,
+    // whether the last of `runs` still takes reports
+    run_open: bool,
+}
+
+impl ProfilerReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ends the current run; the next report starts a new one. Does nothing
+    /// if no layer was reported since the last call.
+    pub fn end_run(&self) {
+        self.0.lock().unwrap().run_open = false;
+    }
+
+    /// The number of runs, counting one that is not ended yet.
+    pub fn num_runs(&self) -> usize {
+        self.0.lock().unwrap().runs.len()
+    }
+
+    pub fn reset(&self) {
+        *self.0.lock().unwrap() = Timings::default();
+    }
+
+    /// The mean time of a whole run, summed over its layers.
+    pub fn mean_run_ms(&self) -> f32 {
+        let timings = self.0.lock().unwrap();
+        if timings.runs.is_empty() {
+            return 0.0;
+        }
+        let total: f32 = timings.runs.iter().flatten().map(|(_, ms)| ms).sum();
+        total / timings.runs.len() as f32
+    }
+
+    /// Per-layer statistics in execution order.
+    pub fn layers(&self) -> Vec<LayerStats> {
+        let timings = self.0.lock().unwrap();
+        let mut stats = timings
+            .names
+            .iter()
+            .map(|name| LayerStats {
+                name: name.clone(),
+                runs: 0,
+                mean_ms: 0.0,
+                min_ms: f32::INFINITY,
+                max_ms: 0.0,
+                total_ms: 0.0,
+            })
+            .collect::<Vec<_>>();
+
+        // a layer reported several times in a run counts with its total
+        let mut run_ms = vec![None; stats.len()];
+        for run in &timings.runs {
+            run_ms.fill(None);
+            for &(index, ms) in run {
+                *run_ms[index].get_or_insert(0.0) += ms;
+            }
+            for (layer, ms) in stats.iter_mut().zip(&run_ms) {
+                if let Some(ms) = *ms {
+                    layer.runs += 1;
+                    layer.min_ms = layer.min_ms.min(ms);
+                    layer.max_ms = layer.max_ms.max(ms);
+                    layer.total_ms += ms;
+                }
+            }
+        }
+        for layer in stats.iter_mut() {
+            layer.mean_ms = layer.total_ms / layer.runs as f32;
+        }
+
+        stats
+    }
+
+    /// One row per layer: `layer,runs,mean_ms,min_ms,max_ms,total_ms`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("layer,runs,mean_ms,min_ms,max_ms,total_ms\n");
+        for layer in self.layers() {
+            let _ = writeln!(
+                csv,
+                "{},{},{:.6},{:.6},{:.6},{:.6}",
+                csv_field(&layer.name),
+                layer.runs,
+                layer.mean_ms,
+                layer.min_ms,
+                layer.max_ms,
+                layer.total_ms,
+            );
+        }
+        csv
+    }
+
+    /// A trace in the Chrome trace event format, with the runs laid out one
+    /// after another and each layer a complete event of its own.
+    pub fn to_chrome_trace(&self) -> String {
+        let timings = self.0.lock().unwrap();
+        let mut events = vec![];
+        // microseconds, the unit of the format
+        let mut ts = 0.0f64;
+        for (run, layers) in timings.runs.iter().enumerate() {
+            for &(index, ms) in layers {
+                let dur = ms as f64 * 1000.0;
+                events.push(format!(
+                    "{{\"name\":{},\"cat\":\"layer\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\
+                     \"pid\":0,\"tid\":0,\"args\":{{\"run\":{}}}}}",
+                    json_string(&timings.names[index]),
+                    ts,
+                    dur,
+                    run,
+                ));
+                ts += dur;
+            }
+        }
+
+        format!("{{\"traceEvents\":[{}],\"displayTimeUnit\":\"ms\"}}", events.join(","))
+    }
+
+    pub fn write_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+
+    pub fn write_chrome_trace<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_chrome_trace())
+    }
+}
+
+impl Profiler for ProfilerReport {
+    fn report_layer_time(&mut self, layer_name: &str, ms: f32) {
+        let mut timings = self.0.lock().unwrap();
+        let index = match timings.indices.get(layer_name) {
+            Some(&index) => index,
+            None => {
+                let index = timings.names.len();
+                timings.names.push(layer_name.to_string());
+                timings.indices.insert(layer_name.to_string(), index);
+                index
+            }
+        };
+
+        if !timings.run_open {
+            timings.runs.push(vec![]);
+            timings.run_open = true;
+        }
+        timings.runs.last_mut().unwrap().push((index, ms));
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report_runs(report: &ProfilerReport, runs: &[&[(&str, f32)]]) {
+        let mut profiler = report.clone();
+        for run in runs {
+            for (name, ms) in run.iter() {
+                profiler.report_layer_time(name, *ms);
+            }
+            report.end_run();
+        }
+    }
+
+    #[test]
+    fn test_profiler_report() {
+        let report = ProfilerReport::new();
+        report_runs(&report, &[
+            &[("conv", 1.0), ("relu, fused", 0.5)],
+            &[("conv", 3.0), ("relu, fused", 0.5)],
+        ]);
+
+        assert_eq!(report.num_runs(), 2);
+        assert_eq!(report.mean_run_ms(), 2.5);
+        let layers = report.layers();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[0].name, "conv");
+        assert_eq!(layers[0].runs, 2);
+        assert_eq!(layers[0].mean_ms, 2.0);
+        assert_eq!(layers[0].min_ms, 1.0);
+        assert_eq!(layers[0].max_ms, 3.0);
+        assert_eq!(layers[1].total_ms, 1.0);
+
+        let csv = report.to_csv();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "layer,runs,mean_ms,min_ms,max_ms,total_ms");
+        assert_eq!(lines[1], "conv,2,2.000000,1.000000,3.000000,4.000000");
+        assert!(lines[2].starts_with("\"relu, fused\",2,"));
+
+        report.reset();
+        assert_eq!(report.num_runs(), 0);
+        assert_eq!(report.mean_run_ms(), 0.0);
+    }
+
+    #[test]
+    fn test_repeated_layer_in_run() {
+        let report = ProfilerReport::new();
+        report_runs(&report, &[
+            &[("embed", 1.0), ("block", 2.0), ("block", 2.0), ("head", 1.0)],
+            &[("embed", 1.0), ("block", 3.0), ("block", 3.0), ("head", 1.0)],
+        ]);
+        // ending a run twice does not add an empty one
+        report.end_run();
+
+        assert_eq!(report.num_runs(), 2);
+        assert_eq!(report.mean_run_ms(), 7.0);
+        let layers = report.layers();
+        assert_eq!(layers.len(), 3);
+        assert_eq!(layers[1].name, "block");
+        assert_eq!(layers[1].runs, 2);
+        assert_eq!(layers[1].min_ms, 4.0);
+        assert_eq!(layers[1].max_ms, 6.0);
+
+        // until it is ended, a run takes every report
+        let mut profiler = report.clone();
+        profiler.report_layer_time("embed", 1.0);
+        profiler.report_layer_time("embed", 1.0);
+        assert_eq!(report.num_runs(), 3);
+        assert_eq!(report.layers()[0].max_ms, 2.0);
+    }
+
+    #[test]
+    fn test_chrome_trace() {
+        let report = ProfilerReport::new();
+        report_runs(&report, &[&[("conv \"3x3\"", 1.0), ("relu", 0.5)], &[("conv \"3x3\"", 2.0)]]);
+
+        let trace = report.to_chrome_trace();
+        assert!(trace.starts_with("{\"traceEvents\":[{\"name\":\"conv \\\"3x3\\\"\""));
+        assert!(trace.ends_with("],\"displayTimeUnit\":\"ms\"}"));
+        assert_eq!(trace.matches("\"ph\":\"X\"").count(), 3);
+        // the second run starts after the first one
+        assert!(trace.contains("\"ts\":1500.000,\"dur\":2000.000"));
+        assert!(trace.contains("\"args\":{\"run\":1}"));
+
+        assert_eq!(json_string("a\\b\n\u{1}"), "\"a\\\\b\\n\\u0001\"");
+    }
+}
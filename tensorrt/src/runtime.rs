@@ -0,0 +1,148 @@
+use crate::error::{TRTError, TRTResult};
+use tensorrt_rs_sys::{
+    logger::Severity,
+    recorder::ErrorRecorder,
+    runtime::{CudaEngine, EngineCapability, Runtime},
+};
+use std::sync::{Arc, Mutex};
+
+/// A runtime, and with it a logger, shared by several engines.
+///
+/// TensorRT allows one runtime to deserialize engines from several threads,
+/// and engines deserialized from it may be used concurrently as long as each
+/// thread has its own execution context. Deserialization and logging still
+/// take `&mut Runtime`, so they are serialized by a mutex here.
+#[derive(Clone)]
+pub struct SharedRuntime(Arc<Mutex<Inner>>);
+
+struct Inner(Runtime);
+
+// SAFETY: the runtime is not tied to a host thread, and every access to it
+// goes through the mutex.
+unsafe impl Send for Inner {}
+
+impl SharedRuntime {
+    pub fn new() -> TRTResult<Self> {
+        match Runtime::new() {
+            Some(runtime) => Ok(Self(Arc::new(Mutex::new(Inner(runtime))))),
+            None => Err(TRTError::RuntimeCreationError),
+        }
+    }
+
+    pub fn deserialize(&self, data: &[u8]) -> TRTResult<CudaEngine> {
+        deserialize_standard(&mut self.0.lock().unwrap().0, data, None)
+    }
+
+    pub fn log(&self, level: Severity, msg: &str) {
+        self.0.lock().unwrap().0.logger().log(level, msg);
+    }
+}
+
+/// Deserializes `data` with the standard runtime, telling plans built for the
+/// safety or DLA standalone runtimes apart from other failures, as far as
+/// `capability_hint` can. `recorder` is
+/// the one set on `runtime`, if any: TensorRT reports its errors there
+/// instead of the logger, and they are returned if the plan is just invalid.
+pub(crate) fn deserialize_standard(
+    runtime: &mut Runtime,
+    data: &[u8],
+    recorder: Option<&ErrorRecorder>,
+) -> TRTResult<CudaEngine> {
+    runtime.logger().take_last_error();
+    if let Some(recorder) = recorder {
+        recorder.clear();
+    }
+    let engine = match runtime.deserialize(data) {
+        Some(engine) => engine,
+        None => {
+            let errors = recorder.map(ErrorRecorder::take_errors).unwrap_or_default();
+            let msg = runtime.logger().take_last_error();
+            let hint = errors
+                .iter()
+                .map(|error| error.description.as_str())
+                .chain(msg.as_deref())
+                .find_map(capability_hint);
+            return match hint {
+                Some(capability) => Err(TRTError::IncompatibleRuntime { capability, have: "standard" }),
+                None if !errors.is_empty() => Err(TRTError::Recorded(errors)),
+                None => Err(TRTError::EngineDeserializationError),
+            };
+        }
+    };
+
+    match engine.get_engine_capability() {
+        EngineCapability::STANDARD => Ok(engine),
+        capability => Err(TRTError::IncompatibleRuntime { capability, have: "standard" }),
+    }
+}
+
+// The standard runtime refuses other plans before they expose a capability,
+// so it is guessed from the error TensorRT logs. Best effort only: the
+// messages are not part of TensorRT's API, and a wording this does not know
+// leaves the failure an `EngineDeserializationError`.
+fn capability_hint(msg: &str) -> Option<EngineCapability> {
+    let msg = msg.to_lowercase();
+    if msg.contains("safety") || msg.contains("safe runtime") {
+        Some(EngineCapability::SAFETY)
+    } else if msg.contains("dla") && (msg.contains("standalone") || msg.contains("loadable")) {
+        Some(EngineCapability::DLASTANDALONE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    // built with `trtexec --useDLACore=0 --safe --buildDLAStandalone`
+    const DLA_LOADABLE_PATH: &str = "../tmp/dla-standalone.loadable";
+
+    #[test]
+    fn test_incompatible_runtime() {
+        let msg = "IRuntime::deserializeCudaEngine: Error Code 1: Cannot deserialize a DLA standalone loadable";
+        assert_eq!(capability_hint(msg), Some(EngineCapability::DLASTANDALONE));
+        assert_eq!(
+            capability_hint("Engine was built for the safety runtime"),
+            Some(EngineCapability::SAFETY)
+        );
+        assert_eq!(capability_hint("Serialization assertion failed"), None);
+
+        let err = TRTError::IncompatibleRuntime {
+            capability: EngineCapability::DLASTANDALONE,
+            have: "standard",
+        };
+        let msg = err.to_string();
+        assert!(msg.contains("DLASTANDALONE") && msg.contains("standard runtime"));
+
+        // garbage is not mistaken for another runtime's plan
+        if let Ok(runtime) = SharedRuntime::new() {
+            assert!(matches!(
+                runtime.deserialize(b"not an engine"),
+                Err(TRTError::EngineDeserializationError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_dla_standalone_plan() -> TRTResult<()> {
+        if !Path::new(DLA_LOADABLE_PATH).exists() {
+            println!("DLA loadable not found! Skip test!");
+            return Ok(());
+        }
+
+        let data = fs::read(DLA_LOADABLE_PATH)?;
+        let runtime = SharedRuntime::new()?;
+        match runtime.deserialize(&data) {
+            Err(TRTError::IncompatibleRuntime { capability, have }) => {
+                assert_eq!(capability, EngineCapability::DLASTANDALONE);
+                assert_eq!(have, "standard");
+            }
+            Err(err) => panic!("the message TensorRT logs is no longer recognized: {}", err),
+            Ok(_) => panic!("a DLA loadable was deserialized by the standard runtime"),
+        }
+
+        Ok(())
+    }
+}
@@ -1,54 +1,253 @@
 use crate::error::{TRTError, TRTResult};
-use cuda_rs::{memory::DeviceMemory, stream::CuStream};
-use tensorrt_rs_sys::runtime::DataType;
+use cuda_rs::{
+    memory::{DeviceMemory, HostMemory},
+    stream::CuStream,
+};
+use tensorrt_rs_sys::{builder::NetworkDefinition, runtime::DataType};
+use std::{collections::HashMap, fmt, mem::ManuallyDrop};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Shape(pub Vec<i32>);
 
 impl Shape {
+    /// The most dimensions a TensorRT tensor can have.
+    pub const MAX_DIMS: usize = NetworkDefinition::MAX_DIMS;
+
+    // Fails unless `dims` is a shape TensorRT accepts: at most `MAX_DIMS`
+    // dimensions, each -1 (dynamic) or larger.
+    fn checked(dims: Vec<i32>) -> TRTResult<Shape> {
+        if dims.len() > Self::MAX_DIMS || dims.iter().any(|&dim| dim < -1) {
+            return Err(TRTError::InvalidShape(dims));
+        }
+
+        Ok(Shape(dims))
+    }
+
     pub fn size(&self) -> usize {
         self.0
             .iter()
             .map(|x| *x as usize)
             .product::<usize>()
     }
+
+    /// Adds `dim` in front, e.g. a batch dimension. Fails if the result has
+    /// more than `MAX_DIMS` dimensions or one below -1.
+    pub fn prepend(&self, dim: i32) -> TRTResult<Shape> {
+        let mut dims = Vec::with_capacity(self.0.len() + 1);
+        dims.push(dim);
+        dims.extend_from_slice(&self.0);
+        Self::checked(dims)
+    }
+
+    /// Adds `dim` at the end; fails like `prepend`.
+    pub fn append(&self, dim: i32) -> TRTResult<Shape> {
+        let mut dims = self.0.clone();
+        dims.push(dim);
+        Self::checked(dims)
+    }
+
+    pub fn remove(&self, index: usize) -> TRTResult<Shape> {
+        if index >= self.0.len() {
+            return Err(TRTError::InvalidShape(self.0.clone()));
+        }
+        let mut dims = self.0.clone();
+        dims.remove(index);
+        Ok(Shape(dims))
+    }
+
+    /// Collapses the dimensions from `index` on into one, e.g. `[2, 3, 4]`
+    /// flattened from 1 is `[2, 12]`. A dynamic dimension stays dynamic.
+    /// Fails for an invalid shape or if the product overflows an `i32`.
+    pub fn flatten_from(&self, index: usize) -> TRTResult<Shape> {
+        let invalid = || TRTError::InvalidShape(self.0.clone());
+        if index >= self.0.len() {
+            return Err(invalid());
+        }
+        Self::checked(self.0.clone())?;
+
+        let tail = &self.0[index..];
+        let flat = if tail.contains(&-1) {
+            -1
+        } else {
+            tail.iter().try_fold(1i32, |flat, &dim| flat.checked_mul(dim)).ok_or_else(invalid)?
+        };
+        let mut dims = self.0[..index].to_vec();
+        dims.push(flat);
+        Ok(Shape(dims))
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, dim) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", dim)?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// Host element types with a matching TensorRT `DataType`.
+pub trait TensorElement: Copy + Default {
+    const DTYPE: DataType;
+}
+
+impl TensorElement for f32 {
+    const DTYPE: DataType = DataType::FLOAT;
+}
+
+impl TensorElement for i8 {
+    const DTYPE: DataType = DataType::INT8;
+}
+
+impl TensorElement for u8 {
+    const DTYPE: DataType = DataType::UINT8;
+}
+
+impl TensorElement for i32 {
+    const DTYPE: DataType = DataType::INT32;
+}
+
+impl TensorElement for i64 {
+    const DTYPE: DataType = DataType::INT64;
+}
+
+/// Where a tensor's elements live.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryLocation {
+    Device,
+    // Page-locked host memory, which the GPU can access directly.
+    HostPinned,
 }
 
 pub struct Tensor {
-    mem: DeviceMemory,
+    // only the stream is dropped for pinned tensors, which point into
+    // `_host`, and for borrowed memory
+    mem: ManuallyDrop<DeviceMemory>,
     shape: Shape,
     dtype: DataType,
+    location: MemoryLocation,
+    // false if the memory belongs to someone else and must not be freed
+    owns_memory: bool,
+    _host: Option<HostMemory>,
 }
 
 impl Tensor {
     pub fn empty(shape: &Shape, dtype: DataType, stream: &CuStream) -> TRTResult<Self> {
-        let mem_size = shape.size() * dtype.get_elem_size();
+        let mem_size = dtype.get_byte_size(shape.size());
         let mem = DeviceMemory::new(mem_size, stream)?;
-        Ok(Self { mem, shape: shape.clone(), dtype })
+        Ok(Self::from_memory(mem, shape, dtype))
+    }
+
+    /// Wraps page-locked host memory, e.g. a staging buffer filled by the
+    /// CPU, as a tensor that copies to and from device tensors directly.
+    pub fn from_pinned_host(
+        host: HostMemory, shape: &Shape, dtype: DataType, stream: &CuStream
+    ) -> TRTResult<Self> {
+        let byte_size = dtype.get_byte_size(shape.size());
+        if host.size < byte_size {
+            return Err(TRTError::BufferSizeMismatch(byte_size, host.size));
+        }
+        let mem = unsafe {
+            DeviceMemory::from_raw(host.get_raw() as _, byte_size, stream)
+        };
+
+        Ok(Self {
+            mem: ManuallyDrop::new(mem),
+            shape: shape.clone(),
+            dtype,
+            location: MemoryLocation::HostPinned,
+            owns_memory: true,
+            _host: Some(host),
+        })
+    }
+
+    /// Uploads `data` into a new tensor and waits for the copy to finish.
+    pub fn from_host_slice<T: TensorElement>(
+        data: &[T], shape: &Shape, stream: &CuStream
+    ) -> TRTResult<Self> {
+        let tensor = Self::empty(shape, T::DTYPE, stream)?;
+        let byte_size = std::mem::size_of_val(data);
+        if byte_size != tensor.byte_size() {
+            return Err(TRTError::BufferSizeMismatch(tensor.byte_size(), byte_size));
+        }
+        tensor.mem.copy_from_raw(data.as_ptr() as _, byte_size, Some(stream))?;
+        stream.synchronize()?;
+
+        Ok(tensor)
+    }
+
+    /// Downloads the tensor, which must hold elements of type `T`.
+    pub fn to_host_vec<T: TensorElement>(&self, stream: Option<&CuStream>) -> TRTResult<Vec<T>> {
+        if self.dtype != T::DTYPE {
+            return Err(TRTError::DTypeMismatch);
+        }
+        let mut data = vec![T::default(); self.numel()];
+        self.mem.copy_to_raw(data.as_mut_ptr() as _, self.byte_size(), stream)?;
+        stream.unwrap_or(&self.mem.stream).synchronize()?;
+
+        Ok(data)
     }
 
     pub fn from_memory(mem: DeviceMemory, shape: &Shape, dtype: DataType) -> Self {
-        Self { mem, shape: shape.clone(), dtype }
+        Self {
+            mem: ManuallyDrop::new(mem),
+            shape: shape.clone(),
+            dtype,
+            location: MemoryLocation::Device,
+            owns_memory: true,
+            _host: None,
+        }
     }
 
     pub fn get_memory(&self) -> &DeviceMemory {
         &self.mem
     }
 
+    /// Takes ownership of the device allocation at `ptr`, which must come
+    /// from `cuMemAlloc`/`cuMemAllocAsync` and is freed when the tensor drops.
     pub fn from_raw_ptr(
         ptr: usize, shape: &Shape, dtype: DataType, stream: &CuStream
     ) -> Self {
-        let mem_size = shape.size() * dtype.get_elem_size();
+        let mem_size = dtype.get_byte_size(shape.size());
         let mem = unsafe {
             DeviceMemory::from_raw(ptr as _, mem_size, stream)
         };
-        Self { mem, shape: shape.clone(), dtype }
+        Self::from_memory(mem, shape, dtype)
+    }
+
+    /// Wraps device memory at `ptr` owned by someone else, e.g. another
+    /// allocator or framework, without ever freeing it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` has to point at device memory of at least `shape` and `dtype`'s
+    /// byte size in the current context, which outlives the tensor and is
+    /// not freed or reused while the tensor or work enqueued on it uses it.
+    pub unsafe fn from_borrowed_ptr(
+        ptr: usize, shape: &Shape, dtype: DataType, stream: &CuStream
+    ) -> Self {
+        let mut tensor = Self::from_raw_ptr(ptr, shape, dtype, stream);
+        tensor.owns_memory = false;
+        tensor
     }
 
     pub unsafe fn get_raw_ptr(&self) -> usize {
         self.mem.get_raw() as usize
     }
 
+    /// Sets the stream used for the tensor's default copies and its free.
+    pub fn set_stream(&mut self, stream: &CuStream) {
+        self.mem.stream = stream.clone();
+    }
+
+    pub fn location(&self) -> MemoryLocation {
+        self.location
+    }
+
     pub fn shape(&self) -> &Shape {
         &self.shape
     }
@@ -57,14 +256,52 @@ impl Tensor {
         self.dtype
     }
 
+    pub fn dtype_ref(&self) -> &DataType {
+        &self.dtype
+    }
+
+    pub fn is_float_type(&self) -> bool {
+        self.dtype.is_float()
+    }
+
+    pub fn is_integer_type(&self) -> bool {
+        self.dtype.is_integer()
+    }
+
+    pub fn is_quantized_type(&self) -> bool {
+        self.dtype.is_quantized()
+    }
+
+    pub fn compatible_with(&self, other: &Tensor) -> bool {
+        self.dtype == other.dtype && self.shape == other.shape
+    }
+
+    /// Bytes taken by the tensor's elements, which for sub-byte types like
+    /// INT4 are packed and rounded up to a whole byte.
+    pub fn byte_size(&self) -> usize {
+        self.dtype.get_byte_size(self.numel())
+    }
+
+    pub fn numel(&self) -> usize {
+        self.shape.size()
+    }
+
+    /// Number of elements the underlying allocation can hold.
+    pub fn capacity(&self) -> usize {
+        self.dtype.get_num_elems(self.mem.size)
+    }
+
     pub unsafe fn reset_shape(&mut self, shape: &Shape) -> TRTResult<()> {
-        if self.shape.size() < shape.size() {
+        if self.capacity() < shape.size() {
             return Err(TRTError::ResetShapesError);
         }
         self.shape = shape.clone();
         Ok(())
     }
 
+    /// Copies `src` into this tensor; either may live on the device or in
+    /// pinned host memory, as the driver infers the direction of the copy
+    /// from the addresses under unified addressing.
     pub fn copy_from(&mut self, src: &Self, stream: Option<&CuStream>) -> TRTResult<()> {
         if self.shape != src.shape {
             return Err(TRTError::ShapeMismatch);
@@ -76,4 +313,420 @@ impl Tensor {
 
         Ok(())
     }
+
+    /// Copies `src` into the start of this tensor, which may be larger, e.g.
+    /// a smaller input into a buffer allocated for the profile's max shape.
+    /// The bytes past `src.byte_size()` are left untouched.
+    pub fn copy_prefix_from(&mut self, src: &Self, stream: Option<&CuStream>) -> TRTResult<()> {
+        if src.numel() > self.numel() {
+            return Err(TRTError::ShapeMismatch);
+        }
+        if self.dtype != src.dtype {
+            return Err(TRTError::DTypeMismatch);
+        }
+        let src_ptr = unsafe { src.mem.get_raw() };
+        self.mem.copy_from_raw(src_ptr, src.byte_size(), stream)?;
+
+        Ok(())
+    }
+
+    /// Copies the tensor into `dst` and waits for the copy to finish, so the
+    /// buffer can be reused across calls without allocating.
+    pub fn to_host_bytes_into(&self, dst: &mut [u8], stream: Option<&CuStream>) -> TRTResult<()> {
+        let byte_size = self.byte_size();
+        if dst.len() != byte_size {
+            return Err(TRTError::BufferSizeMismatch(byte_size, dst.len()));
+        }
+        self.mem.copy_to_raw(dst.as_mut_ptr() as _, byte_size, stream)?;
+        stream.unwrap_or(&self.mem.stream).synchronize()?;
+
+        Ok(())
+    }
+}
+
+impl Drop for Tensor {
+    fn drop(&mut self) {
+        if self.location == MemoryLocation::Device && self.owns_memory {
+            unsafe { ManuallyDrop::drop(&mut self.mem) };
+        } else {
+            // The memory isn't freed here, but the stream clone it holds
+            // still has to be released.
+            unsafe { std::ptr::drop_in_place(&mut self.mem.stream) };
+        }
+    }
+}
+
+/// Typed, by-name access to the outputs of an inference.
+pub struct OutputMap<'a>(&'a HashMap<String, Tensor>);
+
+impl<'a> OutputMap<'a> {
+    pub fn get(&self, name: &str) -> TRTResult<&'a Tensor> {
+        match self.0.get(name) {
+            Some(tensor) => Ok(tensor),
+            None => Err(TRTError::UnknownTensor(name.to_string())),
+        }
+    }
+
+    /// Downloads output `name`, which must hold elements of type `T`, on
+    /// the tensor's own stream.
+    pub fn get_as<T: TensorElement>(&self, name: &str) -> TRTResult<Vec<T>> {
+        self.get(name)?.to_host_vec(None)
+    }
+
+    /// Downloads output `name` as `f32`, widening HALF and BF16 elements on
+    /// the host after the copy.
+    pub fn get_as_f32(&self, name: &str) -> TRTResult<Vec<f32>> {
+        let tensor = self.get(name)?;
+        let widen: fn(u16) -> f32 = match tensor.dtype() {
+            DataType::FLOAT => return tensor.to_host_vec(None),
+            DataType::HALF => f16_to_f32,
+            DataType::BF16 => bf16_to_f32,
+            _ => return Err(TRTError::DTypeMismatch),
+        };
+
+        let mut bytes = vec![0u8; tensor.byte_size()];
+        tensor.to_host_bytes_into(&mut bytes, None)?;
+
+        Ok(bytes
+            .chunks_exact(2)
+            .map(|bits| widen(u16::from_ne_bytes([bits[0], bits[1]])))
+            .collect())
+    }
+
+    pub fn get_shape(&self, name: &str) -> TRTResult<&'a Shape> {
+        Ok(self.get(name)?.shape())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'a str> {
+        self.0.keys().map(|name| name.as_str())
+    }
+}
+
+impl<'a> From<&'a HashMap<String, Tensor>> for OutputMap<'a> {
+    fn from(outputs: &'a HashMap<String, Tensor>) -> Self {
+        Self(outputs)
+    }
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = (bits as u32 & 0x8000) << 16;
+    let exp = (bits >> 10) & 0x1f;
+    let mantissa = bits as u32 & 0x3ff;
+
+    match exp {
+        // zero or subnormal, exactly mantissa * 2^-24
+        0 => {
+            let value = mantissa as f32 / (1 << 24) as f32;
+            f32::from_bits(sign | value.to_bits())
+        }
+        // infinity or NaN
+        0x1f => f32::from_bits(sign | 0x7f80_0000 | mantissa << 13),
+        _ => f32::from_bits(sign | (exp as u32 + 127 - 15) << 23 | mantissa << 13),
+    }
+}
+
+fn bf16_to_f32(bits: u16) -> f32 {
+    f32::from_bits((bits as u32) << 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cuda_rs::device::CuDevice;
+
+    #[test]
+    fn test_shape_display() {
+        assert_eq!(Shape(vec![1, 3, 224, 224]).to_string(), "[1, 3, 224, 224]");
+        assert_eq!(Shape(vec![1, -1, 224, 224]).to_string(), "[1, -1, 224, 224]");
+        assert_eq!(Shape(vec![]).to_string(), "[]");
+    }
+
+    #[test]
+    fn test_shape_prepend_append() {
+        let shape = Shape(vec![3, 224, 224]);
+        assert_eq!(shape.prepend(1).unwrap(), Shape(vec![1, 3, 224, 224]));
+        assert_eq!(shape.append(1).unwrap(), Shape(vec![3, 224, 224, 1]));
+        assert_eq!(Shape(vec![]).prepend(2).unwrap(), Shape(vec![2]));
+        assert_eq!(shape.prepend(-1).unwrap(), Shape(vec![-1, 3, 224, 224]));
+
+        assert!(matches!(shape.prepend(-2), Err(TRTError::InvalidShape(_))));
+        assert!(matches!(shape.append(-2), Err(TRTError::InvalidShape(_))));
+        let full = Shape(vec![1; Shape::MAX_DIMS]);
+        assert!(matches!(full.prepend(1), Err(TRTError::InvalidShape(_))));
+        assert!(matches!(full.append(1), Err(TRTError::InvalidShape(_))));
+    }
+
+    #[test]
+    fn test_shape_remove() {
+        let shape = Shape(vec![1, 3, 224, 224]);
+        assert_eq!(shape.remove(0).unwrap(), Shape(vec![3, 224, 224]));
+        assert_eq!(shape.remove(3).unwrap(), Shape(vec![1, 3, 224]));
+        assert!(matches!(shape.remove(4), Err(TRTError::InvalidShape(_))));
+    }
+
+    #[test]
+    fn test_shape_flatten_from() {
+        let shape = Shape(vec![2, 3, 4, 5]);
+        assert_eq!(shape.flatten_from(0).unwrap(), Shape(vec![120]));
+        assert_eq!(shape.flatten_from(1).unwrap(), Shape(vec![2, 60]));
+        assert_eq!(shape.flatten_from(3).unwrap(), shape);
+        assert_eq!(Shape(vec![-1, 3, -1]).flatten_from(1).unwrap(), Shape(vec![-1, -1]));
+        assert!(matches!(shape.flatten_from(4), Err(TRTError::InvalidShape(_))));
+
+        let overflowing = Shape(vec![1, 65536, 65536]);
+        assert!(matches!(overflowing.flatten_from(1), Err(TRTError::InvalidShape(_))));
+        let invalid = Shape(vec![2, -2, 3]);
+        assert!(matches!(invalid.flatten_from(0), Err(TRTError::InvalidShape(_))));
+    }
+
+    fn with_stream<F>(f: F) -> TRTResult<()>
+    where
+        F: FnOnce(&CuStream) -> TRTResult<()>,
+    {
+        cuda_rs::init()?;
+
+        let device = CuDevice::new(0)?;
+        let ctx = device.retain_primary_context()?;
+        let _guard = ctx.guard()?;
+        let stream = CuStream::new()?;
+
+        f(&stream)
+    }
+
+    #[test]
+    fn test_to_host_bytes_into() -> TRTResult<()> {
+        with_stream(|stream| {
+            let tensor = Tensor::empty(&Shape(vec![2, 8]), DataType::FLOAT, stream)?;
+            let mut dst = vec![0u8; tensor.byte_size()];
+
+            for i in 0..3 {
+                let src: Vec<u8> = (0..dst.len()).map(|j| (i * 7 + j) as u8).collect();
+                tensor.get_memory().copy_from_raw(src.as_ptr() as _, src.len(), Some(stream))?;
+                tensor.to_host_bytes_into(&mut dst, Some(stream))?;
+                assert_eq!(dst, src);
+            }
+
+            let mut short = vec![0u8; 4];
+            assert!(matches!(
+                tensor.to_host_bytes_into(&mut short, None),
+                Err(TRTError::BufferSizeMismatch(64, 4))
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_reset_shape_within_capacity() -> TRTResult<()> {
+        with_stream(|stream| {
+            let mut tensor = Tensor::empty(&Shape(vec![4, 3, 8, 8]), DataType::FLOAT, stream)?;
+            assert_eq!(tensor.capacity(), 4 * 3 * 8 * 8);
+
+            unsafe { tensor.reset_shape(&Shape(vec![2, 3, 8, 8]))? };
+            assert_eq!(tensor.numel(), 2 * 3 * 8 * 8);
+            assert_eq!(tensor.capacity(), 4 * 3 * 8 * 8);
+
+            // growing back within the allocation is fine
+            unsafe { tensor.reset_shape(&Shape(vec![4, 3, 8, 8]))? };
+            assert!(matches!(
+                unsafe { tensor.reset_shape(&Shape(vec![5, 3, 8, 8])) },
+                Err(TRTError::ResetShapesError)
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_i64_round_trip() -> TRTResult<()> {
+        with_stream(|stream| {
+            let token_ids: Vec<i64> = vec![101, 7592, 1010, 2088, 999, 102, 0, 0];
+            let shape = Shape(vec![2, 4]);
+            let tensor = Tensor::from_host_slice(&token_ids, &shape, stream)?;
+            assert_eq!(tensor.dtype(), DataType::INT64);
+            assert_eq!(tensor.byte_size(), 64);
+            assert_eq!(tensor.to_host_vec::<i64>(Some(stream))?, token_ids);
+
+            assert!(matches!(tensor.to_host_vec::<i32>(None), Err(TRTError::DTypeMismatch)));
+            assert!(matches!(
+                Tensor::from_host_slice(&token_ids[..6], &shape, stream),
+                Err(TRTError::BufferSizeMismatch(64, 48))
+            ));
+
+            let mask = vec![1i32, 1, 1, 1, 1, 1, 0, 0];
+            let tensor = Tensor::from_host_slice(&mask, &shape, stream)?;
+            assert_eq!(tensor.to_host_vec::<i32>(None)?, mask);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_compatible_with() -> TRTResult<()> {
+        with_stream(|stream| {
+            let shape = Shape(vec![1, 3, 4, 4]);
+            let a = Tensor::empty(&shape, DataType::FLOAT, stream)?;
+            let b = Tensor::empty(&shape, DataType::FLOAT, stream)?;
+            let c = Tensor::empty(&shape, DataType::HALF, stream)?;
+            let d = Tensor::empty(&Shape(vec![1, 3, 2, 8]), DataType::FLOAT, stream)?;
+
+            assert!(a.compatible_with(&b));
+            assert!(!a.compatible_with(&c));
+            assert!(!a.compatible_with(&d));
+            assert!(a.is_float_type() && !a.is_integer_type());
+            assert_eq!(c.dtype_ref(), &DataType::HALF);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_copy_prefix_from() -> TRTResult<()> {
+        with_stream(|stream| {
+            let data: Vec<f32> = (0..3 * 100 * 100).map(|i| i as f32).collect();
+            let src = Tensor::from_host_slice(&data, &Shape(vec![1, 3, 100, 100]), stream)?;
+            let mut dst = Tensor::empty(&Shape(vec![1, 3, 256, 256]), DataType::FLOAT, stream)?;
+
+            dst.copy_prefix_from(&src, Some(stream))?;
+            let copied = dst.to_host_vec::<f32>(Some(stream))?;
+            assert_eq!(&copied[..data.len()], data.as_slice());
+
+            let mut small = Tensor::empty(src.shape(), DataType::FLOAT, stream)?;
+            assert!(matches!(small.copy_prefix_from(&dst, None), Err(TRTError::ShapeMismatch)));
+            let half = Tensor::empty(&Shape(vec![1, 3, 100, 100]), DataType::HALF, stream)?;
+            assert!(matches!(dst.copy_prefix_from(&half, None), Err(TRTError::DTypeMismatch)));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_byte_size() -> TRTResult<()> {
+        with_stream(|stream| {
+            let shape = Shape(vec![2, 3, 5]);
+            for (dtype, byte_size) in [
+                (DataType::FLOAT, 120),
+                (DataType::HALF, 60),
+                (DataType::INT8, 30),
+                (DataType::INT4, 15),
+            ] {
+                let tensor = Tensor::empty(&shape, dtype, stream)?;
+                assert_eq!(tensor.byte_size(), byte_size);
+                assert_eq!(tensor.get_memory().size, byte_size);
+                assert_eq!(tensor.capacity(), 30);
+            }
+
+            // an odd number of INT4 elements rounds up
+            let tensor = Tensor::empty(&Shape(vec![1, 3]), DataType::INT4, stream)?;
+            assert_eq!(tensor.byte_size(), 2);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_copy_from_pinned_host() -> TRTResult<()> {
+        with_stream(|stream| {
+            let shape = Shape(vec![2, 3, 4]);
+            let data: Vec<f32> = (0..shape.size()).map(|i| i as f32 * 0.5).collect();
+
+            let mut host = HostMemory::new(std::mem::size_of_val(data.as_slice()))?;
+            host.as_mut_slice::<f32>().copy_from_slice(&data);
+            let pinned = Tensor::from_pinned_host(host, &shape, DataType::FLOAT, stream)?;
+            assert_eq!(pinned.location(), MemoryLocation::HostPinned);
+
+            let mut device = Tensor::empty(&shape, DataType::FLOAT, stream)?;
+            assert_eq!(device.location(), MemoryLocation::Device);
+            device.copy_from(&pinned, Some(stream))?;
+            assert_eq!(device.to_host_vec::<f32>(Some(stream))?, data);
+
+            let small = HostMemory::new(4)?;
+            assert!(matches!(
+                Tensor::from_pinned_host(small, &shape, DataType::FLOAT, stream),
+                Err(TRTError::BufferSizeMismatch(96, 4))
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_from_borrowed_ptr() -> TRTResult<()> {
+        with_stream(|stream| {
+            let data: Vec<f32> = (0..16).map(|i| i as f32).collect();
+            let external = DeviceMemory::new(std::mem::size_of_val(data.as_slice()), stream)?;
+            external.copy_from_raw(data.as_ptr() as _, external.size, Some(stream))?;
+
+            let shape = Shape(vec![4, 4]);
+            let ptr = unsafe { external.get_raw() } as usize;
+            let tensor = unsafe { Tensor::from_borrowed_ptr(ptr, &shape, DataType::FLOAT, stream) };
+            assert_eq!(tensor.to_host_vec::<f32>(Some(stream))?, data);
+            drop(tensor);
+
+            // the external buffer is still allocated and intact
+            let mut copied = vec![0f32; data.len()];
+            external.copy_to_raw(copied.as_mut_ptr() as _, external.size, Some(stream))?;
+            stream.synchronize()?;
+            assert_eq!(copied, data);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_output_map() -> TRTResult<()> {
+        with_stream(|stream| {
+            let shape = Shape(vec![1, 768]);
+            let features: Vec<f32> = (0..shape.size()).map(|i| i as f32).collect();
+            let outputs = HashMap::from([(
+                "features".to_string(),
+                Tensor::from_host_slice(&features, &shape, stream)?,
+            )]);
+            let outputs = OutputMap::from(&outputs);
+
+            let got = outputs.get_as::<f32>("features")?;
+            assert_eq!(got.len(), outputs.get_shape("features")?.size());
+            assert_eq!(got, features);
+            assert_eq!(outputs.names().collect::<Vec<_>>(), vec!["features"]);
+
+            assert!(matches!(outputs.get_as::<i32>("features"), Err(TRTError::DTypeMismatch)));
+            assert!(matches!(outputs.get_shape("missing"), Err(TRTError::UnknownTensor(_))));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_output_map_get_as_f32() -> TRTResult<()> {
+        with_stream(|stream| {
+            let half: [u16; 6] = [0x3c00, 0xc000, 0x3555, 0x7bff, 0x0001, 0x8000];
+            let expected = [1.0, -2.0, 0.333_251_95, 65504.0, 5.960_464_5e-8, -0.0];
+            let bf16: [u16; 3] = [0x3f80, 0xc020, 0x4049];
+
+            let mut outputs = HashMap::new();
+            for (name, dtype, bits) in [
+                ("half", DataType::HALF, &half[..]),
+                ("bf16", DataType::BF16, &bf16[..]),
+            ] {
+                let tensor = Tensor::empty(&Shape(vec![bits.len() as i32]), dtype, stream)?;
+                tensor.get_memory().copy_from_raw(
+                    bits.as_ptr() as _,
+                    std::mem::size_of_val(bits),
+                    Some(stream),
+                )?;
+                outputs.insert(name.to_string(), tensor);
+            }
+            stream.synchronize()?;
+            let outputs = OutputMap::from(&outputs);
+
+            let got = outputs.get_as_f32("half")?;
+            for (got, expected) in got.iter().zip(expected) {
+                assert!((got - expected).abs() <= 1e-6 * expected.abs().max(1.0));
+            }
+            assert!(got[5].is_sign_negative());
+            assert_eq!(outputs.get_as_f32("bf16")?, vec![1.0, -2.5, 3.140625]);
+
+            Ok(())
+        })
+    }
 }
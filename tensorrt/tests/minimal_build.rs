@@ -0,0 +1,60 @@
+//! Builds against the library alone: this target must compile without the
+//! `examples` feature, i.e. without clap or tch. It also checks that each
+//! optional feature adds its API exactly when it is enabled.
+
+use tensorrt::{DataType, Shape, TRTEngine, TRTError, TRTResult};
+use tensorrt_rs_sys::logger::Logger;
+
+// Stand-ins for the feature-gated items. Inherent functions and glob imports
+// in an inner scope take precedence, so these only resolve if the real item
+// is missing.
+#[allow(dead_code)]
+mod absent {
+    pub struct ImageDirCalibrator;
+}
+
+#[allow(dead_code)]
+trait Absent {
+    fn from_onnx<P>() {}
+
+    fn tracing() {}
+
+    fn inference_async_tokio() {}
+}
+
+impl<T> Absent for T {}
+
+fn type_name_of<T>(_: T) -> &'static str {
+    std::any::type_name::<T>()
+}
+
+#[test]
+fn test_library_without_examples_feature() {
+    let shape = Shape(vec![1, 3, 32, 32]);
+    assert_eq!(shape.size(), 3 * 32 * 32);
+    assert_eq!(DataType::FLOAT.get_byte_size(shape.size()), 4 * 3 * 32 * 32);
+
+    let res: TRTResult<()> = Err(TRTError::RuntimeCreationError);
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_feature_gated_apis() {
+    let from_onnx = type_name_of(TRTEngine::from_onnx::<&str>);
+    assert_eq!(!from_onnx.contains("Absent"), cfg!(feature = "onnx"), "{}", from_onnx);
+
+    let tracing = type_name_of(Logger::tracing);
+    assert_eq!(!tracing.contains("Absent"), cfg!(feature = "tracing"), "{}", tracing);
+
+    let inference = type_name_of(TRTEngine::inference_async_tokio);
+    assert_eq!(!inference.contains("Absent"), cfg!(feature = "tokio"), "{}", inference);
+
+    #[allow(unused_imports)]
+    use absent::*;
+    let calibrator = {
+        #[allow(unused_imports)]
+        use tensorrt::*;
+        std::any::type_name::<ImageDirCalibrator>()
+    };
+    assert_eq!(calibrator.starts_with("tensorrt::"), cfg!(feature = "image"), "{}", calibrator);
+}